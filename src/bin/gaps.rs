@@ -0,0 +1,119 @@
+// Reports missing, unprocessed and unindexed ledgers as ranges, to backfill with the ingest
+// binary. Unlike /admin/gaps, ranges of any length may be scanned.
+
+#[path = "../gaps/mod.rs"]
+mod gaps;
+#[allow(dead_code)]
+#[path = "../schema/mod.rs"]
+mod schema;
+// The shared gaps module reads its constants from `crate::utils::consts`
+#[path = "../utils"]
+mod utils {
+    #[allow(dead_code)]
+    #[path = "consts.rs"]
+    pub mod consts;
+}
+
+use crate::gaps::{find_gaps, latest_ledger_index, LedgerGaps, LedgerRange};
+use crate::utils::consts::{KEYSPACE, SCYLLA_NODES};
+use clap::Parser;
+use scylla::{Session, SessionBuilder};
+
+/// Scans the XRPL Data API tables for gaps in a range of ledgers.
+#[derive(Parser)]
+struct Args {
+    /// First ledger to scan
+    #[arg(long)]
+    start: i64,
+
+    /// Last ledger to scan, defaults to the latest ledger
+    #[arg(long)]
+    end: Option<i64>,
+
+    /// Print the gaps as JSON rather than one range per line
+    #[arg(long)]
+    json: bool,
+
+    /// Keyspace to scan
+    #[arg(long, default_value = KEYSPACE)]
+    keyspace: String,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+    if !schema::is_valid_keyspace(&args.keyspace) {
+        eprintln!("Invalid keyspace {}", args.keyspace);
+        std::process::exit(1);
+    }
+
+    println!("Connecting to scylla.");
+    let session: Session = SessionBuilder::new()
+        .known_nodes(SCYLLA_NODES)
+        .build()
+        .await
+        .expect("Failed to connect to scylla nodes");
+    session
+        .use_keyspace(&args.keyspace, true)
+        .await
+        .expect("Unable to use keyspace");
+
+    let end = match args.end {
+        Some(end) => end,
+        None => match latest_ledger_index(&session).await {
+            Ok(Some(latest)) => latest,
+            Ok(None) => {
+                eprintln!("No ledgers closed today or yesterday, set --end");
+                std::process::exit(1);
+            }
+            Err(err) => {
+                eprintln!("Finding the latest ledger failed: {}", err);
+                std::process::exit(1);
+            }
+        },
+    };
+
+    let gaps = match find_gaps(&session, args.start, end).await {
+        Ok(gaps) => gaps,
+        Err(err) => {
+            eprintln!("Gap scan failed: {}", err);
+            std::process::exit(1);
+        }
+    };
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&gaps).expect("Failed to serialize gaps")
+        );
+    } else {
+        print_gaps(&gaps);
+    }
+}
+
+// One line per range, such as "missing 1000-1099", then totals
+fn print_gaps(gaps: &LedgerGaps) {
+    for (kind, ranges) in [
+        ("missing", &gaps.missing),
+        ("unprocessed", &gaps.unprocessed),
+        ("unindexed", &gaps.unindexed),
+    ] {
+        for range in ranges {
+            println!("{} {}", kind, format_range(range));
+        }
+    }
+    println!(
+        "Found {} gaps covering {} ledgers in ledgers {} to {}",
+        gaps.gap_count(),
+        gaps.ledger_count(),
+        gaps.start,
+        gaps.end
+    );
+}
+
+fn format_range(range: &LedgerRange) -> String {
+    if range.start == range.end {
+        range.start.to_string()
+    } else {
+        format!("{}-{}", range.start, range.end)
+    }
+}
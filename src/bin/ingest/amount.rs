@@ -0,0 +1,69 @@
+use crate::decimal::Decimal;
+use serde_json::Value;
+
+// XRP amounts are written in XRP, rippled gives them in drops
+const XRP_EXPONENT: i32 = -6;
+
+/// An amount as stored in payments and balance changes. XRP has currency "XRP" and an empty
+/// issuer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Amount {
+    pub currency: String,
+    pub issuer: String,
+    pub value: Decimal,
+}
+
+impl Decimal {
+    /// XRP value of an amount in drops.
+    pub fn from_drops(drops: i64) -> Self {
+        Decimal::new(drops, XRP_EXPONENT)
+    }
+
+    pub fn sub(&self, other: &Decimal) -> Self {
+        self.add(&other.neg())
+    }
+}
+
+impl Amount {
+    pub fn xrp(value: Decimal) -> Self {
+        Amount {
+            currency: "XRP".to_string(),
+            issuer: String::new(),
+            value,
+        }
+    }
+
+    /// Reads an amount in rippled's JSON: drops as a string, a token as an object with
+    /// currency, issuer and value, or an MPT with its issuance ID in place of the currency.
+    pub fn from_json(amount: &Value) -> Option<Self> {
+        match amount {
+            Value::String(drops) => drops
+                .parse::<i64>()
+                .ok()
+                .map(Decimal::from_drops)
+                .map(Amount::xrp),
+            Value::Object(fields) => {
+                let value = Decimal::parse(fields.get("value")?.as_str()?)?;
+                let currency = fields
+                    .get("currency")
+                    .or_else(|| fields.get("mpt_issuance_id"))?
+                    .as_str()?;
+                let issuer = fields
+                    .get("issuer")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                Some(Amount {
+                    currency: currency.to_string(),
+                    issuer: issuer.to_string(),
+                    value,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Drops of an XRP amount in rippled's JSON, such as a transaction's fee.
+pub fn drops(amount: Option<&Value>) -> Option<i64> {
+    amount?.as_str()?.parse::<i64>().ok()
+}
@@ -0,0 +1,498 @@
+use crate::codes::*;
+use crate::decimal::Decimal;
+use crate::error::IngestError;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256, Sha512};
+
+// Decodes rippled's binary format into the JSON rippled returns for the same transactions,
+// metadata and ledger headers, so both forms derive the same rows.
+
+const ADDRESS_ALPHABET: &[u8] = b"rpshnaf39wBUDNEGHJKLM4PQRST7VWXYZ2bcdeCg65jkm8oFqi1tuvAxyz";
+// Prefixes hashed before the data, "TXN\0" for transactions and "LWR\0" for ledgers
+const TRANSACTION_ID_PREFIX: &[u8] = b"TXN\0";
+const LEDGER_HASH_PREFIX: &[u8] = b"LWR\0";
+// Sequence, coins, three hashes, two close times, resolution and flags
+const LEDGER_HEADER_LEN: usize = 4 + 8 + 32 * 3 + 4 + 4 + 1 + 1;
+
+// Amount and path step flags
+const AMOUNT_NOT_XRP: u8 = 0x80;
+const AMOUNT_POSITIVE: u8 = 0x40;
+const AMOUNT_MPT: u8 = 0x20;
+const PATH_END: u8 = 0x00;
+const PATH_BOUNDARY: u8 = 0xff;
+const STEP_ACCOUNT: u8 = 0x01;
+const STEP_CURRENCY: u8 = 0x10;
+const STEP_ISSUER: u8 = 0x20;
+
+/// Decodes a transaction or its metadata, given as hex.
+pub fn decode_object(blob: &str) -> Result<Map<String, Value>, IngestError> {
+    let bytes = decode_hex(blob)?;
+    Parser::new(&bytes).object(false)
+}
+
+/// ID of a transaction given as hex, the hash rippled shows for it.
+pub fn transaction_hash(blob: &str) -> Result<String, IngestError> {
+    let bytes = decode_hex(blob)?;
+    Ok(sha512_half(&[TRANSACTION_ID_PREFIX, &bytes]))
+}
+
+/// Decodes the `ledger_data` of a binary ledger into the header fields of a JSON ledger.
+pub fn decode_ledger_header(blob: &str) -> Result<Map<String, Value>, IngestError> {
+    let bytes = decode_hex(blob)?;
+    if bytes.len() < LEDGER_HEADER_LEN {
+        return Err(IngestError::Binary(format!(
+            "ledger header of {} bytes",
+            bytes.len()
+        )));
+    }
+    let mut parser = Parser::new(&bytes);
+    let mut header = Map::new();
+    header.insert("ledger_index".to_string(), json!(parser.uint(4)?));
+    header.insert(
+        "total_coins".to_string(),
+        json!(parser.uint(8)?.to_string()),
+    );
+    header.insert("parent_hash".to_string(), json!(parser.hex(32)?));
+    header.insert("transaction_hash".to_string(), json!(parser.hex(32)?));
+    header.insert("account_hash".to_string(), json!(parser.hex(32)?));
+    header.insert("parent_close_time".to_string(), json!(parser.uint(4)?));
+    header.insert("close_time".to_string(), json!(parser.uint(4)?));
+    header.insert("close_time_resolution".to_string(), json!(parser.uint(1)?));
+    header.insert("close_flags".to_string(), json!(parser.uint(1)?));
+    header.insert(
+        "ledger_hash".to_string(),
+        json!(sha512_half(&[
+            LEDGER_HASH_PREFIX,
+            &bytes[..LEDGER_HEADER_LEN]
+        ])),
+    );
+    Ok(header)
+}
+
+/// Address of a 20 byte account ID, base58 with a version byte of 0 and a checksum.
+pub fn encode_address(account_id: &[u8]) -> String {
+    let mut payload = Vec::with_capacity(25);
+    payload.push(0);
+    payload.extend_from_slice(account_id);
+    let checksum = Sha256::digest(Sha256::digest(&payload));
+    payload.extend_from_slice(&checksum[..4]);
+
+    // Base58 digits, least significant first
+    let mut digits: Vec<u8> = Vec::new();
+    for byte in &payload {
+        let mut carry = *byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let zeros = payload.iter().take_while(|byte| **byte == 0).count();
+    std::iter::repeat_n(ADDRESS_ALPHABET[0], zeros)
+        .chain(
+            digits
+                .iter()
+                .rev()
+                .map(|digit| ADDRESS_ALPHABET[*digit as usize]),
+        )
+        .map(char::from)
+        .collect()
+}
+
+fn decode_hex(blob: &str) -> Result<Vec<u8>, IngestError> {
+    hex::decode(blob).map_err(|err| IngestError::Binary(err.to_string()))
+}
+
+fn sha512_half(parts: &[&[u8]]) -> String {
+    let mut hasher = Sha512::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hex::encode_upper(&hasher.finalize()[..32])
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Parser { bytes, position: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], IngestError> {
+        let end = self.position + len;
+        if end > self.bytes.len() {
+            return Err(IngestError::Binary(format!(
+                "{} bytes needed at offset {} of {}",
+                len,
+                self.position,
+                self.bytes.len()
+            )));
+        }
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, IngestError> {
+        Ok(self.take(1)?[0])
+    }
+
+    // Big endian unsigned integer of `len` bytes
+    fn uint(&mut self, len: usize) -> Result<u64, IngestError> {
+        Ok(self
+            .take(len)?
+            .iter()
+            .fold(0u64, |value, byte| (value << 8) | *byte as u64))
+    }
+
+    fn hex(&mut self, len: usize) -> Result<String, IngestError> {
+        Ok(hex::encode_upper(self.take(len)?))
+    }
+
+    // Type and field codes of 1 to 3 bytes, codes above 15 are in their own byte
+    fn field_id(&mut self) -> Result<(u16, u16), IngestError> {
+        let byte = self.byte()?;
+        let mut type_code = (byte >> 4) as u16;
+        let mut field_code = (byte & 0x0f) as u16;
+        if type_code == 0 {
+            type_code = self.byte()? as u16;
+        }
+        if field_code == 0 {
+            field_code = self.byte()? as u16;
+        }
+        Ok((type_code, field_code))
+    }
+
+    // Length prefix of variable length fields
+    fn length(&mut self) -> Result<usize, IngestError> {
+        let first = self.byte()? as usize;
+        match first {
+            0..=192 => Ok(first),
+            193..=240 => Ok(193 + (first - 193) * 256 + self.byte()? as usize),
+            241..=254 => {
+                let rest = self.uint(2)? as usize;
+                Ok(12481 + (first - 241) * 65536 + rest)
+            }
+            _ => Err(IngestError::Binary("invalid length prefix".to_string())),
+        }
+    }
+
+    // A nested object ends with a marker, the top level object with the data
+    fn object(&mut self, nested: bool) -> Result<Map<String, Value>, IngestError> {
+        let mut object = Map::new();
+        loop {
+            if !nested && self.position == self.bytes.len() {
+                break;
+            }
+            let id = self.field_id()?;
+            if nested && id == OBJECT_END {
+                break;
+            }
+            let name = field_name(id.0, id.1);
+            let value = self.value(id, &name)?;
+            object.insert(name, value);
+        }
+        Ok(object)
+    }
+
+    // Elements are objects wrapped in their field name
+    fn array(&mut self) -> Result<Vec<Value>, IngestError> {
+        let mut array = Vec::new();
+        loop {
+            let id = self.field_id()?;
+            if id == ARRAY_END {
+                break;
+            }
+            if id.0 != OBJECT {
+                return Err(IngestError::Binary(format!(
+                    "array element of type {}",
+                    id.0
+                )));
+            }
+            let name = field_name(id.0, id.1);
+            let element = self.object(true)?;
+            array.push(json!({ name: element }));
+        }
+        Ok(array)
+    }
+
+    fn value(
+        &mut self,
+        (type_code, field_code): (u16, u16),
+        name: &str,
+    ) -> Result<Value, IngestError> {
+        let value = match type_code {
+            UINT8 => {
+                let code = self.byte()?;
+                match name {
+                    "TransactionResult" => named(transaction_result_name(code as i16), code),
+                    _ => json!(code),
+                }
+            }
+            UINT16 => {
+                let code = self.uint(2)? as u16;
+                match name {
+                    "TransactionType" => named(transaction_type_name(code), code),
+                    "LedgerEntryType" => named(ledger_entry_type_name(code), code),
+                    _ => json!(code),
+                }
+            }
+            UINT32 => json!(self.uint(4)?),
+            UINT64 => json!(self.hex(8)?),
+            HASH128 => json!(self.hex(16)?),
+            HASH160 => json!(self.hex(20)?),
+            HASH256 => json!(self.hex(32)?),
+            UINT96 => json!(self.hex(12)?),
+            UINT192 => json!(self.hex(24)?),
+            UINT384 => json!(self.hex(48)?),
+            UINT512 => json!(self.hex(64)?),
+            AMOUNT => self.amount()?,
+            BLOB => {
+                let len = self.length()?;
+                json!(self.hex(len)?)
+            }
+            ACCOUNT_ID => json!(self.account()?),
+            NUMBER => {
+                let mantissa = self.uint(8)? as i64;
+                let exponent = self.uint(4)? as u32 as i32;
+                json!(Decimal::new(mantissa, exponent).to_string())
+            }
+            INT32 => json!(self.uint(4)? as u32 as i32),
+            INT64 => json!(self.uint(8)? as i64),
+            OBJECT => Value::Object(self.object(true)?),
+            ARRAY => Value::Array(self.array()?),
+            PATH_SET => self.path_set()?,
+            VECTOR256 => {
+                let len = self.length()?;
+                let hashes = (0..len / 32)
+                    .map(|_| self.hex(32))
+                    .collect::<Result<Vec<String>, IngestError>>()?;
+                json!(hashes)
+            }
+            ISSUE => self.issue()?,
+            XCHAIN_BRIDGE => json!({
+                "LockingChainDoor": self.account()?,
+                "LockingChainIssue": self.issue()?,
+                "IssuingChainDoor": self.account()?,
+                "IssuingChainIssue": self.issue()?,
+            }),
+            CURRENCY => json!(currency_code(self.take(20)?)),
+            _ => {
+                return Err(IngestError::Binary(format!(
+                    "unknown type {} of field {}",
+                    type_code, field_code
+                )))
+            }
+        };
+        Ok(value)
+    }
+
+    fn account(&mut self) -> Result<String, IngestError> {
+        let len = self.length()?;
+        Ok(encode_address(self.take(len)?))
+    }
+
+    fn amount(&mut self) -> Result<Value, IngestError> {
+        let first = self.bytes.get(self.position).copied().unwrap_or_default();
+        if first & AMOUNT_NOT_XRP == 0 {
+            if first & AMOUNT_MPT != 0 {
+                self.byte()?;
+                let value = self.uint(8)?;
+                let issuance_id = self.hex(24)?;
+                return Ok(json!({ "mpt_issuance_id": issuance_id, "value": value.to_string() }));
+            }
+            let value = self.uint(8)?;
+            let drops = (value & 0x3fff_ffff_ffff_ffff) as i64;
+            let drops = if first & AMOUNT_POSITIVE == 0 {
+                -drops
+            } else {
+                drops
+            };
+            return Ok(json!(drops.to_string()));
+        }
+
+        let value = self.uint(8)?;
+        let mantissa = (value & 0x003f_ffff_ffff_ffff) as i64;
+        let exponent = ((value >> 54) & 0xff) as i32 - 97;
+        let decimal = if mantissa == 0 {
+            Decimal::zero()
+        } else if first & AMOUNT_POSITIVE == 0 {
+            Decimal::new(-mantissa, exponent)
+        } else {
+            Decimal::new(mantissa, exponent)
+        };
+        let currency = currency_code(self.take(20)?);
+        let issuer = encode_address(self.take(20)?);
+        Ok(json!({ "currency": currency, "issuer": issuer, "value": decimal.to_string() }))
+    }
+
+    fn issue(&mut self) -> Result<Value, IngestError> {
+        let currency = currency_code(self.take(20)?);
+        if currency == "XRP" {
+            return Ok(json!({ "currency": currency }));
+        }
+        let issuer = encode_address(self.take(20)?);
+        Ok(json!({ "currency": currency, "issuer": issuer }))
+    }
+
+    fn path_set(&mut self) -> Result<Value, IngestError> {
+        let mut paths = Vec::new();
+        let mut path = Vec::new();
+        loop {
+            let flags = self.byte()?;
+            if flags == PATH_END || flags == PATH_BOUNDARY {
+                paths.push(Value::Array(std::mem::take(&mut path)));
+                if flags == PATH_END {
+                    break;
+                }
+                continue;
+            }
+            let mut step = Map::new();
+            if flags & STEP_ACCOUNT != 0 {
+                step.insert("account".to_string(), json!(encode_address(self.take(20)?)));
+            }
+            if flags & STEP_CURRENCY != 0 {
+                step.insert("currency".to_string(), json!(currency_code(self.take(20)?)));
+            }
+            if flags & STEP_ISSUER != 0 {
+                step.insert("issuer".to_string(), json!(encode_address(self.take(20)?)));
+            }
+            path.push(Value::Object(step));
+        }
+        Ok(Value::Array(paths))
+    }
+}
+
+// Name of a code, or the code itself when the table does not know it
+fn named(name: Option<&str>, code: impl Into<Value>) -> Value {
+    name.map(Value::from).unwrap_or_else(|| code.into())
+}
+
+// Standard codes are three ASCII characters at bytes 12 to 14, others are shown as hex
+fn currency_code(bytes: &[u8]) -> String {
+    if bytes.iter().all(|byte| *byte == 0) {
+        return "XRP".to_string();
+    }
+    let standard = bytes[0] == 0
+        && bytes[..12]
+            .iter()
+            .chain(&bytes[15..])
+            .all(|byte| *byte == 0)
+        && bytes[12..15].iter().all(|byte| byte.is_ascii_graphic());
+    if standard {
+        String::from_utf8_lossy(&bytes[12..15]).to_string()
+    } else {
+        hex::encode_upper(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_object, encode_address, Parser};
+    use serde_json::json;
+
+    const GENESIS_ID: &str = "B5F762798A53D543A014CAF8B297CFF8F2F937E8";
+
+    fn field_id(bytes: &[u8]) -> (u16, u16) {
+        Parser::new(bytes).field_id().unwrap()
+    }
+
+    fn length(bytes: &[u8]) -> usize {
+        Parser::new(bytes).length().unwrap()
+    }
+
+    #[test]
+    fn decodes_field_ids() {
+        // Type and field codes below 16 share a byte
+        assert_eq!(field_id(&[0x12]), (1, 2));
+        assert_eq!(field_id(&[0x81]), (8, 1));
+        // Larger codes follow in their own byte, the type's first
+        assert_eq!(field_id(&[0x20, 0x1b]), (2, 27));
+        assert_eq!(field_id(&[0x01, 0x11]), (17, 1));
+        assert_eq!(field_id(&[0x00, 0x11, 0x22]), (17, 34));
+    }
+
+    #[test]
+    fn decodes_length_prefixes() {
+        assert_eq!(length(&[0x00]), 0);
+        assert_eq!(length(&[0xc0]), 192);
+        assert_eq!(length(&[0xc1, 0x00]), 193);
+        assert_eq!(length(&[0xf0, 0xff]), 12480);
+        assert_eq!(length(&[0xf1, 0x00, 0x00]), 12481);
+        assert_eq!(length(&[0xfe, 0xd4, 0x17]), 918744);
+        assert!(Parser::new(&[0xff]).length().is_err());
+        assert!(Parser::new(&[0xc1]).length().is_err());
+    }
+
+    #[test]
+    fn encodes_addresses() {
+        assert_eq!(encode_address(&[0; 20]), "rrrrrrrrrrrrrrrrrrrrrhoLvTp");
+        let mut one = [0; 20];
+        one[19] = 1;
+        assert_eq!(encode_address(&one), "rrrrrrrrrrrrrrrrrrrrBZbvji");
+        assert_eq!(
+            encode_address(&hex::decode(GENESIS_ID).unwrap()),
+            "rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh"
+        );
+    }
+
+    #[test]
+    fn decodes_a_payment() {
+        let blob = [
+            // TransactionType and Sequence
+            "120000",
+            "2400000005",
+            // Amount and Fee in drops
+            "614000000005F5E100",
+            "68400000000000000C",
+            // Account and Destination, length prefixed
+            "8114",
+            GENESIS_ID,
+            "8314",
+            &"11".repeat(20),
+        ]
+        .concat();
+        assert_eq!(
+            json!(decode_object(&blob).unwrap()),
+            json!({
+                "TransactionType": "Payment",
+                "Sequence": 5,
+                "Amount": "100000000",
+                "Fee": "12",
+                "Account": "rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh",
+                "Destination": "rpZNAnHcvr6TbaY7QJa9yrVfu6coDz9pPH",
+            })
+        );
+    }
+
+    #[test]
+    fn decodes_token_amounts() {
+        // 1050000000000000e-14 of USD, issued by an account of 0x22 bytes
+        let blob = [
+            "61",
+            "D4C3BAF82D03A000",
+            &"00".repeat(12),
+            "555344",
+            &"00".repeat(5),
+            &"22".repeat(20),
+        ]
+        .concat();
+        assert_eq!(
+            json!(decode_object(&blob).unwrap()),
+            json!({
+                "Amount": {
+                    "currency": "USD",
+                    "issuer": "rhf7192NqpPvBUnAobBJAryNFQNbPKz11w",
+                    "value": "10.5",
+                },
+            })
+        );
+    }
+}
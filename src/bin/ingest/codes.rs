@@ -0,0 +1,422 @@
+// Names rippled uses in JSON for the codes of its binary format. Fields that are missing from
+// the table are decoded under a placeholder name, see `field_name`.
+
+pub static TRANSACTION_TYPES: &[(u16, &str)] = &[
+    (0, "Payment"),
+    (1, "EscrowCreate"),
+    (2, "EscrowFinish"),
+    (3, "AccountSet"),
+    (4, "EscrowCancel"),
+    (5, "SetRegularKey"),
+    (6, "NickNameSet"),
+    (7, "OfferCreate"),
+    (8, "OfferCancel"),
+    (9, "Contract"),
+    (10, "TicketCreate"),
+    (12, "SignerListSet"),
+    (13, "PaymentChannelCreate"),
+    (14, "PaymentChannelFund"),
+    (15, "PaymentChannelClaim"),
+    (16, "CheckCreate"),
+    (17, "CheckCash"),
+    (18, "CheckCancel"),
+    (19, "DepositPreauth"),
+    (20, "TrustSet"),
+    (21, "AccountDelete"),
+    (22, "SetHook"),
+    (25, "NFTokenMint"),
+    (26, "NFTokenBurn"),
+    (27, "NFTokenCreateOffer"),
+    (28, "NFTokenCancelOffer"),
+    (29, "NFTokenAcceptOffer"),
+    (30, "Clawback"),
+    (35, "AMMCreate"),
+    (36, "AMMDeposit"),
+    (37, "AMMWithdraw"),
+    (38, "AMMVote"),
+    (39, "AMMBid"),
+    (40, "AMMDelete"),
+    (41, "XChainCreateClaimID"),
+    (42, "XChainCommit"),
+    (43, "XChainClaim"),
+    (44, "XChainAccountCreateCommit"),
+    (45, "XChainAddClaimAttestation"),
+    (46, "XChainAddAccountCreateAttestation"),
+    (47, "XChainModifyBridge"),
+    (48, "XChainCreateBridge"),
+    (49, "DIDSet"),
+    (50, "DIDDelete"),
+    (51, "OracleSet"),
+    (52, "OracleDelete"),
+    (100, "EnableAmendment"),
+    (101, "SetFee"),
+    (102, "UNLModify"),
+];
+
+pub static LEDGER_ENTRY_TYPES: &[(u16, &str)] = &[
+    (0x0037, "NFTokenOffer"),
+    (0x0043, "Check"),
+    (0x0049, "DID"),
+    (0x004e, "NegativeUNL"),
+    (0x0050, "NFTokenPage"),
+    (0x0053, "SignerList"),
+    (0x0054, "Ticket"),
+    (0x0061, "AccountRoot"),
+    (0x0064, "DirectoryNode"),
+    (0x0066, "Amendments"),
+    (0x0068, "LedgerHashes"),
+    (0x0069, "Bridge"),
+    (0x006f, "Offer"),
+    (0x0070, "DepositPreauth"),
+    (0x0071, "XChainOwnedClaimID"),
+    (0x0072, "RippleState"),
+    (0x0073, "FeeSettings"),
+    (0x0074, "XChainOwnedCreateAccountClaimID"),
+    (0x0075, "Escrow"),
+    (0x0078, "PayChannel"),
+    (0x0079, "AMM"),
+    (0x0080, "Oracle"),
+];
+
+// Only tes and tec results are included in validated ledgers
+pub static TRANSACTION_RESULTS: &[(i16, &str)] = &[
+    (0, "tesSUCCESS"),
+    (100, "tecCLAIM"),
+    (101, "tecPATH_PARTIAL"),
+    (102, "tecUNFUNDED_ADD"),
+    (103, "tecUNFUNDED_OFFER"),
+    (104, "tecUNFUNDED_PAYMENT"),
+    (105, "tecFAILED_PROCESSING"),
+    (121, "tecDIR_FULL"),
+    (122, "tecINSUF_RESERVE_LINE"),
+    (123, "tecINSUF_RESERVE_OFFER"),
+    (124, "tecNO_DST"),
+    (125, "tecNO_DST_INSUF_XRP"),
+    (126, "tecNO_LINE_INSUF_RESERVE"),
+    (127, "tecNO_LINE_REDUNDANT"),
+    (128, "tecPATH_DRY"),
+    (129, "tecUNFUNDED"),
+    (130, "tecNO_ALTERNATIVE_KEY"),
+    (131, "tecNO_REGULAR_KEY"),
+    (132, "tecOWNERS"),
+    (133, "tecNO_ISSUER"),
+    (134, "tecNO_AUTH"),
+    (135, "tecNO_LINE"),
+    (136, "tecINSUFF_FEE"),
+    (137, "tecFROZEN"),
+    (138, "tecNO_TARGET"),
+    (139, "tecNO_PERMISSION"),
+    (140, "tecNO_ENTRY"),
+    (141, "tecINSUFFICIENT_RESERVE"),
+    (142, "tecNEED_MASTER_KEY"),
+    (143, "tecDST_TAG_NEEDED"),
+    (144, "tecINTERNAL"),
+    (145, "tecOVERSIZE"),
+    (146, "tecCRYPTOCONDITION_ERROR"),
+    (147, "tecINVARIANT_FAILED"),
+    (148, "tecEXPIRED"),
+    (149, "tecDUPLICATE"),
+    (150, "tecKILLED"),
+    (151, "tecHAS_OBLIGATIONS"),
+    (152, "tecTOO_SOON"),
+    (153, "tecHOOK_REJECTED"),
+    (154, "tecMAX_SEQUENCE_REACHED"),
+    (155, "tecNO_SUITABLE_NFTOKEN_PAGE"),
+    (156, "tecNFTOKEN_BUY_SELL_MISMATCH"),
+    (157, "tecNFTOKEN_OFFER_TYPE_MISMATCH"),
+    (158, "tecCANT_ACCEPT_OWN_NFTOKEN_OFFER"),
+    (159, "tecINSUFFICIENT_FUNDS"),
+    (160, "tecOBJECT_NOT_FOUND"),
+    (161, "tecINSUFFICIENT_PAYMENT"),
+    (162, "tecUNFUNDED_AMM"),
+    (163, "tecAMM_BALANCE"),
+    (164, "tecAMM_FAILED"),
+    (165, "tecAMM_INVALID_TOKENS"),
+    (166, "tecAMM_EMPTY"),
+    (167, "tecAMM_NOT_EMPTY"),
+    (168, "tecAMM_ACCOUNT"),
+    (169, "tecINCOMPLETE"),
+    (170, "tecXCHAIN_BAD_TRANSFER_ISSUE"),
+    (171, "tecXCHAIN_NO_CLAIM_ID"),
+    (172, "tecXCHAIN_BAD_CLAIM_ID"),
+    (173, "tecXCHAIN_CLAIM_NO_QUORUM"),
+    (174, "tecXCHAIN_PROOF_UNKNOWN_KEY"),
+    (175, "tecXCHAIN_CREATE_ACCOUNT_NONXRP_ISSUE"),
+    (176, "tecXCHAIN_WRONG_CHAIN"),
+    (177, "tecXCHAIN_REWARD_MISMATCH"),
+    (178, "tecXCHAIN_NO_SIGNERS_LIST"),
+    (179, "tecXCHAIN_SENDING_ACCOUNT_MISMATCH"),
+    (180, "tecXCHAIN_INSUFF_CREATE_AMOUNT"),
+    (181, "tecXCHAIN_ACCOUNT_CREATE_PAST"),
+    (182, "tecXCHAIN_ACCOUNT_CREATE_TOO_MANY"),
+    (183, "tecXCHAIN_PAYMENT_FAILED"),
+    (184, "tecXCHAIN_SELF_COMMIT"),
+    (185, "tecXCHAIN_BAD_PUBLIC_KEY_ACCOUNT_PAIR"),
+    (186, "tecXCHAIN_CREATE_ACCOUNT_DISABLED"),
+    (187, "tecEMPTY_DID"),
+    (188, "tecINVALID_UPDATE_TIME"),
+    (189, "tecTOKEN_PAIR_NOT_FOUND"),
+    (190, "tecARRAY_EMPTY"),
+    (191, "tecARRAY_TOO_LARGE"),
+];
+
+// Type codes of the binary format
+pub const UINT16: u16 = 1;
+pub const UINT32: u16 = 2;
+pub const UINT64: u16 = 3;
+pub const HASH128: u16 = 4;
+pub const HASH256: u16 = 5;
+pub const AMOUNT: u16 = 6;
+pub const BLOB: u16 = 7;
+pub const ACCOUNT_ID: u16 = 8;
+pub const NUMBER: u16 = 9;
+pub const INT32: u16 = 10;
+pub const INT64: u16 = 11;
+pub const OBJECT: u16 = 14;
+pub const ARRAY: u16 = 15;
+pub const UINT8: u16 = 16;
+pub const HASH160: u16 = 17;
+pub const PATH_SET: u16 = 18;
+pub const VECTOR256: u16 = 19;
+pub const UINT96: u16 = 20;
+pub const UINT192: u16 = 21;
+pub const UINT384: u16 = 22;
+pub const UINT512: u16 = 23;
+pub const ISSUE: u16 = 24;
+pub const XCHAIN_BRIDGE: u16 = 25;
+pub const CURRENCY: u16 = 26;
+
+// Ends the fields of a nested object or the elements of an array
+pub const OBJECT_END: (u16, u16) = (OBJECT, 1);
+pub const ARRAY_END: (u16, u16) = (ARRAY, 1);
+
+// (type code, field code, name)
+static FIELDS: &[(u16, u16, &str)] = &[
+    (UINT8, 1, "CloseResolution"),
+    (UINT8, 2, "Method"),
+    (UINT8, 3, "TransactionResult"),
+    (UINT8, 16, "TickSize"),
+    (UINT8, 17, "UNLModifyDisabling"),
+    (UINT16, 1, "LedgerEntryType"),
+    (UINT16, 2, "TransactionType"),
+    (UINT16, 3, "SignerWeight"),
+    (UINT16, 4, "TransferFee"),
+    (UINT16, 5, "TradingFee"),
+    (UINT16, 6, "DiscountedFee"),
+    (UINT16, 16, "Version"),
+    (UINT32, 1, "NetworkID"),
+    (UINT32, 2, "Flags"),
+    (UINT32, 3, "SourceTag"),
+    (UINT32, 4, "Sequence"),
+    (UINT32, 5, "PreviousTxnLgrSeq"),
+    (UINT32, 6, "LedgerSequence"),
+    (UINT32, 7, "CloseTime"),
+    (UINT32, 8, "ParentCloseTime"),
+    (UINT32, 9, "SigningTime"),
+    (UINT32, 10, "Expiration"),
+    (UINT32, 11, "TransferRate"),
+    (UINT32, 12, "WalletSize"),
+    (UINT32, 13, "OwnerCount"),
+    (UINT32, 14, "DestinationTag"),
+    (UINT32, 15, "LastUpdateTime"),
+    (UINT32, 16, "HighQualityIn"),
+    (UINT32, 17, "HighQualityOut"),
+    (UINT32, 18, "LowQualityIn"),
+    (UINT32, 19, "LowQualityOut"),
+    (UINT32, 20, "QualityIn"),
+    (UINT32, 21, "QualityOut"),
+    (UINT32, 22, "StampEscrow"),
+    (UINT32, 23, "BondAmount"),
+    (UINT32, 24, "LoadFee"),
+    (UINT32, 25, "OfferSequence"),
+    (UINT32, 26, "FirstLedgerSequence"),
+    (UINT32, 27, "LastLedgerSequence"),
+    (UINT32, 28, "TransactionIndex"),
+    (UINT32, 29, "OperationLimit"),
+    (UINT32, 30, "ReferenceFeeUnits"),
+    (UINT32, 31, "ReserveBase"),
+    (UINT32, 32, "ReserveIncrement"),
+    (UINT32, 33, "SetFlag"),
+    (UINT32, 34, "ClearFlag"),
+    (UINT32, 35, "SignerQuorum"),
+    (UINT32, 36, "CancelAfter"),
+    (UINT32, 37, "FinishAfter"),
+    (UINT32, 38, "SignerListID"),
+    (UINT32, 39, "SettleDelay"),
+    (UINT32, 40, "TicketCount"),
+    (UINT32, 41, "TicketSequence"),
+    (UINT32, 42, "NFTokenTaxon"),
+    (UINT32, 43, "MintedNFTokens"),
+    (UINT32, 44, "BurnedNFTokens"),
+    (UINT32, 47, "VoteWeight"),
+    (UINT32, 48, "FirstNFTokenSequence"),
+    (UINT64, 1, "IndexNext"),
+    (UINT64, 2, "IndexPrevious"),
+    (UINT64, 3, "BookNode"),
+    (UINT64, 4, "OwnerNode"),
+    (UINT64, 5, "BaseFee"),
+    (UINT64, 6, "ExchangeRate"),
+    (UINT64, 7, "LowNode"),
+    (UINT64, 8, "HighNode"),
+    (UINT64, 9, "DestinationNode"),
+    (UINT64, 10, "Cookie"),
+    (UINT64, 11, "ServerVersion"),
+    (UINT64, 12, "NFTokenOfferNode"),
+    (HASH128, 1, "EmailHash"),
+    (HASH160, 1, "TakerPaysCurrency"),
+    (HASH160, 2, "TakerPaysIssuer"),
+    (HASH160, 3, "TakerGetsCurrency"),
+    (HASH160, 4, "TakerGetsIssuer"),
+    (HASH256, 1, "LedgerHash"),
+    (HASH256, 2, "ParentHash"),
+    (HASH256, 3, "TransactionHash"),
+    (HASH256, 4, "AccountHash"),
+    (HASH256, 5, "PreviousTxnID"),
+    (HASH256, 6, "LedgerIndex"),
+    (HASH256, 7, "WalletLocator"),
+    (HASH256, 8, "RootIndex"),
+    (HASH256, 9, "AccountTxnID"),
+    (HASH256, 10, "NFTokenID"),
+    (HASH256, 14, "AMMID"),
+    (HASH256, 16, "BookDirectory"),
+    (HASH256, 17, "InvoiceID"),
+    (HASH256, 18, "Nickname"),
+    (HASH256, 19, "Amendment"),
+    (HASH256, 21, "Digest"),
+    (HASH256, 22, "Channel"),
+    (HASH256, 23, "ConsensusHash"),
+    (HASH256, 24, "CheckID"),
+    (HASH256, 25, "ValidatedHash"),
+    (HASH256, 26, "PreviousPageMin"),
+    (HASH256, 27, "NextPageMin"),
+    (HASH256, 28, "NFTokenBuyOffer"),
+    (HASH256, 29, "NFTokenSellOffer"),
+    (AMOUNT, 1, "Amount"),
+    (AMOUNT, 2, "Balance"),
+    (AMOUNT, 3, "LimitAmount"),
+    (AMOUNT, 4, "TakerPays"),
+    (AMOUNT, 5, "TakerGets"),
+    (AMOUNT, 6, "LowLimit"),
+    (AMOUNT, 7, "HighLimit"),
+    (AMOUNT, 8, "Fee"),
+    (AMOUNT, 9, "SendMax"),
+    (AMOUNT, 10, "DeliverMin"),
+    (AMOUNT, 11, "Amount2"),
+    (AMOUNT, 12, "BidMin"),
+    (AMOUNT, 13, "BidMax"),
+    (AMOUNT, 16, "MinimumOffer"),
+    (AMOUNT, 17, "RippleEscrow"),
+    (AMOUNT, 18, "DeliveredAmount"),
+    (AMOUNT, 19, "NFTokenBrokerFee"),
+    (AMOUNT, 22, "BaseFeeDrops"),
+    (AMOUNT, 23, "ReserveBaseDrops"),
+    (AMOUNT, 24, "ReserveIncrementDrops"),
+    (AMOUNT, 25, "LPTokenOut"),
+    (AMOUNT, 26, "LPTokenIn"),
+    (AMOUNT, 27, "EPrice"),
+    (AMOUNT, 28, "Price"),
+    (AMOUNT, 31, "LPTokenBalance"),
+    (BLOB, 1, "PublicKey"),
+    (BLOB, 2, "MessageKey"),
+    (BLOB, 3, "SigningPubKey"),
+    (BLOB, 4, "TxnSignature"),
+    (BLOB, 5, "URI"),
+    (BLOB, 6, "Signature"),
+    (BLOB, 7, "Domain"),
+    (BLOB, 8, "FundCode"),
+    (BLOB, 9, "RemoveCode"),
+    (BLOB, 10, "ExpireCode"),
+    (BLOB, 11, "CreateCode"),
+    (BLOB, 12, "MemoType"),
+    (BLOB, 13, "MemoData"),
+    (BLOB, 14, "MemoFormat"),
+    (BLOB, 16, "Fulfillment"),
+    (BLOB, 17, "Condition"),
+    (BLOB, 18, "MasterSignature"),
+    (BLOB, 19, "UNLModifyValidator"),
+    (BLOB, 20, "ValidatorToDisable"),
+    (BLOB, 21, "ValidatorToReEnable"),
+    (ACCOUNT_ID, 1, "Account"),
+    (ACCOUNT_ID, 2, "Owner"),
+    (ACCOUNT_ID, 3, "Destination"),
+    (ACCOUNT_ID, 4, "Issuer"),
+    (ACCOUNT_ID, 5, "Authorize"),
+    (ACCOUNT_ID, 6, "Unauthorize"),
+    (ACCOUNT_ID, 8, "RegularKey"),
+    (ACCOUNT_ID, 9, "NFTokenMinter"),
+    (OBJECT, 1, "ObjectEndMarker"),
+    (OBJECT, 2, "TransactionMetaData"),
+    (OBJECT, 3, "CreatedNode"),
+    (OBJECT, 4, "DeletedNode"),
+    (OBJECT, 5, "ModifiedNode"),
+    (OBJECT, 6, "PreviousFields"),
+    (OBJECT, 7, "FinalFields"),
+    (OBJECT, 8, "NewFields"),
+    (OBJECT, 9, "TemplateEntry"),
+    (OBJECT, 10, "Memo"),
+    (OBJECT, 11, "SignerEntry"),
+    (OBJECT, 12, "NFToken"),
+    (OBJECT, 16, "Signer"),
+    (OBJECT, 18, "Majority"),
+    (OBJECT, 19, "DisabledValidator"),
+    (OBJECT, 25, "VoteEntry"),
+    (OBJECT, 26, "AuctionSlot"),
+    (OBJECT, 27, "AuthAccount"),
+    (ARRAY, 1, "ArrayEndMarker"),
+    (ARRAY, 3, "Signers"),
+    (ARRAY, 4, "SignerEntries"),
+    (ARRAY, 5, "Template"),
+    (ARRAY, 6, "Necessary"),
+    (ARRAY, 7, "Sufficient"),
+    (ARRAY, 8, "AffectedNodes"),
+    (ARRAY, 9, "Memos"),
+    (ARRAY, 10, "NFTokens"),
+    (ARRAY, 12, "VoteSlots"),
+    (ARRAY, 16, "Majorities"),
+    (ARRAY, 17, "DisabledValidators"),
+    (ARRAY, 25, "AuthAccounts"),
+    (PATH_SET, 1, "Paths"),
+    (VECTOR256, 1, "Indexes"),
+    (VECTOR256, 2, "Hashes"),
+    (VECTOR256, 3, "Amendments"),
+    (VECTOR256, 4, "NFTokenOffers"),
+    (ISSUE, 1, "LockingChainIssue"),
+    (ISSUE, 2, "IssuingChainIssue"),
+    (ISSUE, 3, "Asset"),
+    (ISSUE, 4, "Asset2"),
+    (XCHAIN_BRIDGE, 1, "XChainBridge"),
+];
+
+pub fn field_name(type_code: u16, field_code: u16) -> String {
+    FIELDS
+        .iter()
+        .find(|(field_type, code, _)| *field_type == type_code && *code == field_code)
+        .map(|(_, _, name)| name.to_string())
+        .unwrap_or_else(|| format!("Field{}_{}", type_code, field_code))
+}
+
+pub fn transaction_type_name(code: u16) -> Option<&'static str> {
+    find_name(TRANSACTION_TYPES, code)
+}
+
+pub fn ledger_entry_type_name(code: u16) -> Option<&'static str> {
+    find_name(LEDGER_ENTRY_TYPES, code)
+}
+
+pub fn transaction_result_name(code: i16) -> Option<&'static str> {
+    find_name(TRANSACTION_RESULTS, code)
+}
+
+pub fn transaction_result_code(name: &str) -> Option<i16> {
+    TRANSACTION_RESULTS
+        .iter()
+        .find(|(_, result)| *result == name)
+        .map(|(code, _)| *code)
+}
+
+fn find_name<T: PartialEq + Copy>(names: &[(T, &'static str)], code: T) -> Option<&'static str> {
+    names
+        .iter()
+        .find(|(known, _)| *known == code)
+        .map(|(_, name)| *name)
+}
@@ -0,0 +1,27 @@
+use scylla::transport::errors::QueryError;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum IngestError {
+    #[error("reading {0} failed ({1})")]
+    Read(PathBuf, std::io::Error),
+
+    #[error("parsing JSON failed ({0})")]
+    Json(#[from] serde_json::Error),
+
+    #[error("requesting rippled failed ({0})")]
+    Request(#[from] reqwest::Error),
+
+    #[error("rippled returned an error ({0})")]
+    Rpc(String),
+
+    #[error("invalid ledger ({0})")]
+    InvalidLedger(String),
+
+    #[error("decoding binary failed ({0})")]
+    Binary(String),
+
+    #[error("writing rows failed ({0})")]
+    QueryFailed(#[from] QueryError),
+}
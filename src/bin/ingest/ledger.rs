@@ -0,0 +1,160 @@
+use crate::binary::{decode_ledger_header, decode_object, transaction_hash};
+use crate::consts::RIPPLE_EPOCH_OFFSET;
+use crate::error::IngestError;
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+
+/// A closed ledger with its transactions in rippled's JSON, whichever form it was read in.
+/// Transactions are in the order they were applied.
+pub struct LedgerDump {
+    pub ledger_index: i64,
+    pub ledger_hash: String,
+    pub parent_hash: String,
+    pub account_hash: String,
+    pub transaction_hash: String,
+    pub close_flags: i32,
+    pub close_time: DateTime<Utc>,
+    pub parent_close_time: DateTime<Utc>,
+    pub total_coins: i64,
+    pub transactions: Vec<TransactionDump>,
+}
+
+pub struct TransactionDump {
+    pub hash: String,
+    pub tx: Map<String, Value>,
+    pub meta: Map<String, Value>,
+}
+
+impl LedgerDump {
+    /// Reads a response of rippled's `ledger` method, either whole or only its result, with
+    /// expanded transactions in JSON or binary form.
+    pub fn from_response(response: Value) -> Result<Self, IngestError> {
+        let mut result = match response {
+            Value::Object(mut response) => match response.remove("result") {
+                Some(Value::Object(result)) => result,
+                Some(_) => return Err(invalid("result is not an object")),
+                None => response,
+            },
+            _ => return Err(invalid("response is not an object")),
+        };
+        if let Some(error) = result.get("error").and_then(Value::as_str) {
+            let message = result
+                .get("error_message")
+                .and_then(Value::as_str)
+                .unwrap_or(error);
+            return Err(IngestError::Rpc(message.to_string()));
+        }
+
+        let mut ledger = match result.remove("ledger") {
+            Some(Value::Object(ledger)) => ledger,
+            _ => return Err(invalid("no ledger in the response")),
+        };
+        if let Some(data) = ledger.get("ledger_data").and_then(Value::as_str) {
+            let header = decode_ledger_header(data)?;
+            ledger.extend(header);
+        }
+        let string = |field: &str| {
+            ledger
+                .get(field)
+                .or_else(|| result.get(field))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| invalid(&format!("no {}", field)))
+        };
+        let number = |field: &str| {
+            integer(ledger.get(field).or_else(|| result.get(field)))
+                .ok_or_else(|| invalid(&format!("no {}", field)))
+        };
+
+        let mut transactions = match ledger.get("transactions") {
+            Some(Value::Array(transactions)) => transactions
+                .iter()
+                .map(TransactionDump::from_entry)
+                .collect::<Result<Vec<TransactionDump>, IngestError>>()?,
+            _ => Vec::new(),
+        };
+        transactions.sort_by_key(|transaction| transaction.index());
+
+        Ok(LedgerDump {
+            ledger_index: number("ledger_index")?,
+            ledger_hash: string("ledger_hash")?,
+            parent_hash: string("parent_hash")?,
+            account_hash: string("account_hash")?,
+            transaction_hash: string("transaction_hash")?,
+            close_flags: number("close_flags").unwrap_or_default() as i32,
+            close_time: ripple_time(number("close_time")?)?,
+            parent_close_time: ripple_time(number("parent_close_time")?)?,
+            total_coins: number("total_coins")?,
+            transactions,
+        })
+    }
+}
+
+impl TransactionDump {
+    // Entries are a binary `tx_blob` with its `meta`, API v2's `tx_json` with `meta`, or
+    // API v1's transaction fields with `metaData`
+    fn from_entry(entry: &Value) -> Result<Self, IngestError> {
+        let mut entry = match entry {
+            Value::Object(entry) => entry.clone(),
+            _ => return Err(invalid("transactions are not expanded")),
+        };
+        let hash = entry
+            .get("hash")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+
+        if let Some(blob) = entry.get("tx_blob").and_then(Value::as_str) {
+            let meta = entry
+                .get("meta")
+                .or_else(|| entry.get("metaData"))
+                .and_then(Value::as_str)
+                .ok_or_else(|| invalid("binary transaction without metadata"))?;
+            return Ok(TransactionDump {
+                hash: hash.map_or_else(|| transaction_hash(blob), Ok)?,
+                tx: decode_object(blob)?,
+                meta: decode_object(meta)?,
+            });
+        }
+
+        let (tx, meta) = match entry.remove("tx_json") {
+            Some(Value::Object(tx)) => (tx, entry.remove("meta")),
+            _ => {
+                entry.remove("hash");
+                let meta = entry.remove("metaData");
+                (entry, meta)
+            }
+        };
+        let meta = match meta {
+            Some(Value::Object(meta)) => meta,
+            _ => return Err(invalid("transaction without metadata")),
+        };
+        Ok(TransactionDump {
+            hash: hash.ok_or_else(|| invalid("transaction without a hash"))?,
+            tx,
+            meta,
+        })
+    }
+
+    /// Position of the transaction in its ledger.
+    pub fn index(&self) -> i64 {
+        integer(self.meta.get("TransactionIndex")).unwrap_or_default()
+    }
+}
+
+/// An integer rippled gives as a number or a string of digits.
+pub fn integer(value: Option<&Value>) -> Option<i64> {
+    match value? {
+        Value::Number(number) => number.as_i64(),
+        Value::String(digits) => digits.parse::<i64>().ok(),
+        _ => None,
+    }
+}
+
+fn ripple_time(seconds: i64) -> Result<DateTime<Utc>, IngestError> {
+    DateTime::from_timestamp(seconds + RIPPLE_EPOCH_OFFSET, 0)
+        .ok_or_else(|| invalid(&format!("close time {} out of range", seconds)))
+}
+
+fn invalid(message: &str) -> IngestError {
+    IngestError::InvalidLedger(message.to_string())
+}
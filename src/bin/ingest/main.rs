@@ -0,0 +1,242 @@
+// Loads ledgers into the tables the API reads, from saved rippled `ledger` responses or from a
+// rippled JSON-RPC endpoint. Rows are upserts keyed on the ledger, so ingesting a ledger again
+// is harmless, and a named checkpoint lets an interrupted run resume where it stopped.
+
+mod amount;
+mod binary;
+mod codes;
+#[allow(dead_code)]
+#[path = "../../utils/consts.rs"]
+mod consts;
+#[allow(dead_code)]
+#[path = "../../utils/decimal.rs"]
+mod decimal;
+mod error;
+mod ledger;
+mod rows;
+#[path = "../../schema/mod.rs"]
+mod schema;
+mod source;
+mod writer;
+
+use crate::consts::{KEYSPACE, SCYLLA_NODES};
+use crate::error::IngestError;
+use crate::rows::derive_rows;
+use crate::source::{list_files, validated_ledger_index, LedgerRef, Source};
+use crate::writer::Writer;
+use clap::Parser;
+use futures::StreamExt;
+use scylla::{Session, SessionBuilder};
+use std::path::PathBuf;
+
+pub static INGEST_CHECKPOINTS_TABLE: &str = "ingest_checkpoints";
+// Ledgers processed at once, unless set with --concurrency
+pub static INGEST_DEFAULT_CONCURRENCY: usize = 4;
+// Rows of a single ledger written at once
+pub static INGEST_WRITE_CONCURRENCY: usize = 32;
+
+/// Loads rippled ledgers, with their transactions and metadata, into the XRPL Data API tables.
+#[derive(Parser)]
+struct Args {
+    /// Files or directories of `ledger` responses with expanded transactions, in JSON or binary
+    /// form. Directories are read in file name order.
+    #[arg(long, num_args = 1.., required_unless_present = "rpc", conflicts_with = "rpc")]
+    files: Vec<PathBuf>,
+
+    /// rippled JSON-RPC endpoint to fetch ledgers from, such as http://127.0.0.1:5005
+    #[arg(long)]
+    rpc: Option<String>,
+
+    /// First ledger to fetch, defaults to the one after the checkpoint
+    #[arg(long, requires = "rpc")]
+    start: Option<i64>,
+
+    /// Last ledger to fetch, defaults to the latest validated ledger
+    #[arg(long, requires = "rpc")]
+    end: Option<i64>,
+
+    /// Fetch ledgers in binary form, which rippled serves faster
+    #[arg(long, requires = "rpc")]
+    binary: bool,
+
+    /// Ledgers fetched and written at once
+    #[arg(long, default_value_t = INGEST_DEFAULT_CONCURRENCY)]
+    concurrency: usize,
+
+    /// Name to save progress under. Ledgers up to the saved one are skipped, and the latest
+    /// ledger written with every ledger before it is saved as the run goes.
+    #[arg(long)]
+    checkpoint: Option<String>,
+
+    /// Keyspace to write to
+    #[arg(long, default_value = KEYSPACE)]
+    keyspace: String,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+    if !schema::is_valid_keyspace(&args.keyspace) {
+        eprintln!("Invalid keyspace {}", args.keyspace);
+        std::process::exit(1);
+    }
+
+    println!("Connecting to scylla.");
+    let session: Session = SessionBuilder::new()
+        .known_nodes(SCYLLA_NODES)
+        .build()
+        .await
+        .expect("Failed to connect to scylla nodes");
+
+    // Rows are written to the tables of the expected schema
+    let schema_version = schema::applied_version(&session, &args.keyspace)
+        .await
+        .expect("Unable to read the schema version");
+    if schema_version < schema::expected_version() {
+        eprintln!(
+            "Schema is at version {} but version {} is required, run the migrate binary",
+            schema_version,
+            schema::expected_version()
+        );
+        std::process::exit(1);
+    }
+    session
+        .use_keyspace(&args.keyspace, true)
+        .await
+        .expect("Unable to use keyspace");
+
+    let writer = Writer::new(session)
+        .await
+        .expect("Unable to prepare statements");
+    if let Err(err) = ingest(&args, &writer).await {
+        eprintln!("Ingestion failed: {}", err);
+        std::process::exit(1);
+    }
+}
+
+async fn ingest(args: &Args, writer: &Writer) -> Result<(), IngestError> {
+    let checkpoint = match &args.checkpoint {
+        Some(name) => writer.checkpoint(name).await?,
+        None => None,
+    };
+    if let Some(ledger_index) = checkpoint {
+        println!("Resuming after ledger {}", ledger_index);
+    }
+
+    let (source, ledgers) = match &args.rpc {
+        Some(url) => {
+            let client = reqwest::Client::new();
+            let start = match (args.start, checkpoint) {
+                (Some(start), Some(ledger_index)) => start.max(ledger_index + 1),
+                (Some(start), None) => start,
+                (None, Some(ledger_index)) => ledger_index + 1,
+                (None, None) => {
+                    return Err(IngestError::InvalidLedger(
+                        "--start is required without a checkpoint".to_string(),
+                    ))
+                }
+            };
+            let end = match args.end {
+                Some(end) => end,
+                None => validated_ledger_index(&client, url).await?,
+            };
+            println!("Fetching ledgers {} to {} from {}", start, end, url);
+            // Ledgers are requested as they are read from the stream, not listed up front
+            let ledgers = futures::stream::iter(start..=end)
+                .map(LedgerRef::Index)
+                .boxed();
+            let source = Source::Rpc {
+                client,
+                url: url.clone(),
+                binary: args.binary,
+            };
+            (source, ledgers)
+        }
+        None => {
+            let mut files = list_files(&args.files)?;
+            if let Some(ledger_index) = checkpoint {
+                files = files_after(files, ledger_index).await?;
+            }
+            println!("Reading {} files", files.len());
+            (
+                Source::Files,
+                futures::stream::iter(files).map(LedgerRef::File).boxed(),
+            )
+        }
+    };
+
+    // Ledgers complete in order, so a checkpoint never passes a ledger that was not written
+    let mut written = ledgers
+        .map(|ledger| {
+            let source = &source;
+            async move {
+                let ledger = source.load(&ledger).await?;
+                // Files out of ledger order may still be at or before the checkpoint
+                if checkpoint.is_some_and(|checkpoint| ledger.ledger_index <= checkpoint) {
+                    return Ok(None);
+                }
+                let rows = derive_rows(&ledger)?;
+                writer.write(&rows).await?;
+                Ok::<_, IngestError>(Some((ledger.ledger_index, rows.transactions.len())))
+            }
+        })
+        .buffered(args.concurrency.max(1));
+
+    // The checkpoint only moves to the ledger after the saved one, moving it over a gap would
+    // skip the missing ledgers when resuming
+    let mut saved = checkpoint;
+    let mut ledger_count = 0;
+    let mut transaction_count = 0;
+    while let Some(result) = written.next().await {
+        let Some((ledger_index, transactions)) = result? else {
+            continue;
+        };
+        if let Some(name) = &args.checkpoint {
+            match saved {
+                Some(saved) if ledger_index != saved + 1 => {
+                    println!(
+                        "Ledger {} does not follow ledger {}, the checkpoint stays there",
+                        ledger_index, saved
+                    );
+                }
+                _ => {
+                    writer.save_checkpoint(name, ledger_index).await?;
+                    saved = Some(ledger_index);
+                }
+            }
+        }
+        ledger_count += 1;
+        transaction_count += transactions;
+        println!(
+            "Wrote ledger {} with {} transactions",
+            ledger_index, transactions
+        );
+    }
+    println!(
+        "Wrote {} ledgers with {} transactions",
+        ledger_count, transaction_count
+    );
+    Ok(())
+}
+
+// The files after the one of the checkpoint ledger. Files are in ledger order, so it is found
+// by bisecting, loading a few files instead of every file before it.
+async fn files_after(
+    mut files: Vec<PathBuf>,
+    checkpoint: i64,
+) -> Result<Vec<PathBuf>, IngestError> {
+    let (mut low, mut high) = (0, files.len());
+    while low < high {
+        let middle = (low + high) / 2;
+        let ledger = Source::Files
+            .load(&LedgerRef::File(files[middle].clone()))
+            .await?;
+        if ledger.ledger_index <= checkpoint {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+    println!("Skipping {} files up to the checkpoint", low);
+    Ok(files.split_off(low))
+}
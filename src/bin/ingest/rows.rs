@@ -0,0 +1,718 @@
+use crate::amount::{drops, Amount};
+use crate::codes::transaction_result_code;
+use crate::decimal::Decimal;
+use crate::error::IngestError;
+use crate::ledger::{integer, LedgerDump, TransactionDump};
+use chrono::{DateTime, NaiveDate, Utc};
+use num_bigint::BigInt;
+use scylla::SerializeRow;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+// Rows of the tables the API reads, derived from a ledger. Keys only depend on the ledger, so
+// writing a ledger again overwrites the same rows.
+
+// The fee is a balance change of its own, before the nodes of the metadata
+const TRANSACTION_COST_NODE: i64 = -1;
+const SUCCESS: &str = "tesSUCCESS";
+const XRP: &str = "XRP";
+
+#[derive(SerializeRow)]
+pub struct LedgerRow {
+    pub ledger_index: i64,
+    pub ledger_hash: String,
+    pub parent_hash: String,
+    pub account_hash: String,
+    pub transaction_hash: String,
+    pub close_flags: i32,
+    pub close_time: DateTime<Utc>,
+    pub parent_close_time: DateTime<Utc>,
+    pub total_coins: i64,
+    pub tx_count: BigInt,
+    pub ledger_processed: bool,
+}
+
+#[derive(SerializeRow)]
+pub struct DailyLedgerRow {
+    pub ledger_close_day: NaiveDate,
+    pub ledger_index: i64,
+    pub close_time: DateTime<Utc>,
+}
+
+#[derive(SerializeRow)]
+pub struct TransactionRow {
+    pub account: String,
+    pub hash: String,
+    pub ctid: String,
+    pub ledger_index: i64,
+    pub tx_index: BigInt,
+    pub tx_type: String,
+    pub timestamp: DateTime<Utc>,
+    pub flags: i64,
+    pub fee: BigInt,
+    pub sequence: i64,
+    pub result: i16,
+    pub meta: Vec<u8>,
+    pub tx: Vec<u8>,
+}
+
+#[derive(SerializeRow)]
+pub struct AccountRow {
+    pub ledger_index: i64,
+    pub tx_index: BigInt,
+    pub account: String,
+    pub client: Option<String>,
+    pub initial_balance: String,
+    pub parent: String,
+    pub timestamp: DateTime<Utc>,
+    pub tx_hash: String,
+}
+
+#[derive(SerializeRow)]
+pub struct PaymentRow {
+    pub tx_hash: String,
+    pub ledger_index: i64,
+    pub tx_index: BigInt,
+    pub source: String,
+    pub source_currency: String,
+    pub source_currency_issuer: String,
+    pub destination: String,
+    pub destination_currency: String,
+    pub destination_currency_issuer: String,
+    pub amount: String,
+    pub delivered_amount: String,
+    pub transaction_cost: BigInt,
+    pub destination_tag: Option<i64>,
+    pub source_tag: Option<i64>,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(SerializeRow)]
+pub struct BalanceChangeRow {
+    pub ledger_index: i64,
+    pub tx_index: BigInt,
+    pub node_index: BigInt,
+    pub account: String,
+    pub change: String,
+    pub change_type: String,
+    pub counterparty: Option<String>,
+    pub currency: String,
+    pub final_balance: String,
+    pub timestamp: DateTime<Utc>,
+    pub tx_hash: String,
+}
+
+#[derive(SerializeRow)]
+pub struct ExchangePairRow {
+    pub base: String,
+    pub counter: String,
+    pub ledger_index: i64,
+    pub tx_index: BigInt,
+    pub tx_hash: String,
+}
+
+pub struct LedgerRows {
+    pub ledger: LedgerRow,
+    pub daily_ledger: DailyLedgerRow,
+    pub transactions: Vec<TransactionRow>,
+    pub accounts: Vec<AccountRow>,
+    pub payments: Vec<PaymentRow>,
+    pub balance_changes: Vec<BalanceChangeRow>,
+    pub exchange_pairs: Vec<ExchangePairRow>,
+}
+
+// Fields of a transaction needed by the rows derived from it
+struct Context<'a> {
+    ledger: &'a LedgerDump,
+    transaction: &'a TransactionDump,
+    tx_type: &'a str,
+    sender: &'a str,
+    fee: i64,
+    succeeded: bool,
+}
+
+pub fn derive_rows(ledger: &LedgerDump) -> Result<LedgerRows, IngestError> {
+    let mut rows = LedgerRows {
+        ledger: LedgerRow {
+            ledger_index: ledger.ledger_index,
+            ledger_hash: ledger.ledger_hash.clone(),
+            parent_hash: ledger.parent_hash.clone(),
+            account_hash: ledger.account_hash.clone(),
+            transaction_hash: ledger.transaction_hash.clone(),
+            close_flags: ledger.close_flags,
+            close_time: ledger.close_time,
+            parent_close_time: ledger.parent_close_time,
+            total_coins: ledger.total_coins,
+            tx_count: BigInt::from(ledger.transactions.len()),
+            ledger_processed: true,
+        },
+        daily_ledger: DailyLedgerRow {
+            ledger_close_day: ledger.close_time.date_naive(),
+            ledger_index: ledger.ledger_index,
+            close_time: ledger.close_time,
+        },
+        transactions: Vec::new(),
+        accounts: Vec::new(),
+        payments: Vec::new(),
+        balance_changes: Vec::new(),
+        exchange_pairs: Vec::new(),
+    };
+
+    for transaction in &ledger.transactions {
+        let result = string(&transaction.meta, "TransactionResult");
+        let context = Context {
+            ledger,
+            transaction,
+            tx_type: string(&transaction.tx, "TransactionType"),
+            sender: string(&transaction.tx, "Account"),
+            fee: drops(transaction.tx.get("Fee")).unwrap_or_default(),
+            succeeded: result == SUCCESS,
+        };
+        rows.transactions.push(transaction_row(&context, result)?);
+        if context.succeeded {
+            rows.accounts.extend(account_rows(&context));
+            rows.payments.extend(payment_row(&context));
+        }
+        let balance_changes = balance_change_rows(&context);
+        rows.exchange_pairs
+            .extend(exchange_pair_rows(&balance_changes));
+        rows.balance_changes.extend(balance_changes);
+    }
+    Ok(rows)
+}
+
+fn transaction_row(context: &Context, result: &str) -> Result<TransactionRow, IngestError> {
+    let transaction = context.transaction;
+    let result = transaction_result_code(result).ok_or_else(|| {
+        IngestError::InvalidLedger(format!("unknown result {} of {}", result, transaction.hash))
+    })?;
+    let network_id = integer(transaction.tx.get("NetworkID")).unwrap_or_default();
+    Ok(TransactionRow {
+        account: context.sender.to_string(),
+        hash: transaction.hash.clone(),
+        ctid: ctid(context.ledger.ledger_index, transaction.index(), network_id),
+        ledger_index: context.ledger.ledger_index,
+        tx_index: BigInt::from(transaction.index()),
+        tx_type: context.tx_type.to_string(),
+        timestamp: context.ledger.close_time,
+        flags: integer(transaction.tx.get("Flags")).unwrap_or_default(),
+        fee: BigInt::from(context.fee),
+        sequence: integer(transaction.tx.get("Sequence")).unwrap_or_default(),
+        result,
+        meta: serde_json::to_vec(&transaction.meta)?,
+        tx: serde_json::to_vec(&transaction.tx)?,
+    })
+}
+
+// Accounts funded by the transaction, with its sender as their parent
+fn account_rows(context: &Context) -> Vec<AccountRow> {
+    affected_nodes(&context.transaction.meta)
+        .filter(|node| node.kind == "CreatedNode" && node.entry_type == "AccountRoot")
+        .filter_map(|node| {
+            let fields = node.final_fields?;
+            let balance = drops(fields.get("Balance"))?;
+            Some(AccountRow {
+                ledger_index: context.ledger.ledger_index,
+                tx_index: BigInt::from(context.transaction.index()),
+                account: string(fields, "Account").to_string(),
+                client: None,
+                initial_balance: Decimal::from_drops(balance).to_string(),
+                parent: context.sender.to_string(),
+                timestamp: context.ledger.close_time,
+                tx_hash: context.transaction.hash.clone(),
+            })
+        })
+        .collect()
+}
+
+fn payment_row(context: &Context) -> Option<PaymentRow> {
+    if context.tx_type != "Payment" {
+        return None;
+    }
+    let tx = &context.transaction.tx;
+    let meta = &context.transaction.meta;
+    // API v2 renames a payment's Amount to DeliverMax
+    let amount = Amount::from_json(tx.get("Amount").or_else(|| tx.get("DeliverMax"))?)?;
+    let delivered = meta
+        .get("delivered_amount")
+        .or_else(|| meta.get("DeliveredAmount"))
+        .and_then(Amount::from_json)
+        .unwrap_or_else(|| amount.clone());
+    let source = tx
+        .get("SendMax")
+        .and_then(Amount::from_json)
+        .unwrap_or_else(|| amount.clone());
+
+    Some(PaymentRow {
+        tx_hash: context.transaction.hash.clone(),
+        ledger_index: context.ledger.ledger_index,
+        tx_index: BigInt::from(context.transaction.index()),
+        source: context.sender.to_string(),
+        source_currency: source.currency,
+        source_currency_issuer: source.issuer,
+        destination: string(tx, "Destination").to_string(),
+        destination_currency: amount.currency,
+        destination_currency_issuer: amount.issuer,
+        amount: amount.value.to_string(),
+        delivered_amount: delivered.value.to_string(),
+        transaction_cost: BigInt::from(context.fee),
+        destination_tag: integer(tx.get("DestinationTag")),
+        source_tag: integer(tx.get("SourceTag")),
+        timestamp: context.ledger.close_time,
+    })
+}
+
+// XRP changes from AccountRoot nodes, with the fee split off the sender's, and token changes
+// from trust lines seen from both sides
+fn balance_change_rows(context: &Context) -> Vec<BalanceChangeRow> {
+    let nodes = affected_nodes(&context.transaction.meta).collect::<Vec<AffectedNode>>();
+    let offer_owners = nodes
+        .iter()
+        .filter(|node| node.entry_type == "Offer")
+        .filter_map(|node| node.final_fields)
+        .map(|fields| string(fields, "Account"))
+        .collect::<HashSet<&str>>();
+    let destination = string(&context.transaction.tx, "Destination");
+    let change_type = |account: &str| match context.tx_type {
+        "Payment" if account == context.sender => "payment_source".to_string(),
+        "Payment" if account == destination => "payment_destination".to_string(),
+        "Payment" if offer_owners.contains(account) => "exchange".to_string(),
+        "Payment" => "intermediary".to_string(),
+        "OfferCreate" => "exchange".to_string(),
+        tx_type => snake_case(tx_type),
+    };
+
+    let mut rows = Vec::new();
+    let mut push = |node_index: i64,
+                    account: &str,
+                    counterparty: Option<&str>,
+                    currency: &str,
+                    change: Decimal,
+                    final_balance: Decimal,
+                    change_type: String| {
+        rows.push(BalanceChangeRow {
+            ledger_index: context.ledger.ledger_index,
+            tx_index: BigInt::from(context.transaction.index()),
+            node_index: BigInt::from(node_index),
+            account: account.to_string(),
+            change: change.to_string(),
+            change_type,
+            counterparty: counterparty.map(str::to_string),
+            currency: currency.to_string(),
+            final_balance: final_balance.to_string(),
+            timestamp: context.ledger.close_time,
+            tx_hash: context.transaction.hash.clone(),
+        })
+    };
+
+    for node in &nodes {
+        let Some(fields) = node.final_fields else {
+            continue;
+        };
+        match node.entry_type {
+            "AccountRoot" => {
+                let account = string(fields, "Account");
+                let Some(final_balance) = drops(fields.get("Balance")) else {
+                    continue;
+                };
+                let previous = node
+                    .previous_fields
+                    .and_then(|fields| drops(fields.get("Balance")));
+                let previous = match (previous, node.kind) {
+                    (Some(previous), _) => previous,
+                    (None, "CreatedNode") => 0,
+                    (None, _) => continue,
+                };
+
+                let mut change = final_balance - previous;
+                if account == context.sender && context.fee > 0 {
+                    push(
+                        TRANSACTION_COST_NODE,
+                        account,
+                        None,
+                        "XRP",
+                        Decimal::from_drops(-context.fee),
+                        Decimal::from_drops(previous - context.fee),
+                        "transaction_cost".to_string(),
+                    );
+                    change += context.fee;
+                }
+                if change != 0 {
+                    push(
+                        node.index,
+                        account,
+                        None,
+                        "XRP",
+                        Decimal::from_drops(change),
+                        Decimal::from_drops(final_balance),
+                        change_type(account),
+                    );
+                }
+            }
+            "RippleState" => {
+                let Some(balance) = fields.get("Balance").and_then(Amount::from_json) else {
+                    continue;
+                };
+                let previous = node
+                    .previous_fields
+                    .and_then(|fields| fields.get("Balance"))
+                    .and_then(Amount::from_json)
+                    .map(|previous| previous.value);
+                let previous = match (previous, node.kind) {
+                    (Some(previous), _) => previous,
+                    (None, "CreatedNode") => Decimal::zero(),
+                    (None, _) => continue,
+                };
+                let change = balance.value.sub(&previous);
+                if change.is_zero() {
+                    continue;
+                }
+
+                // The balance is held by the low account, and owed by it when negative
+                let low = limit_issuer(fields, "LowLimit");
+                let high = limit_issuer(fields, "HighLimit");
+                push(
+                    node.index,
+                    low,
+                    Some(high),
+                    &balance.currency,
+                    change.clone(),
+                    balance.value.clone(),
+                    change_type(low),
+                );
+                push(
+                    node.index,
+                    high,
+                    Some(low),
+                    &balance.currency,
+                    change.neg(),
+                    balance.value.neg(),
+                    change_type(high),
+                );
+            }
+            _ => {}
+        }
+    }
+    rows
+}
+
+// Pairs of currencies traded by an account in a transaction, from its exchange balance changes.
+// The taker of an autobridged trade traded each of its currencies against XRP, as the API
+// reports it.
+fn exchange_pair_rows(changes: &[BalanceChangeRow]) -> Vec<ExchangePairRow> {
+    let mut currencies = BTreeMap::<&str, BTreeSet<String>>::new();
+    for change in changes
+        .iter()
+        .filter(|change| change.change_type == "exchange")
+    {
+        let currency = match &change.counterparty {
+            Some(issuer) => format!("{}+{}", change.currency, issuer),
+            None => change.currency.clone(),
+        };
+        currencies
+            .entry(change.account.as_str())
+            .or_default()
+            .insert(currency);
+    }
+    let bridged = currencies
+        .values()
+        .any(|currencies| currencies.contains(XRP));
+
+    let mut pairs = BTreeSet::new();
+    for currencies in currencies.values() {
+        if bridged && !currencies.contains(XRP) {
+            for currency in currencies {
+                pairs.insert((XRP.to_string(), currency.clone()));
+            }
+            continue;
+        }
+        let mut currencies = currencies.iter().collect::<Vec<&String>>();
+        // Same order as the pairs of the API
+        currencies.sort_by_key(|currency| (currency.as_str() != XRP, *currency));
+        for (index, base) in currencies.iter().enumerate() {
+            for counter in &currencies[index + 1..] {
+                pairs.insert((base.to_string(), counter.to_string()));
+            }
+        }
+    }
+    let Some(first) = changes.first() else {
+        return Vec::new();
+    };
+    pairs
+        .into_iter()
+        .map(|(base, counter)| ExchangePairRow {
+            base,
+            counter,
+            ledger_index: first.ledger_index,
+            tx_index: first.tx_index.clone(),
+            tx_hash: first.tx_hash.clone(),
+        })
+        .collect()
+}
+
+struct AffectedNode<'a> {
+    index: i64,
+    kind: &'a str,
+    entry_type: &'a str,
+    // New fields of created nodes
+    final_fields: Option<&'a Map<String, Value>>,
+    previous_fields: Option<&'a Map<String, Value>>,
+}
+
+fn affected_nodes(meta: &Map<String, Value>) -> impl Iterator<Item = AffectedNode<'_>> {
+    meta.get("AffectedNodes")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .filter_map(|(index, node)| {
+            let (kind, node) = node.as_object()?.iter().next()?;
+            let node = node.as_object()?;
+            let object = |field: &str| node.get(field).and_then(Value::as_object);
+            Some(AffectedNode {
+                index: index as i64,
+                kind,
+                entry_type: node.get("LedgerEntryType").and_then(Value::as_str)?,
+                final_fields: object("FinalFields").or_else(|| object("NewFields")),
+                previous_fields: object("PreviousFields"),
+            })
+        })
+}
+
+fn limit_issuer<'a>(fields: &'a Map<String, Value>, limit: &str) -> &'a str {
+    fields
+        .get(limit)
+        .and_then(|limit| limit.get("issuer"))
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+}
+
+fn string<'a>(fields: &'a Map<String, Value>, field: &str) -> &'a str {
+    fields
+        .get(field)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+}
+
+// Concise transaction ID, XLS-37: 4 bits of 0xC, then the ledger, transaction and network
+fn ctid(ledger_index: i64, tx_index: i64, network_id: i64) -> String {
+    let ctid = (0xc_u64 << 60)
+        | ((ledger_index as u64 & 0x0fff_ffff) << 32)
+        | ((tx_index as u64 & 0xffff) << 16)
+        | (network_id as u64 & 0xffff);
+    format!("{:016X}", ctid)
+}
+
+// "TrustSet" is "trust_set"
+fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    let chars = name.chars().collect::<Vec<char>>();
+    for (index, c) in chars.iter().enumerate() {
+        let next_lower = chars.get(index + 1).is_some_and(|c| c.is_lowercase());
+        let previous_lower = index > 0 && chars[index - 1].is_lowercase();
+        if c.is_uppercase() && index > 0 && (previous_lower || next_lower) {
+            snake.push('_');
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{derive_rows, exchange_pair_rows, BalanceChangeRow};
+    use crate::ledger::LedgerDump;
+    use chrono::DateTime;
+    use num_bigint::BigInt;
+
+    const GENESIS: &str = "rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh";
+    const FUNDED: &str = "rpZNAnHcvr6TbaY7QJa9yrVfu6coDz9pPH";
+    const ISSUER: &str = "rhf7192NqpPvBUnAobBJAryNFQNbPKz11w";
+
+    // A token payment from its issuer, listed after an XRP payment funding a new account
+    // although it was applied first
+    fn ledger() -> LedgerDump {
+        let response = serde_json::from_str(include_str!("fixtures/ledger.json")).unwrap();
+        LedgerDump::from_response(response).unwrap()
+    }
+
+    #[test]
+    fn derives_ledger_and_transactions_in_applied_order() {
+        let rows = derive_rows(&ledger()).unwrap();
+
+        assert_eq!(rows.ledger.ledger_index, 90000000);
+        assert_eq!(rows.ledger.tx_count, BigInt::from(2));
+        assert_eq!(
+            rows.ledger.close_time.to_rfc3339(),
+            "2025-05-08T06:13:20+00:00"
+        );
+        assert_eq!(rows.daily_ledger.ledger_close_day.to_string(), "2025-05-08");
+
+        let transactions = rows
+            .transactions
+            .iter()
+            .map(|row| {
+                (
+                    row.tx_index.clone(),
+                    row.account.as_str(),
+                    row.ctid.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            transactions,
+            [
+                (BigInt::from(0), ISSUER, "C55D4A8000000000"),
+                (BigInt::from(1), GENESIS, "C55D4A8000010000"),
+            ]
+        );
+        assert_eq!(rows.transactions[0].fee, BigInt::from(10));
+        assert_eq!(rows.transactions[0].flags, 2147483648);
+        assert_eq!(rows.transactions[0].result, 0);
+    }
+
+    #[test]
+    fn derives_funded_accounts_and_payments() {
+        let rows = derive_rows(&ledger()).unwrap();
+
+        assert_eq!(rows.accounts.len(), 1);
+        assert_eq!(rows.accounts[0].account, FUNDED);
+        assert_eq!(rows.accounts[0].parent, GENESIS);
+        assert_eq!(rows.accounts[0].initial_balance, "100");
+
+        let payments = rows
+            .payments
+            .iter()
+            .map(|row| {
+                (
+                    row.destination.as_str(),
+                    row.destination_currency.as_str(),
+                    row.destination_currency_issuer.as_str(),
+                    row.amount.as_str(),
+                    row.delivered_amount.as_str(),
+                    row.destination_tag,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            payments,
+            [
+                (GENESIS, "USD", ISSUER, "10.5", "10.5", Some(42)),
+                (FUNDED, "XRP", "", "100", "100", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn derives_balance_changes_with_the_fee_split_off() {
+        let rows = derive_rows(&ledger()).unwrap();
+
+        let changes = rows
+            .balance_changes
+            .iter()
+            .map(|row| {
+                (
+                    row.account.as_str(),
+                    row.currency.as_str(),
+                    row.change.as_str(),
+                    row.final_balance.as_str(),
+                    row.change_type.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            [
+                // The issuer's XRP balance only changed by the fee
+                (ISSUER, "XRP", "-0.00001", "49.99999", "transaction_cost"),
+                (ISSUER, "USD", "-10.5", "-10.5", "payment_source"),
+                (GENESIS, "USD", "10.5", "10.5", "payment_destination"),
+                (
+                    GENESIS,
+                    "XRP",
+                    "-0.000012",
+                    "999.999988",
+                    "transaction_cost"
+                ),
+                (GENESIS, "XRP", "-100", "899.999988", "payment_source"),
+                (FUNDED, "XRP", "100", "100", "payment_destination"),
+            ]
+        );
+        assert_eq!(rows.balance_changes[0].node_index, BigInt::from(-1));
+    }
+
+    fn exchange_change(
+        account: &str,
+        currency: &str,
+        counterparty: Option<&str>,
+    ) -> BalanceChangeRow {
+        BalanceChangeRow {
+            ledger_index: 90000000,
+            tx_index: BigInt::from(3),
+            node_index: BigInt::from(0),
+            account: account.to_string(),
+            change: "1".to_string(),
+            change_type: "exchange".to_string(),
+            counterparty: counterparty.map(str::to_string),
+            currency: currency.to_string(),
+            final_balance: "1".to_string(),
+            timestamp: DateTime::from_timestamp(0, 0).unwrap(),
+            tx_hash: "3333333333333333333333333333333333333333333333333333333333333333".to_string(),
+        }
+    }
+
+    fn pairs(changes: &[BalanceChangeRow]) -> Vec<(String, String)> {
+        exchange_pair_rows(changes)
+            .into_iter()
+            .map(|row| (row.base, row.counter))
+            .collect()
+    }
+
+    #[test]
+    fn derives_the_pair_of_an_offer_crossing() {
+        // GENESIS sells XRP for ISSUER's USD to FUNDED
+        let changes = [
+            exchange_change(GENESIS, "XRP", None),
+            exchange_change(GENESIS, "USD", Some(ISSUER)),
+            exchange_change(FUNDED, "USD", Some(ISSUER)),
+            exchange_change(FUNDED, "XRP", None),
+        ];
+
+        let rows = exchange_pair_rows(&changes);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].tx_index, BigInt::from(3));
+        assert_eq!(
+            pairs(&changes),
+            [("XRP".to_string(), format!("USD+{}", ISSUER))]
+        );
+    }
+
+    #[test]
+    fn derives_both_xrp_pairs_of_an_autobridged_trade() {
+        // GENESIS takes EUR for USD through XRP, from FUNDED's USD/XRP offer and the XRP/EUR
+        // offer of "rMaker"
+        let changes = [
+            exchange_change(GENESIS, "USD", Some(ISSUER)),
+            exchange_change(GENESIS, "EUR", Some(ISSUER)),
+            exchange_change(FUNDED, "USD", Some(ISSUER)),
+            exchange_change(FUNDED, "XRP", None),
+            exchange_change("rMaker", "XRP", None),
+            exchange_change("rMaker", "EUR", Some(ISSUER)),
+        ];
+
+        assert_eq!(
+            pairs(&changes),
+            [
+                ("XRP".to_string(), format!("EUR+{}", ISSUER)),
+                ("XRP".to_string(), format!("USD+{}", ISSUER)),
+            ]
+        );
+    }
+
+    #[test]
+    fn derives_no_pairs_without_exchanges() {
+        let rows = derive_rows(&ledger()).unwrap();
+
+        assert!(rows.exchange_pairs.is_empty());
+    }
+}
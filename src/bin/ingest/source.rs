@@ -0,0 +1,106 @@
+use crate::error::IngestError;
+use crate::ledger::{integer, LedgerDump};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+/// Where ledgers are read from: saved `ledger` responses, or a rippled JSON-RPC endpoint.
+pub enum Source {
+    Files,
+    Rpc {
+        client: reqwest::Client,
+        url: String,
+        binary: bool,
+    },
+}
+
+/// A ledger to load, a file for files and an index for JSON-RPC.
+pub enum LedgerRef {
+    File(PathBuf),
+    Index(i64),
+}
+
+impl Source {
+    pub async fn load(&self, ledger: &LedgerRef) -> Result<LedgerDump, IngestError> {
+        let response = match (self, ledger) {
+            (_, LedgerRef::File(path)) => {
+                let contents = tokio::fs::read(path)
+                    .await
+                    .map_err(|err| IngestError::Read(path.clone(), err))?;
+                serde_json::from_slice::<Value>(&contents)?
+            }
+            (
+                Source::Rpc {
+                    client,
+                    url,
+                    binary,
+                },
+                LedgerRef::Index(ledger_index),
+            ) => {
+                let params = json!({
+                    "ledger_index": ledger_index,
+                    "transactions": true,
+                    "expand": true,
+                    "binary": binary,
+                });
+                request_ledger(client, url, params).await?
+            }
+            (Source::Files, LedgerRef::Index(ledger_index)) => {
+                return Err(IngestError::InvalidLedger(format!(
+                    "ledger {} has no file",
+                    ledger_index
+                )))
+            }
+        };
+        LedgerDump::from_response(response)
+    }
+}
+
+/// Index of the latest validated ledger of the endpoint.
+pub async fn validated_ledger_index(
+    client: &reqwest::Client,
+    url: &str,
+) -> Result<i64, IngestError> {
+    let response = request_ledger(client, url, json!({ "ledger_index": "validated" })).await?;
+    integer(response.pointer("/result/ledger_index"))
+        .ok_or_else(|| IngestError::Rpc("no validated ledger".to_string()))
+}
+
+/// The files of `paths`, with directories replaced by their files, in name order. Ledgers are
+/// written in this order, so files should be named so that it follows their indexes.
+pub fn list_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>, IngestError> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let entries = std::fs::read_dir(path).map_err(|err| read_error(path, err))?;
+            for entry in entries {
+                let entry = entry.map_err(|err| read_error(path, err))?;
+                if entry.path().is_file() {
+                    files.push(entry.path());
+                }
+            }
+        } else {
+            files.push(path.clone());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+async fn request_ledger(
+    client: &reqwest::Client,
+    url: &str,
+    params: Value,
+) -> Result<Value, IngestError> {
+    let body = json!({ "method": "ledger", "params": [params] });
+    let response = client
+        .post(url)
+        .json(&body)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(response.json::<Value>().await?)
+}
+
+fn read_error(path: &Path, err: std::io::Error) -> IngestError {
+    IngestError::Read(path.to_path_buf(), err)
+}
@@ -0,0 +1,219 @@
+use crate::consts::{
+    ACCOUNTS_TABLE, BALANCE_CHANGES_TABLE, DAILY_LEDGERS_TABLE, EXCHANGE_PAIRS_TABLE, LEDGER_TABLE,
+    PAYMENTS_TABLE, TRANSACTIONS_TABLE,
+};
+use crate::error::IngestError;
+use crate::rows::LedgerRows;
+use crate::{INGEST_CHECKPOINTS_TABLE, INGEST_WRITE_CONCURRENCY};
+use chrono::Utc;
+use futures::{StreamExt, TryStreamExt};
+use scylla::prepared_statement::PreparedStatement;
+use scylla::serialize::row::SerializeRow;
+use scylla::transport::errors::QueryError;
+use scylla::Session;
+
+/// Writes derived rows with prepared upserts, so a ledger written twice ends up the same.
+pub struct Writer {
+    session: Session,
+    insert_ledger: PreparedStatement,
+    insert_daily_ledger: PreparedStatement,
+    insert_transaction: PreparedStatement,
+    insert_account: PreparedStatement,
+    insert_payment: PreparedStatement,
+    insert_balance_change: PreparedStatement,
+    insert_exchange_pair: PreparedStatement,
+}
+
+impl Writer {
+    pub async fn new(session: Session) -> Result<Self, QueryError> {
+        let insert_ledger = session
+            .prepare(insert_query(
+                LEDGER_TABLE,
+                &[
+                    "ledger_index",
+                    "ledger_hash",
+                    "parent_hash",
+                    "account_hash",
+                    "transaction_hash",
+                    "close_flags",
+                    "close_time",
+                    "parent_close_time",
+                    "total_coins",
+                    "tx_count",
+                    "ledger_processed",
+                ],
+            ))
+            .await?;
+        let insert_daily_ledger = session
+            .prepare(insert_query(
+                DAILY_LEDGERS_TABLE,
+                &["ledger_close_day", "ledger_index", "close_time"],
+            ))
+            .await?;
+        let insert_transaction = session
+            .prepare(insert_query(
+                TRANSACTIONS_TABLE,
+                &[
+                    "account",
+                    "hash",
+                    "ctid",
+                    "ledger_index",
+                    "tx_index",
+                    "tx_type",
+                    "timestamp",
+                    "flags",
+                    "fee",
+                    "sequence",
+                    "result",
+                    "meta",
+                    "tx",
+                ],
+            ))
+            .await?;
+        let insert_account = session
+            .prepare(insert_query(
+                ACCOUNTS_TABLE,
+                &[
+                    "ledger_index",
+                    "tx_index",
+                    "account",
+                    "client",
+                    "initial_balance",
+                    "parent",
+                    "timestamp",
+                    "tx_hash",
+                ],
+            ))
+            .await?;
+        let insert_payment = session
+            .prepare(insert_query(
+                PAYMENTS_TABLE,
+                &[
+                    "tx_hash",
+                    "ledger_index",
+                    "tx_index",
+                    "source",
+                    "source_currency",
+                    "source_currency_issuer",
+                    "destination",
+                    "destination_currency",
+                    "destination_currency_issuer",
+                    "amount",
+                    "delivered_amount",
+                    "transaction_cost",
+                    "destination_tag",
+                    "source_tag",
+                    "timestamp",
+                ],
+            ))
+            .await?;
+        let insert_balance_change = session
+            .prepare(insert_query(
+                BALANCE_CHANGES_TABLE,
+                &[
+                    "ledger_index",
+                    "tx_index",
+                    "node_index",
+                    "account",
+                    "change",
+                    "change_type",
+                    "counterparty",
+                    "currency",
+                    "final_balance",
+                    "timestamp",
+                    "tx_hash",
+                ],
+            ))
+            .await?;
+        let insert_exchange_pair = session
+            .prepare(insert_query(
+                EXCHANGE_PAIRS_TABLE,
+                &["base", "counter", "ledger_index", "tx_index", "tx_hash"],
+            ))
+            .await?;
+
+        Ok(Writer {
+            session,
+            insert_ledger,
+            insert_daily_ledger,
+            insert_transaction,
+            insert_account,
+            insert_payment,
+            insert_balance_change,
+            insert_exchange_pair,
+        })
+    }
+
+    /// Writes the rows of a ledger. The ledger itself is written after its transactions and
+    /// balance changes, and its day last, so readers that find it find all of its rows.
+    pub async fn write(&self, rows: &LedgerRows) -> Result<(), IngestError> {
+        tokio::try_join!(
+            self.write_all(&self.insert_transaction, &rows.transactions),
+            self.write_all(&self.insert_account, &rows.accounts),
+            self.write_all(&self.insert_payment, &rows.payments),
+            self.write_all(&self.insert_balance_change, &rows.balance_changes),
+            self.write_all(&self.insert_exchange_pair, &rows.exchange_pairs),
+        )?;
+        self.session
+            .execute(&self.insert_ledger, &rows.ledger)
+            .await?;
+        self.session
+            .execute(&self.insert_daily_ledger, &rows.daily_ledger)
+            .await?;
+        Ok(())
+    }
+
+    async fn write_all<T: SerializeRow>(
+        &self,
+        statement: &PreparedStatement,
+        rows: &[T],
+    ) -> Result<(), QueryError> {
+        futures::stream::iter(rows)
+            .map(|row| self.session.execute(statement, row))
+            .buffer_unordered(INGEST_WRITE_CONCURRENCY)
+            .try_for_each(|_| async { Ok(()) })
+            .await
+    }
+
+    /// Last ledger written under the checkpoint `name`, if any.
+    pub async fn checkpoint(&self, name: &str) -> Result<Option<i64>, IngestError> {
+        let query = format!(
+            "SELECT ledger_index FROM {} WHERE name=?;",
+            INGEST_CHECKPOINTS_TABLE
+        );
+        println!("Query: {}", query);
+        let query_result = self.session.query(query, (name,)).await?;
+        // todo: better row error handling
+        let ledger_index = query_result
+            .rows_typed_or_empty::<(i64,)>()
+            .filter_map(|row| row.ok())
+            .map(|(ledger_index,)| ledger_index)
+            .next();
+        Ok(ledger_index)
+    }
+
+    pub async fn save_checkpoint(&self, name: &str, ledger_index: i64) -> Result<(), IngestError> {
+        let query = format!(
+            "INSERT INTO {} (name, ledger_index, updated_at) VALUES (?, ?, ?);",
+            INGEST_CHECKPOINTS_TABLE
+        );
+        self.session
+            .query(query, (name, ledger_index, Utc::now()))
+            .await?;
+        Ok(())
+    }
+}
+
+// Bind markers are named after their columns, which row structs are matched on
+fn insert_query(table: &str, columns: &[&str]) -> String {
+    let markers = columns
+        .iter()
+        .map(|column| format!(":{}", column))
+        .collect::<Vec<String>>();
+    format!(
+        "INSERT INTO {} ({}) VALUES ({});",
+        table,
+        columns.join(", "),
+        markers.join(", ")
+    )
+}
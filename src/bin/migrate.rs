@@ -0,0 +1,63 @@
+// Creates or upgrades the keyspace to the schema the server expects. Run before deploying a
+// server that adds migrations, the server refuses to start on an older schema.
+
+#[allow(dead_code)]
+#[path = "../utils/consts.rs"]
+mod consts;
+#[path = "../schema/mod.rs"]
+mod schema;
+
+use crate::consts::{KEYSPACE, SCYLLA_NODES};
+use clap::Parser;
+use scylla::{Session, SessionBuilder};
+
+// Replicas of each row in a new keyspace, one per node of the default cluster
+static DEFAULT_REPLICATION_FACTOR: u32 = 3;
+
+/// Applies the XRPL Data API migrations that are newer than the keyspace's schema.
+#[derive(Parser)]
+struct Args {
+    /// Keyspace to create or upgrade
+    #[arg(long, default_value = KEYSPACE)]
+    keyspace: String,
+
+    /// Replicas of each row when the keyspace is created, 1 for a single node. Existing
+    /// keyspaces keep their replication.
+    #[arg(long, default_value_t = DEFAULT_REPLICATION_FACTOR)]
+    replication_factor: u32,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+    // The name is part of the statements, so only plain identifiers are accepted
+    if !schema::is_valid_keyspace(&args.keyspace) || args.replication_factor == 0 {
+        eprintln!(
+            "Invalid keyspace {} or replication factor {}",
+            args.keyspace, args.replication_factor
+        );
+        std::process::exit(1);
+    }
+
+    println!("Connecting to scylla.");
+    let session: Session = SessionBuilder::new()
+        .known_nodes(SCYLLA_NODES)
+        .build()
+        .await
+        .expect("Failed to connect to scylla nodes");
+
+    let applied = schema::applied_version(&session, &args.keyspace)
+        .await
+        .expect("Failed to read the schema version");
+    let expected = schema::expected_version();
+    println!("Schema is at version {}, latest is {}", applied, expected);
+
+    let replication = schema::replication(args.replication_factor);
+    match schema::migrate(&session, &args.keyspace, &replication).await {
+        Ok(version) => println!("Schema is at version {}", version),
+        Err(err) => {
+            eprintln!("Migration failed: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use crate::utils::consts::{
+    DAILY_LEDGERS_TABLE, GAP_SCAN_CHUNK, GAP_SCAN_CONCURRENCY, LEDGER_TABLE,
+};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use futures::StreamExt;
+use scylla::transport::errors::QueryError;
+use scylla::Session;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use utoipa::ToSchema;
+
+// Shared by the server and the `gaps` binary
+
+// Processed flag and close time of a ledger in `ledgers`
+type LedgerHeader = (Option<bool>, Option<DateTime<Utc>>);
+
+/// An inclusive range of ledger indexes.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, ToSchema)]
+pub struct LedgerRange {
+    pub start: i64,
+    pub end: i64,
+}
+
+/// Ledgers of the scanned range the API can not fully serve, as ascending ranges.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LedgerGaps {
+    pub start: i64,
+    pub end: i64,
+    /// Ledgers without a header in `ledgers`, which the API answers with 404
+    pub missing: Vec<LedgerRange>,
+    /// Ledgers whose header is not marked as processed, so their rows may be incomplete
+    pub unprocessed: Vec<LedgerRange>,
+    /// Ledgers missing from `daily_ledgers`, which lookups by time skip
+    pub unindexed: Vec<LedgerRange>,
+}
+
+impl LedgerGaps {
+    /// Ranges of every kind.
+    pub fn gap_count(&self) -> usize {
+        self.missing.len() + self.unprocessed.len() + self.unindexed.len()
+    }
+
+    /// Ledgers within the ranges of every kind.
+    pub fn ledger_count(&self) -> i64 {
+        [&self.missing, &self.unprocessed, &self.unindexed]
+            .into_iter()
+            .flatten()
+            .map(|range| range.end - range.start + 1)
+            .sum()
+    }
+}
+
+/// Scans the ledgers from `start` to `end` for missing, unprocessed and unindexed ledgers.
+/// Headers are looked up in chunks and checked against the day they closed on, so the scan
+/// holds a chunk and a day of ledgers at a time whatever the range.
+pub async fn find_gaps(session: &Session, start: i64, end: i64) -> Result<LedgerGaps, QueryError> {
+    let query = format!(
+        "SELECT ledger_index, ledger_processed, close_time from {} WHERE ledger_index IN ?;",
+        LEDGER_TABLE
+    );
+    println!("Query: {}", query);
+    let mut chunks = futures::stream::iter(ledger_chunks(start, end))
+        .map(|chunk| {
+            let query = query.as_str();
+            async move {
+                let query_result = session.query(query, (&chunk,)).await?;
+                Ok::<_, QueryError>((chunk, query_result))
+            }
+        })
+        .buffered(GAP_SCAN_CONCURRENCY);
+
+    let mut gaps = LedgerGaps {
+        start,
+        end,
+        missing: Vec::new(),
+        unprocessed: Vec::new(),
+        unindexed: Vec::new(),
+    };
+    // Ledgers close in order, so a day is complete once a ledger of the next day is found
+    let mut day: Option<(NaiveDate, Vec<i64>)> = None;
+    while let Some(chunk) = chunks.next().await {
+        let (chunk, query_result) = chunk?;
+        // todo: better row error handling
+        let headers = query_result
+            .rows_typed_or_empty::<(i64, Option<bool>, Option<DateTime<Utc>>)>()
+            .filter_map(|row| row.ok())
+            .map(|(ledger_index, processed, close_time)| (ledger_index, (processed, close_time)))
+            .collect::<HashMap<i64, LedgerHeader>>();
+
+        for (ledger_index, close_day) in push_chunk_gaps(&mut gaps, &chunk, &headers) {
+            if let Some((previous_day, ledgers)) = day.take_if(|(day, _)| *day != close_day) {
+                find_unindexed(session, previous_day, &ledgers, &mut gaps.unindexed).await?;
+            }
+            day.get_or_insert_with(|| (close_day, Vec::new()))
+                .1
+                .push(ledger_index);
+        }
+    }
+    if let Some((day, ledgers)) = day {
+        find_unindexed(session, day, &ledgers, &mut gaps.unindexed).await?;
+    }
+
+    Ok(gaps)
+}
+
+/// Index of the latest ledger in `daily_ledgers`, looking back to yesterday's partition
+/// while today's is still empty.
+pub async fn latest_ledger_index(session: &Session) -> Result<Option<i64>, QueryError> {
+    let query = format!(
+        "SELECT ledger_index from {} WHERE ledger_close_day=? \
+        ORDER BY ledger_index DESC LIMIT 1;",
+        DAILY_LEDGERS_TABLE
+    );
+    println!("Query: {}", query);
+    let today = Utc::now().date_naive();
+    for day in [today, today - Duration::days(1)] {
+        let query_result = session.query(query.as_str(), (day,)).await?;
+        // todo: better row error handling
+        let latest = query_result
+            .rows_typed_or_empty::<(i64,)>()
+            .filter_map(|row| row.ok())
+            .map(|(ledger_index,)| ledger_index)
+            .next();
+        if latest.is_some() {
+            return Ok(latest);
+        }
+    }
+    Ok(None)
+}
+
+// Adds the ledgers of `chunk` without a header or not processed to `gaps`, and returns the day
+// each ledger with a close time closed on
+fn push_chunk_gaps(
+    gaps: &mut LedgerGaps,
+    chunk: &[i64],
+    headers: &HashMap<i64, LedgerHeader>,
+) -> Vec<(i64, NaiveDate)> {
+    let mut close_days = Vec::new();
+    for ledger_index in chunk {
+        let Some((processed, close_time)) = headers.get(ledger_index) else {
+            push_ledger(&mut gaps.missing, *ledger_index);
+            continue;
+        };
+        if *processed != Some(true) {
+            push_ledger(&mut gaps.unprocessed, *ledger_index);
+        }
+        if let Some(close_time) = close_time {
+            close_days.push((*ledger_index, close_time.date_naive()));
+        }
+    }
+    close_days
+}
+
+// Adds the ledgers of `day`, ascending, that its partition of `daily_ledgers` lacks
+async fn find_unindexed(
+    session: &Session,
+    day: NaiveDate,
+    ledgers: &[i64],
+    unindexed: &mut Vec<LedgerRange>,
+) -> Result<(), QueryError> {
+    let (Some(first), Some(last)) = (ledgers.first(), ledgers.last()) else {
+        return Ok(());
+    };
+    let query = format!(
+        "SELECT ledger_index from {} WHERE ledger_close_day=? \
+        AND ledger_index>=? AND ledger_index<=?;",
+        DAILY_LEDGERS_TABLE
+    );
+    let query_result = session.query(query, (day, first, last)).await?;
+    // todo: better row error handling
+    let indexed = query_result
+        .rows_typed_or_empty::<(i64,)>()
+        .filter_map(|row| row.ok())
+        .map(|(ledger_index,)| ledger_index)
+        .collect::<HashSet<i64>>();
+    for ledger_index in ledgers {
+        if !indexed.contains(ledger_index) {
+            push_ledger(unindexed, *ledger_index);
+        }
+    }
+    Ok(())
+}
+
+// Extends the last range when the ledger follows it, ledgers are pushed in ascending order
+fn push_ledger(ranges: &mut Vec<LedgerRange>, ledger_index: i64) {
+    match ranges.last_mut() {
+        Some(range) if range.end + 1 == ledger_index => range.end = ledger_index,
+        _ => ranges.push(LedgerRange {
+            start: ledger_index,
+            end: ledger_index,
+        }),
+    }
+}
+
+fn ledger_chunks(start: i64, end: i64) -> impl Iterator<Item = Vec<i64>> {
+    (start..=end)
+        .step_by(GAP_SCAN_CHUNK)
+        .map(move |first| (first..=end.min(first + GAP_SCAN_CHUNK as i64 - 1)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ledger_chunks, push_chunk_gaps, push_ledger, LedgerGaps, LedgerRange};
+    use crate::utils::consts::GAP_SCAN_CHUNK;
+    use chrono::{TimeZone, Utc};
+    use std::collections::HashMap;
+
+    fn range(start: i64, end: i64) -> LedgerRange {
+        LedgerRange { start, end }
+    }
+
+    #[test]
+    fn push_ledger_merges_consecutive_ledgers() {
+        let mut ranges = Vec::new();
+        for ledger_index in [5, 6, 7, 9, 11, 12] {
+            push_ledger(&mut ranges, ledger_index);
+        }
+        assert_eq!(ranges, [range(5, 7), range(9, 9), range(11, 12)]);
+    }
+
+    #[test]
+    fn ledger_chunks_cover_the_range_once() {
+        let chunk = GAP_SCAN_CHUNK as i64;
+        let chunks = ledger_chunks(10, 10 + 2 * chunk).collect::<Vec<Vec<i64>>>();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0], (10..10 + chunk).collect::<Vec<i64>>());
+        assert_eq!(
+            chunks[1],
+            (10 + chunk..10 + 2 * chunk).collect::<Vec<i64>>()
+        );
+        // The last chunk stops at the end of the range
+        assert_eq!(chunks[2], [10 + 2 * chunk]);
+
+        assert_eq!(ledger_chunks(3, 3).collect::<Vec<Vec<i64>>>(), [[3]]);
+        assert_eq!(ledger_chunks(4, 3).count(), 0);
+    }
+
+    #[test]
+    fn counts_gaps_and_their_ledgers() {
+        let gaps = LedgerGaps {
+            start: 1,
+            end: 100,
+            missing: vec![range(1, 3), range(10, 10)],
+            unprocessed: vec![range(20, 29)],
+            unindexed: Vec::new(),
+        };
+        assert_eq!(gaps.gap_count(), 3);
+        assert_eq!(gaps.ledger_count(), 14);
+    }
+
+    #[test]
+    fn merges_gaps_across_chunks_and_keeps_the_range_ends() {
+        let chunk = GAP_SCAN_CHUNK as i64;
+        let (start, end) = (1, 2 * chunk + 50);
+        let close_time = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        // Missing at the start, across the first chunk boundary and at the end, unprocessed
+        // across the second boundary
+        let missing = |ledger_index: i64| {
+            ledger_index <= 2
+                || (chunk - 1..=chunk + 2).contains(&ledger_index)
+                || ledger_index >= end - 1
+        };
+        let headers = (start..=end)
+            .filter(|ledger_index| !missing(*ledger_index))
+            .map(|ledger_index| {
+                let processed = !(2 * chunk - 1..=2 * chunk + 3).contains(&ledger_index);
+                (ledger_index, (Some(processed), Some(close_time)))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let mut gaps = LedgerGaps {
+            start,
+            end,
+            missing: Vec::new(),
+            unprocessed: Vec::new(),
+            unindexed: Vec::new(),
+        };
+        let mut close_days = Vec::new();
+        for ledgers in ledger_chunks(start, end) {
+            close_days.extend(push_chunk_gaps(&mut gaps, &ledgers, &headers));
+        }
+
+        assert_eq!(
+            gaps.missing,
+            [
+                range(1, 2),
+                range(chunk - 1, chunk + 2),
+                range(end - 1, end)
+            ]
+        );
+        assert_eq!(gaps.unprocessed, [range(2 * chunk - 1, 2 * chunk + 3)]);
+        assert_eq!(close_days.len(), headers.len());
+        assert_eq!(close_days.first().map(|(ledger, _)| *ledger), Some(3));
+        assert_eq!(close_days.last().map(|(ledger, _)| *ledger), Some(end - 2));
+    }
+}
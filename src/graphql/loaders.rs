@@ -0,0 +1,180 @@
+use crate::handlers::account::{get_account, get_account_children};
+use crate::handlers::balance_change::get_balance_changes;
+use crate::handlers::ledger::{get_ledger, LedgerIdentifier};
+use crate::handlers::payment::{get_ledger_payments, get_payments, PaymentDirection};
+use crate::handlers::transaction::{get_ledger_transactions, get_transactions};
+use crate::models::account::Account;
+use crate::models::balance_change::BalanceChange;
+use crate::models::ledger::Ledger;
+use crate::models::payment::Payment;
+use crate::models::transaction::Transaction;
+use crate::utils::consts::GRAPHQL_LOOKUP_CONCURRENCY;
+use crate::utils::errors::DataApiError;
+use crate::utils::rows::ScanBudget;
+use async_graphql::dataloader::Loader;
+use futures::{StreamExt, TryStreamExt};
+use scylla::Session;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::Arc;
+
+/// Runs the lookup of every distinct key of a batch concurrently. Keys without data are left
+/// out, so they resolve to `None`.
+async fn load_each<K, V, F, Fut>(keys: &[K], lookup: F) -> Result<HashMap<K, V>, Arc<DataApiError>>
+where
+    K: Clone + Eq + Hash,
+    F: Fn(K) -> Fut,
+    Fut: Future<Output = Result<V, DataApiError>>,
+{
+    futures::stream::iter(keys.iter().cloned())
+        .map(|key| {
+            let value = lookup(key.clone());
+            async move {
+                match value.await {
+                    Ok(value) => Ok(Some((key, value))),
+                    Err(DataApiError::NoDataReturned) => Ok(None),
+                    Err(err) => {
+                        eprintln!("{}", err);
+                        Err(Arc::new(err))
+                    }
+                }
+            }
+        })
+        .buffer_unordered(GRAPHQL_LOOKUP_CONCURRENCY)
+        .try_filter_map(|entry| async move { Ok(entry) })
+        .try_collect()
+        .await
+}
+
+pub struct LedgerLoader(pub Arc<Session>);
+
+impl Loader<i64> for LedgerLoader {
+    type Value = Ledger;
+    type Error = Arc<DataApiError>;
+
+    async fn load(&self, keys: &[i64]) -> Result<HashMap<i64, Ledger>, Self::Error> {
+        load_each(keys, |ledger_index| async move {
+            let identifier = LedgerIdentifier::BigInt(ledger_index);
+            get_ledger(&self.0, "ledger_index", &identifier).await
+        })
+        .await
+    }
+}
+
+pub struct TransactionLoader(pub Arc<Session>);
+
+impl Loader<String> for TransactionLoader {
+    type Value = Transaction;
+    type Error = Arc<DataApiError>;
+
+    async fn load(&self, keys: &[String]) -> Result<HashMap<String, Transaction>, Self::Error> {
+        load_each(keys, |hash| async move {
+            let transactions = get_transactions(&self.0, "hash", (&hash,)).await?;
+            transactions
+                .into_iter()
+                .next()
+                .ok_or(DataApiError::NoDataReturned)
+        })
+        .await
+    }
+}
+
+pub struct LedgerTransactionsLoader(pub Arc<Session>, pub ScanBudget);
+
+impl Loader<i64> for LedgerTransactionsLoader {
+    type Value = Vec<Transaction>;
+    type Error = Arc<DataApiError>;
+
+    async fn load(&self, keys: &[i64]) -> Result<HashMap<i64, Vec<Transaction>>, Self::Error> {
+        load_each(keys, |ledger_index| {
+            get_ledger_transactions(&self.0, ledger_index, &self.1)
+        })
+        .await
+    }
+}
+
+// Payments are only indexed by account and ledger, so payments of a transaction are taken
+// from its ledger's payments
+pub struct LedgerPaymentsLoader(pub Arc<Session>, pub ScanBudget);
+
+impl Loader<i64> for LedgerPaymentsLoader {
+    type Value = Vec<Payment>;
+    type Error = Arc<DataApiError>;
+
+    async fn load(&self, keys: &[i64]) -> Result<HashMap<i64, Vec<Payment>>, Self::Error> {
+        load_each(keys, |ledger_index| {
+            get_ledger_payments(&self.0, ledger_index, &self.1)
+        })
+        .await
+    }
+}
+
+pub struct AccountLoader(pub Arc<Session>);
+
+impl Loader<String> for AccountLoader {
+    type Value = Account;
+    type Error = Arc<DataApiError>;
+
+    async fn load(&self, keys: &[String]) -> Result<HashMap<String, Account>, Self::Error> {
+        load_each(keys, |account| async move {
+            get_account(&self.0, &account).await
+        })
+        .await
+    }
+}
+
+/// Lists of an account are keyed by the account and the requested limit.
+pub type AccountPage = (String, i32);
+
+pub struct AccountPaymentsLoader(pub Arc<Session>);
+
+impl Loader<AccountPage> for AccountPaymentsLoader {
+    type Value = Vec<Payment>;
+    type Error = Arc<DataApiError>;
+
+    async fn load(
+        &self,
+        keys: &[AccountPage],
+    ) -> Result<HashMap<AccountPage, Vec<Payment>>, Self::Error> {
+        load_each(keys, |(account, limit)| async move {
+            get_payments(&self.0, &account, &[PaymentDirection::Sent], limit).await
+        })
+        .await
+    }
+}
+
+pub struct AccountBalanceChangesLoader(pub Arc<Session>);
+
+impl Loader<AccountPage> for AccountBalanceChangesLoader {
+    type Value = Vec<BalanceChange>;
+    type Error = Arc<DataApiError>;
+
+    async fn load(
+        &self,
+        keys: &[AccountPage],
+    ) -> Result<HashMap<AccountPage, Vec<BalanceChange>>, Self::Error> {
+        load_each(keys, |(account, limit)| async move {
+            get_balance_changes(&self.0, "account", &account, limit).await
+        })
+        .await
+    }
+}
+
+pub struct AccountChildrenLoader(pub Arc<Session>);
+
+impl Loader<AccountPage> for AccountChildrenLoader {
+    type Value = Vec<Account>;
+    type Error = Arc<DataApiError>;
+
+    async fn load(
+        &self,
+        keys: &[AccountPage],
+    ) -> Result<HashMap<AccountPage, Vec<Account>>, Self::Error> {
+        load_each(keys, |(account, limit)| async move {
+            let children = get_account_children(&self.0, &account, limit, None).await?;
+            Ok(children.items)
+        })
+        .await
+    }
+}
@@ -0,0 +1,125 @@
+pub mod loaders;
+pub mod types;
+
+use crate::graphql::loaders::{
+    AccountBalanceChangesLoader, AccountChildrenLoader, AccountLoader, AccountPaymentsLoader,
+    LedgerLoader, LedgerPaymentsLoader, LedgerTransactionsLoader, TransactionLoader,
+};
+use crate::graphql::types::{
+    load_account, load_ledger, load_transaction, Account, DailyLedger, Ledger, Transaction,
+};
+use crate::handlers::daily_ledger::get_ledgers_on_day;
+use crate::handlers::ledger::{get_ledger, LedgerIdentifier};
+use crate::utils::auth::Client;
+use crate::utils::consts::{GRAPHQL_MAX_COMPLEXITY, GRAPHQL_MAX_DEPTH};
+use crate::utils::errors::DataApiError;
+use crate::utils::rows::ScanBudget;
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, Error, Object, Request, Result, Schema,
+};
+use chrono::NaiveDate;
+use scylla::Session;
+use std::sync::Arc;
+
+pub type GraphQlSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// A ledger by index or hash.
+    async fn ledger(
+        &self,
+        ctx: &Context<'_>,
+        index: Option<i64>,
+        hash: Option<String>,
+    ) -> Result<Option<Ledger>> {
+        match (index, hash) {
+            (Some(index), None) => load_ledger(ctx, index).await,
+            (None, Some(hash)) => {
+                let session = ctx.data_unchecked::<Arc<Session>>();
+                match get_ledger(session, "ledger_hash", &LedgerIdentifier::String(hash)).await {
+                    Ok(ledger) => Ok(Some(Ledger(ledger))),
+                    Err(DataApiError::NoDataReturned) => Ok(None),
+                    Err(err) => {
+                        eprintln!("{}", err);
+                        Err(Error::new(err.to_string()))
+                    }
+                }
+            }
+            _ => Err(Error::new("exactly one of index and hash is required")),
+        }
+    }
+
+    async fn transaction(&self, ctx: &Context<'_>, hash: String) -> Result<Option<Transaction>> {
+        load_transaction(ctx, &hash).await
+    }
+
+    async fn account(&self, ctx: &Context<'_>, address: String) -> Result<Option<Account>> {
+        load_account(ctx, &address).await
+    }
+
+    /// Ledgers closed on a day.
+    async fn daily_ledgers(&self, ctx: &Context<'_>, day: NaiveDate) -> Result<Vec<DailyLedger>> {
+        let session = ctx.data_unchecked::<Arc<Session>>();
+        match get_ledgers_on_day(session, day).await {
+            Ok(ledgers) => Ok(ledgers.into_iter().map(DailyLedger).collect()),
+            Err(DataApiError::NoDataReturned) => Ok(Vec::new()),
+            Err(err) => {
+                eprintln!("{}", err);
+                Err(Error::new(err.to_string()))
+            }
+        }
+    }
+}
+
+pub fn build_schema() -> GraphQlSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(GRAPHQL_MAX_DEPTH)
+        .limit_complexity(GRAPHQL_MAX_COMPLEXITY)
+        .finish()
+}
+
+/// Attaches the session, the caller and fresh loaders to a request. Loaders only live for one
+/// request, so lookups are batched and deduplicated within it but never cached across callers.
+/// Loaders that read whole partitions share the request's scan budget.
+pub fn with_request_data(
+    request: Request,
+    session: &Arc<Session>,
+    client: Client,
+    budget: ScanBudget,
+) -> Request {
+    request
+        .data(session.clone())
+        .data(client)
+        .data(DataLoader::new(LedgerLoader(session.clone()), tokio::spawn))
+        .data(DataLoader::new(
+            TransactionLoader(session.clone()),
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(
+            LedgerTransactionsLoader(session.clone(), budget.clone()),
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(
+            LedgerPaymentsLoader(session.clone(), budget),
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(
+            AccountLoader(session.clone()),
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(
+            AccountPaymentsLoader(session.clone()),
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(
+            AccountBalanceChangesLoader(session.clone()),
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(
+            AccountChildrenLoader(session.clone()),
+            tokio::spawn,
+        ))
+}
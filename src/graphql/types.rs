@@ -0,0 +1,422 @@
+use crate::graphql::loaders::{
+    AccountBalanceChangesLoader, AccountChildrenLoader, AccountLoader, AccountPaymentsLoader,
+    LedgerLoader, LedgerPaymentsLoader, LedgerTransactionsLoader, TransactionLoader,
+};
+use crate::models;
+use crate::utils::auth::Client;
+use crate::utils::consts::{DEFAULT_RESULT_LIMIT, GRAPHQL_LEDGER_LIST_COMPLEXITY};
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{Context, Error, Object, Result};
+use chrono::{DateTime, Utc};
+
+// Objects wrap the models and expose the same fields, big integers and binary fields as strings
+
+pub struct Ledger(pub models::ledger::Ledger);
+
+#[Object]
+impl Ledger {
+    async fn ledger_index(&self) -> i64 {
+        self.0.ledger_index
+    }
+
+    async fn ledger_hash(&self) -> &str {
+        &self.0.ledger_hash
+    }
+
+    async fn parent_hash(&self) -> &str {
+        &self.0.parent_hash
+    }
+
+    async fn account_hash(&self) -> &str {
+        &self.0.account_hash
+    }
+
+    async fn transaction_hash(&self) -> &str {
+        &self.0.transaction_hash
+    }
+
+    async fn close_flags(&self) -> i32 {
+        self.0.close_flags
+    }
+
+    async fn close_time(&self) -> DateTime<Utc> {
+        self.0.close_time
+    }
+
+    async fn parent_close_time(&self) -> DateTime<Utc> {
+        self.0.parent_close_time
+    }
+
+    async fn total_coins(&self) -> i64 {
+        self.0.total_coins
+    }
+
+    async fn tx_count(&self) -> String {
+        self.0.tx_count.to_string()
+    }
+
+    #[graphql(complexity = "GRAPHQL_LEDGER_LIST_COMPLEXITY * child_complexity")]
+    async fn transactions(&self, ctx: &Context<'_>) -> Result<Vec<Transaction>> {
+        let transactions = ctx
+            .data_unchecked::<DataLoader<LedgerTransactionsLoader>>()
+            .load_one(self.0.ledger_index)
+            .await?;
+        Ok(transactions
+            .unwrap_or_default()
+            .into_iter()
+            .map(Transaction)
+            .collect())
+    }
+}
+
+pub struct Transaction(pub models::transaction::Transaction);
+
+#[Object]
+impl Transaction {
+    async fn account(&self) -> &str {
+        &self.0.account
+    }
+
+    async fn hash(&self) -> &str {
+        &self.0.hash
+    }
+
+    async fn ctid(&self) -> &str {
+        &self.0.ctid
+    }
+
+    async fn ledger_index(&self) -> i64 {
+        self.0.ledger_index
+    }
+
+    async fn tx_index(&self) -> String {
+        self.0.tx_index.to_string()
+    }
+
+    async fn tx_type(&self) -> &str {
+        &self.0.tx_type
+    }
+
+    async fn timestamp(&self) -> DateTime<Utc> {
+        self.0.timestamp
+    }
+
+    async fn flags(&self) -> i64 {
+        self.0.flags
+    }
+
+    async fn fee(&self) -> String {
+        self.0.fee.to_string()
+    }
+
+    async fn sequence(&self) -> i64 {
+        self.0.sequence
+    }
+
+    async fn result(&self) -> i16 {
+        self.0.result
+    }
+
+    async fn meta(&self) -> String {
+        String::from_utf8_lossy(&self.0.meta).to_string()
+    }
+
+    async fn tx(&self) -> String {
+        String::from_utf8_lossy(&self.0.tx).to_string()
+    }
+
+    async fn ledger(&self, ctx: &Context<'_>) -> Result<Option<Ledger>> {
+        load_ledger(ctx, self.0.ledger_index).await
+    }
+
+    async fn sender(&self, ctx: &Context<'_>) -> Result<Option<Account>> {
+        load_account(ctx, &self.0.account).await
+    }
+
+    #[graphql(complexity = "GRAPHQL_LEDGER_LIST_COMPLEXITY * child_complexity")]
+    async fn payments(&self, ctx: &Context<'_>) -> Result<Vec<Payment>> {
+        let payments = ctx
+            .data_unchecked::<DataLoader<LedgerPaymentsLoader>>()
+            .load_one(self.0.ledger_index)
+            .await?;
+        Ok(payments
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|payment| payment.tx_hash == self.0.hash)
+            .map(Payment)
+            .collect())
+    }
+}
+
+pub struct Payment(pub models::payment::Payment);
+
+#[Object]
+impl Payment {
+    async fn tx_hash(&self) -> &str {
+        &self.0.tx_hash
+    }
+
+    async fn ledger_index(&self) -> i64 {
+        self.0.ledger_index
+    }
+
+    async fn tx_index(&self) -> String {
+        self.0.tx_index.to_string()
+    }
+
+    async fn source(&self) -> &str {
+        &self.0.source
+    }
+
+    async fn source_currency(&self) -> &str {
+        &self.0.source_currency
+    }
+
+    async fn source_currency_issuer(&self) -> &str {
+        &self.0.source_currency_issuer
+    }
+
+    async fn destination(&self) -> &str {
+        &self.0.destination
+    }
+
+    async fn destination_currency(&self) -> &str {
+        &self.0.destination_currency
+    }
+
+    async fn destination_currency_issuer(&self) -> &str {
+        &self.0.destination_currency_issuer
+    }
+
+    async fn amount(&self) -> &str {
+        &self.0.amount
+    }
+
+    async fn delivered_amount(&self) -> &str {
+        &self.0.delivered_amount
+    }
+
+    async fn transaction_cost(&self) -> String {
+        self.0.transaction_cost.to_string()
+    }
+
+    async fn destination_tag(&self) -> Option<i64> {
+        self.0.destination_tag
+    }
+
+    async fn source_tag(&self) -> Option<i64> {
+        self.0.source_tag
+    }
+
+    async fn timestamp(&self) -> DateTime<Utc> {
+        self.0.timestamp
+    }
+
+    async fn transaction(&self, ctx: &Context<'_>) -> Result<Option<Transaction>> {
+        load_transaction(ctx, &self.0.tx_hash).await
+    }
+
+    async fn source_account(&self, ctx: &Context<'_>) -> Result<Option<Account>> {
+        load_account(ctx, &self.0.source).await
+    }
+
+    async fn destination_account(&self, ctx: &Context<'_>) -> Result<Option<Account>> {
+        load_account(ctx, &self.0.destination).await
+    }
+}
+
+pub struct BalanceChange(pub models::balance_change::BalanceChange);
+
+#[Object]
+impl BalanceChange {
+    async fn ledger_index(&self) -> i64 {
+        self.0.ledger_index
+    }
+
+    async fn tx_index(&self) -> String {
+        self.0.tx_index.to_string()
+    }
+
+    async fn node_index(&self) -> String {
+        self.0.node_index.to_string()
+    }
+
+    async fn account(&self) -> &str {
+        &self.0.account
+    }
+
+    async fn change(&self) -> &str {
+        &self.0.change
+    }
+
+    async fn change_type(&self) -> &str {
+        &self.0.change_type
+    }
+
+    async fn counterparty(&self) -> Option<&str> {
+        self.0.counterparty.as_deref()
+    }
+
+    async fn currency(&self) -> &str {
+        &self.0.currency
+    }
+
+    async fn final_balance(&self) -> &str {
+        &self.0.final_balance
+    }
+
+    async fn timestamp(&self) -> DateTime<Utc> {
+        self.0.timestamp
+    }
+
+    async fn tx_hash(&self) -> &str {
+        &self.0.tx_hash
+    }
+
+    async fn transaction(&self, ctx: &Context<'_>) -> Result<Option<Transaction>> {
+        load_transaction(ctx, &self.0.tx_hash).await
+    }
+}
+
+pub struct Account(pub models::account::Account);
+
+#[Object]
+impl Account {
+    async fn ledger_index(&self) -> i64 {
+        self.0.ledger_index
+    }
+
+    async fn tx_index(&self) -> String {
+        self.0.tx_index.to_string()
+    }
+
+    async fn account(&self) -> &str {
+        &self.0.account
+    }
+
+    async fn client(&self) -> Option<&str> {
+        self.0.client.as_deref()
+    }
+
+    async fn initial_balance(&self) -> &str {
+        &self.0.initial_balance
+    }
+
+    async fn parent(&self) -> &str {
+        &self.0.parent
+    }
+
+    async fn timestamp(&self) -> DateTime<Utc> {
+        self.0.timestamp
+    }
+
+    async fn tx_hash(&self) -> &str {
+        &self.0.tx_hash
+    }
+
+    async fn parent_account(&self, ctx: &Context<'_>) -> Result<Option<Account>> {
+        load_account(ctx, &self.0.parent).await
+    }
+
+    #[graphql(
+        complexity = "limit.unwrap_or(DEFAULT_RESULT_LIMIT).max(1) as usize * child_complexity"
+    )]
+    async fn payments(&self, ctx: &Context<'_>, limit: Option<i32>) -> Result<Vec<Payment>> {
+        let key = (self.0.account.clone(), page_limit(ctx, limit)?);
+        let payments = ctx
+            .data_unchecked::<DataLoader<AccountPaymentsLoader>>()
+            .load_one(key)
+            .await?;
+        Ok(payments
+            .unwrap_or_default()
+            .into_iter()
+            .map(Payment)
+            .collect())
+    }
+
+    #[graphql(
+        complexity = "limit.unwrap_or(DEFAULT_RESULT_LIMIT).max(1) as usize * child_complexity"
+    )]
+    async fn balance_changes(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i32>,
+    ) -> Result<Vec<BalanceChange>> {
+        let key = (self.0.account.clone(), page_limit(ctx, limit)?);
+        let balance_changes = ctx
+            .data_unchecked::<DataLoader<AccountBalanceChangesLoader>>()
+            .load_one(key)
+            .await?;
+        Ok(balance_changes
+            .unwrap_or_default()
+            .into_iter()
+            .map(BalanceChange)
+            .collect())
+    }
+
+    #[graphql(
+        complexity = "limit.unwrap_or(DEFAULT_RESULT_LIMIT).max(1) as usize * child_complexity"
+    )]
+    async fn children(&self, ctx: &Context<'_>, limit: Option<i32>) -> Result<Vec<Account>> {
+        let key = (self.0.account.clone(), page_limit(ctx, limit)?);
+        let children = ctx
+            .data_unchecked::<DataLoader<AccountChildrenLoader>>()
+            .load_one(key)
+            .await?;
+        Ok(children
+            .unwrap_or_default()
+            .into_iter()
+            .map(Account)
+            .collect())
+    }
+}
+
+pub struct DailyLedger(pub models::daily_ledger::DailyLedger);
+
+#[Object]
+impl DailyLedger {
+    async fn ledger_index(&self) -> i64 {
+        self.0.ledger_index
+    }
+
+    async fn close_time(&self) -> DateTime<Utc> {
+        self.0.close_time
+    }
+
+    async fn ledger(&self, ctx: &Context<'_>) -> Result<Option<Ledger>> {
+        load_ledger(ctx, self.0.ledger_index).await
+    }
+}
+
+/// The requested limit, clamped to the maximum of the caller's tier like REST limits.
+fn page_limit(ctx: &Context<'_>, limit: Option<i32>) -> Result<i32> {
+    let limit = limit.unwrap_or(DEFAULT_RESULT_LIMIT);
+    if limit <= 0 {
+        return Err(Error::new(format!("invalid limit {}", limit)));
+    }
+    Ok(limit.min(ctx.data_unchecked::<Client>().tier.max_limit))
+}
+
+pub async fn load_ledger(ctx: &Context<'_>, ledger_index: i64) -> Result<Option<Ledger>> {
+    let ledger = ctx
+        .data_unchecked::<DataLoader<LedgerLoader>>()
+        .load_one(ledger_index)
+        .await?;
+    Ok(ledger.map(Ledger))
+}
+
+pub async fn load_transaction(ctx: &Context<'_>, hash: &str) -> Result<Option<Transaction>> {
+    let transaction = ctx
+        .data_unchecked::<DataLoader<TransactionLoader>>()
+        .load_one(hash.to_string())
+        .await?;
+    Ok(transaction.map(Transaction))
+}
+
+pub async fn load_account(ctx: &Context<'_>, account: &str) -> Result<Option<Account>> {
+    let account = ctx
+        .data_unchecked::<DataLoader<AccountLoader>>()
+        .load_one(account.to_string())
+        .await?;
+    Ok(account.map(Account))
+}
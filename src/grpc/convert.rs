@@ -0,0 +1,107 @@
+use crate::grpc::proto;
+use crate::models::account::Account;
+use crate::models::balance_change::BalanceChange;
+use crate::models::ledger::Ledger;
+use crate::models::payment::Payment;
+use crate::models::transaction::Transaction;
+use chrono::{DateTime, Utc};
+use prost_types::Timestamp;
+
+fn timestamp(time: DateTime<Utc>) -> Option<Timestamp> {
+    Some(Timestamp {
+        seconds: time.timestamp(),
+        nanos: time.timestamp_subsec_nanos() as i32,
+    })
+}
+
+impl From<Ledger> for proto::Ledger {
+    fn from(ledger: Ledger) -> Self {
+        proto::Ledger {
+            ledger_index: ledger.ledger_index,
+            ledger_hash: ledger.ledger_hash,
+            account_hash: ledger.account_hash,
+            parent_hash: ledger.parent_hash,
+            transaction_hash: ledger.transaction_hash,
+            close_flags: ledger.close_flags,
+            close_time: timestamp(ledger.close_time),
+            parent_close_time: timestamp(ledger.parent_close_time),
+            total_coins: ledger.total_coins,
+            tx_count: ledger.tx_count.to_string(),
+        }
+    }
+}
+
+impl From<Transaction> for proto::Transaction {
+    fn from(transaction: Transaction) -> Self {
+        proto::Transaction {
+            account: transaction.account,
+            hash: transaction.hash,
+            ctid: transaction.ctid,
+            ledger_index: transaction.ledger_index,
+            tx_index: transaction.tx_index.to_string(),
+            tx_type: transaction.tx_type,
+            timestamp: timestamp(transaction.timestamp),
+            flags: transaction.flags,
+            fee: transaction.fee.to_string(),
+            sequence: transaction.sequence,
+            result: transaction.result.into(),
+            meta: transaction.meta,
+            tx: transaction.tx,
+        }
+    }
+}
+
+impl From<Account> for proto::Account {
+    fn from(account: Account) -> Self {
+        proto::Account {
+            ledger_index: account.ledger_index,
+            tx_index: account.tx_index.to_string(),
+            account: account.account,
+            client: account.client,
+            initial_balance: account.initial_balance,
+            parent: account.parent,
+            timestamp: timestamp(account.timestamp),
+            tx_hash: account.tx_hash,
+        }
+    }
+}
+
+impl From<Payment> for proto::Payment {
+    fn from(payment: Payment) -> Self {
+        proto::Payment {
+            tx_hash: payment.tx_hash,
+            ledger_index: payment.ledger_index,
+            tx_index: payment.tx_index.to_string(),
+            source: payment.source,
+            source_currency: payment.source_currency,
+            source_currency_issuer: payment.source_currency_issuer,
+            destination: payment.destination,
+            destination_currency: payment.destination_currency,
+            destination_currency_issuer: payment.destination_currency_issuer,
+            amount: payment.amount,
+            delivered_amount: payment.delivered_amount,
+            transaction_cost: payment.transaction_cost.to_string(),
+            destination_tag: payment.destination_tag,
+            source_tag: payment.source_tag,
+            timestamp: timestamp(payment.timestamp),
+        }
+    }
+}
+
+impl From<BalanceChange> for proto::BalanceChange {
+    fn from(balance_change: BalanceChange) -> Self {
+        proto::BalanceChange {
+            ledger_index: balance_change.ledger_index,
+            tx_index: balance_change.tx_index.to_string(),
+            node_index: balance_change.node_index.to_string(),
+            account: balance_change.account,
+            change: balance_change.change,
+            change_type: balance_change.change_type,
+            counterparty: balance_change.counterparty,
+            currency: balance_change.currency,
+            final_balance: balance_change.final_balance,
+            timestamp: timestamp(balance_change.timestamp),
+            tx_hash: balance_change.tx_hash,
+        }
+    }
+}
@@ -0,0 +1,263 @@
+pub mod convert;
+
+pub mod proto {
+    tonic::include_proto!("xrpl.data_api.v1");
+}
+
+use crate::grpc::proto::data_api_server::DataApi;
+use crate::grpc::proto::get_ledger_request::Ledger as LedgerIdentifierProto;
+use crate::grpc::proto::{
+    AccountStreamRequest, GetAccountRequest, GetLedgerRequest, GetTransactionRequest,
+};
+use crate::handlers::account::get_account;
+use crate::handlers::balance_change::balance_change_rows;
+use crate::handlers::ledger::{get_ledger, LedgerIdentifier};
+use crate::handlers::payment::{payment_rows, PaymentDirection};
+use crate::handlers::transaction::{get_transactions, transaction_rows};
+use crate::utils::auth::{admit, Client};
+use crate::utils::consts::API_KEY_HEADER;
+use crate::utils::errors::{map_error_to_status_code, DataApiError};
+use crate::utils::guardrails::streamed_budget;
+use crate::utils::rate_limit::RateLimitDecision;
+use crate::utils::rows::ScanBudget;
+use crate::utils::stream::row_stream;
+use crate::AppState;
+use axum::http::StatusCode;
+use futures::{Stream, StreamExt};
+use std::net::{IpAddr, Ipv4Addr};
+use std::pin::Pin;
+use std::sync::Arc;
+use tonic::metadata::MetadataMap;
+use tonic::{Code, Request, Response, Status};
+
+type RowStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+/// gRPC service over the same state and queries as the HTTP API. Each RPC is authorized and
+/// rate limited as the route it mirrors, so tiers and costs apply to both servers alike.
+pub struct DataApiService {
+    state: Arc<AppState>,
+}
+
+impl DataApiService {
+    pub fn new(state: Arc<AppState>) -> Self {
+        DataApiService { state }
+    }
+
+    async fn authorize<T>(
+        &self,
+        request: &Request<T>,
+        route: &str,
+        limit: Option<i32>,
+    ) -> Result<(Client, RateLimitDecision), Status> {
+        let presented = request
+            .metadata()
+            .get(API_KEY_HEADER)
+            .and_then(|key| key.to_str().ok());
+        let address = request
+            .remote_addr()
+            .map(|address| address.ip())
+            .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+
+        let (client, decision) = admit(&self.state, presented, address, route, limit)
+            .await
+            .map_err(|status_code| status(status_code, "request rejected"))?;
+        if !decision.allowed {
+            return Err(Status::with_metadata(
+                Code::ResourceExhausted,
+                "rate limited",
+                MetadataMap::from_headers(decision.headers()),
+            ));
+        }
+        Ok((client, decision))
+    }
+
+    /// Authorizes a streaming RPC. As for the HTTP streams, the RPC itself costs a single row and
+    /// the streamed rows are charged to the caller's rate limit as they are read, so the limit is
+    /// not clamped to the caller's maximum.
+    async fn authorize_stream(
+        &self,
+        request: Request<AccountStreamRequest>,
+        route: &str,
+    ) -> Result<
+        (
+            AccountStreamRequest,
+            Option<usize>,
+            ScanBudget,
+            RateLimitDecision,
+        ),
+        Status,
+    > {
+        let limit = match request.get_ref().limit {
+            Some(0) => return Err(Status::invalid_argument("limit must be positive")),
+            Some(limit) => Some(limit as usize),
+            None => None,
+        };
+        let (client, decision) = self.authorize(&request, route, Some(1)).await?;
+        let budget = streamed_budget(&self.state, &client, route);
+        Ok((request.into_inner(), limit, budget, decision))
+    }
+}
+
+#[tonic::async_trait]
+impl DataApi for DataApiService {
+    async fn get_ledger(
+        &self,
+        request: Request<GetLedgerRequest>,
+    ) -> Result<Response<proto::Ledger>, Status> {
+        let (_, decision) = self
+            .authorize(&request, "/ledger/:ledger_identifier", None)
+            .await?;
+        let (field, identifier) = match request.into_inner().ledger {
+            Some(LedgerIdentifierProto::LedgerIndex(ledger_index)) => {
+                ("ledger_index", LedgerIdentifier::BigInt(ledger_index))
+            }
+            Some(LedgerIdentifierProto::LedgerHash(ledger_hash)) => {
+                ("ledger_hash", LedgerIdentifier::String(ledger_hash))
+            }
+            None => return Err(Status::invalid_argument("ledger index or hash is required")),
+        };
+
+        match get_ledger(&self.state.scylla_session, field, &identifier).await {
+            Ok(ledger) => Ok(respond(ledger.into(), &decision)),
+            Err(err) => Err(map_error_to_status(&err)),
+        }
+    }
+
+    async fn get_transaction(
+        &self,
+        request: Request<GetTransactionRequest>,
+    ) -> Result<Response<proto::Transaction>, Status> {
+        let (_, decision) = self
+            .authorize(&request, "/transaction/hash/:tx_hash", None)
+            .await?;
+        let hash = request.into_inner().hash;
+
+        let transactions = get_transactions(&self.state.scylla_session, "hash", (&hash,)).await;
+        let transaction = transactions.and_then(|transactions| {
+            transactions
+                .into_iter()
+                .next()
+                .ok_or(DataApiError::NoDataReturned)
+        });
+        match transaction {
+            Ok(transaction) => Ok(respond(transaction.into(), &decision)),
+            Err(err) => Err(map_error_to_status(&err)),
+        }
+    }
+
+    async fn get_account(
+        &self,
+        request: Request<GetAccountRequest>,
+    ) -> Result<Response<proto::Account>, Status> {
+        let (_, decision) = self.authorize(&request, "/account/:account", None).await?;
+        let account = request.into_inner().account;
+
+        match get_account(&self.state.scylla_session, &account).await {
+            Ok(account) => Ok(respond(account.into(), &decision)),
+            Err(err) => Err(map_error_to_status(&err)),
+        }
+    }
+
+    type StreamAccountTransactionsStream = RowStream<proto::Transaction>;
+
+    async fn stream_account_transactions(
+        &self,
+        request: Request<AccountStreamRequest>,
+    ) -> Result<Response<Self::StreamAccountTransactionsStream>, Status> {
+        let route = "/transaction/account/:account";
+        let (request, limit, budget, decision) = self.authorize_stream(request, route).await?;
+
+        let rows = transaction_rows(&self.state.scylla_session, "account", (&request.account,));
+        match rows.await {
+            Ok(rows) => Ok(respond(
+                into_messages(row_stream(rows, limit, budget)),
+                &decision,
+            )),
+            Err(err) => Err(map_error_to_status(&err)),
+        }
+    }
+
+    type StreamAccountPaymentsStream = RowStream<proto::Payment>;
+
+    async fn stream_account_payments(
+        &self,
+        request: Request<AccountStreamRequest>,
+    ) -> Result<Response<Self::StreamAccountPaymentsStream>, Status> {
+        let route = "/account/:account/payments";
+        let (request, limit, budget, decision) = self.authorize_stream(request, route).await?;
+
+        match payment_rows(
+            &self.state.scylla_session,
+            &request.account,
+            &[PaymentDirection::Sent],
+        )
+        .await
+        {
+            Ok(rows) => Ok(respond(
+                into_messages(row_stream(rows, limit, budget)),
+                &decision,
+            )),
+            Err(err) => Err(map_error_to_status(&err)),
+        }
+    }
+
+    type StreamAccountBalanceChangesStream = RowStream<proto::BalanceChange>;
+
+    async fn stream_account_balance_changes(
+        &self,
+        request: Request<AccountStreamRequest>,
+    ) -> Result<Response<Self::StreamAccountBalanceChangesStream>, Status> {
+        let route = "/account/:account/balance_changes";
+        let (request, limit, budget, decision) = self.authorize_stream(request, route).await?;
+
+        match balance_change_rows(&self.state.scylla_session, "account", &request.account).await {
+            Ok(rows) => Ok(respond(
+                into_messages(row_stream(rows, limit, budget)),
+                &decision,
+            )),
+            Err(err) => Err(map_error_to_status(&err)),
+        }
+    }
+}
+
+// tonic streams items as `Result<_, Status>`
+#[allow(clippy::result_large_err)]
+fn into_messages<T, M>(
+    rows: impl Stream<Item = Result<T, DataApiError>> + Send + 'static,
+) -> RowStream<M>
+where
+    M: From<T> + Send + 'static,
+{
+    let messages = rows.map(|row| match row {
+        Ok(row) => Ok(M::from(row)),
+        Err(err) => Err(map_error_to_status(&err)),
+    });
+    Box::pin(messages)
+}
+
+/// Adds the caller's rate limit to the response metadata, as the HTTP API does with headers.
+fn respond<T>(message: T, decision: &RateLimitDecision) -> Response<T> {
+    let mut response = Response::new(message);
+    *response.metadata_mut() = MetadataMap::from_headers(decision.headers());
+    response
+}
+
+fn map_error_to_status(err: &DataApiError) -> Status {
+    eprintln!("{}", err);
+    status(map_error_to_status_code(err), err.to_string())
+}
+
+// Errors are mapped to HTTP status codes first, so both servers classify them the same way
+fn status(status_code: StatusCode, message: impl Into<String>) -> Status {
+    let code = match status_code {
+        StatusCode::BAD_REQUEST => Code::InvalidArgument,
+        StatusCode::UNAUTHORIZED => Code::Unauthenticated,
+        StatusCode::FORBIDDEN => Code::PermissionDenied,
+        StatusCode::NOT_FOUND => Code::NotFound,
+        StatusCode::UNPROCESSABLE_ENTITY => Code::FailedPrecondition,
+        StatusCode::TOO_MANY_REQUESTS => Code::ResourceExhausted,
+        StatusCode::GATEWAY_TIMEOUT => Code::DeadlineExceeded,
+        _ => Code::Internal,
+    };
+    Status::new(code, message)
+}
@@ -1,13 +1,47 @@
-use crate::models::account::Account;
-use crate::utils::consts::ACCOUNTS_TABLE;
+use crate::handlers::daily_ledger::get_ledger_index_bounds;
+use crate::models::account::{Account, AccountBalance, AccountSummary};
+use crate::utils::consts::{
+    ACCOUNTS_PARENT_MV_TABLE, ACCOUNTS_TABLE, ACCOUNT_SUMMARY_WINDOW_DAYS,
+    ANCESTRY_COMPLETE_HEADER, BALANCE_CHANGES_TABLE, DEFAULT_RESULT_LIMIT, GENESIS_ACCOUNT,
+    MAX_ANCESTRY_DEPTH, PAYMENTS_DESTINATION_MV_TABLE, PAYMENTS_TABLE,
+    TRANSACTIONS_ACCOUNT_MV_TABLE,
+};
 use crate::utils::errors::{map_error_to_status_code, DataApiError};
+use crate::utils::format::{Formatted, OutputFormat};
+use crate::utils::pagination::{decode_marker, encode_marker, Paginated};
+use crate::utils::params::DataApiQueryParams;
+use crate::utils::rows::{next_row, ScanBudget};
 use crate::AppState;
 use axum::extract::{Path, State};
-use axum::http::StatusCode;
+use axum::http::{HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
 use axum::Json;
+use chrono::{DateTime, Duration, Utc};
+use num_bigint::BigInt;
 use scylla::Session;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 
+// ! NOTE: select column order is important. Must match the order of the struct fields
+const ACCOUNT_COLUMNS: &str = "ledger_index, \
+    tx_index, \
+    account, \
+    client, \
+    initial_balance, \
+    parent, \
+    timestamp, \
+    tx_hash";
+
+#[utoipa::path(
+    get,
+    path = "/account/{account}",
+    tag = "accounts",
+    params(("account" = String, Path, description = "Account address")),
+    responses(
+        (status = 200, description = "Activation of the account", body = Account),
+        (status = 404, description = "Account not found"),
+    ),
+)]
 pub async fn get_account_handler(
     State(state): State<Arc<AppState>>,
     Path(account): Path<String>,
@@ -21,18 +55,107 @@ pub async fn get_account_handler(
     }
 }
 
-async fn get_account(session: &Session, account: &str) -> Result<Account, DataApiError> {
+#[utoipa::path(
+    get,
+    path = "/account/{account}/children",
+    tag = "accounts",
+    params(("account" = String, Path, description = "Account address"), DataApiQueryParams, ("format" = Option<OutputFormat>, Query, description = "Output format, overrides the `Accept` header")),
+    responses(
+        (status = 200, description = "Accounts activated by the account, the next page marker is in `x-next-marker`", body = [Account]),
+        (status = 400, description = "Invalid limit or marker"),
+    ),
+)]
+pub async fn get_account_children_handler(
+    State(state): State<Arc<AppState>>,
+    Path(account): Path<String>,
+    params: axum::extract::Query<DataApiQueryParams>,
+    format: OutputFormat,
+) -> anyhow::Result<Formatted<Account>, StatusCode> {
+    let limit = params.limit.unwrap_or(DEFAULT_RESULT_LIMIT);
+
+    let children = match decode_marker(params.marker.as_deref()) {
+        Ok(paging_state) => {
+            get_account_children(&state.scylla_session, &account, limit, paging_state).await
+        }
+        Err(err) => Err(err),
+    };
+    match children {
+        Ok(children) => Ok(Formatted::page(format, children)),
+        Err(err) => {
+            eprintln!("{}", err);
+            Err(map_error_to_status_code(&err))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/account/{account}/ancestry",
+    tag = "accounts",
+    params(("account" = String, Path, description = "Account address"), ("format" = Option<OutputFormat>, Query, description = "Output format, overrides the `Accept` header")),
+    responses(
+        (status = 200, description = "Chain of activating accounts up to the genesis account, `x-ancestry-complete` is false when it could not be followed that far", body = [Account]),
+        (status = 404, description = "Account not found"),
+    ),
+)]
+pub async fn get_account_ancestry_handler(
+    State(state): State<Arc<AppState>>,
+    Path(account): Path<String>,
+    format: OutputFormat,
+) -> anyhow::Result<Response, StatusCode> {
+    match get_account_ancestry(&state.scylla_session, &account).await {
+        Ok(ancestry) => {
+            let mut response = Formatted::new(format, ancestry.accounts).into_response();
+            response.headers_mut().insert(
+                ANCESTRY_COMPLETE_HEADER,
+                HeaderValue::from_static(if ancestry.complete { "true" } else { "false" }),
+            );
+            Ok(response)
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            Err(map_error_to_status_code(&err))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/account/{account}/summary",
+    tag = "accounts",
+    params(("account" = String, Path, description = "Account address")),
+    responses(
+        (status = 200, description = "Balances and activity of the account over the last 90 days", body = AccountSummary),
+        (status = 404, description = "Account not found"),
+        (status = 422, description = "The account has more activity in the window than the scan budget"),
+    ),
+)]
+pub async fn get_account_summary_handler(
+    State(state): State<Arc<AppState>>,
+    Path(account): Path<String>,
+) -> anyhow::Result<Json<Arc<AccountSummary>>, StatusCode> {
+    // Concurrent requests share the fill, so it scans under its own budget rather than the
+    // budget of whichever request started it
+    let summary = state
+        .account_summary_cache
+        .try_get_with(
+            account.clone(),
+            get_account_summary(&state.scylla_session, &account, ScanBudget::default()),
+        )
+        .await;
+    match summary {
+        Ok(summary) => Ok(Json(summary)),
+        Err(err) => {
+            eprintln!("{}", err);
+            Err(map_error_to_status_code(&err))
+        }
+    }
+}
+
+pub async fn get_account(session: &Session, account: &str) -> Result<Account, DataApiError> {
     let query = format!(
-        "SELECT ledger_index, \
-            tx_index, \
-            account, \
-            client, \
-            initial_balance, \
-            parent, \
-            timestamp, \
-            tx_hash \
-        from {} WHERE account=?;",
-        ACCOUNTS_TABLE
+        "SELECT {} from {} WHERE account=?;",
+        ACCOUNT_COLUMNS, ACCOUNTS_TABLE
     );
     println!("Query: {}", query);
     let query_result = session.query(query, (account,)).await?;
@@ -44,3 +167,252 @@ async fn get_account(session: &Session, account: &str) -> Result<Account, DataAp
     let account = query_result.single_row_typed::<Account>()?;
     Ok(account)
 }
+
+pub async fn get_account_children(
+    session: &Session,
+    parent: &str,
+    limit: i32,
+    paging_state: Option<bytes::Bytes>,
+) -> Result<Paginated<Account>, DataApiError> {
+    let query = format!(
+        "SELECT {} from {} WHERE parent=?;",
+        ACCOUNT_COLUMNS, ACCOUNTS_PARENT_MV_TABLE
+    );
+    let mut query = scylla::query::Query::new(query);
+    query.set_page_size(limit);
+
+    println!("Query: {}", query.contents);
+    let query_result = session.query_paged(query, (parent,), paging_state).await?;
+    let marker = encode_marker(query_result.paging_state.clone());
+
+    // todo: better row error handling
+    let children = query_result
+        .rows_typed_or_empty::<Account>()
+        .filter_map(|row| row.ok())
+        .collect::<Vec<Account>>();
+
+    if children.is_empty() {
+        return Err(DataApiError::NoDataReturned);
+    }
+    println!("Returning {} child accounts", children.len());
+
+    Ok(Paginated {
+        items: children,
+        marker,
+    })
+}
+
+struct Ancestry {
+    accounts: Vec<Account>,
+    // False when a parent is missing from the accounts table, the chain loops or it is longer
+    // than `MAX_ANCESTRY_DEPTH`
+    complete: bool,
+}
+
+/// Walks `parent` links from `account` up to the genesis account. The returned list starts
+/// with the requested account and ends with the oldest ancestor found in the accounts table.
+async fn get_account_ancestry(session: &Session, account: &str) -> Result<Ancestry, DataApiError> {
+    let mut current = get_account(session, account).await?;
+    let mut visited = HashSet::from([current.account.clone()]);
+    let mut accounts = Vec::new();
+
+    let complete = loop {
+        let parent = current.parent.clone();
+        let reached_genesis = current.account == GENESIS_ACCOUNT;
+        accounts.push(current);
+
+        if reached_genesis || parent.is_empty() {
+            break true;
+        }
+        if accounts.len() >= MAX_ANCESTRY_DEPTH || !visited.insert(parent.clone()) {
+            break false;
+        }
+        current = match get_account(session, &parent).await {
+            Ok(parent_account) => parent_account,
+            // The genesis account has no activation record
+            Err(DataApiError::NoDataReturned) if parent == GENESIS_ACCOUNT => break true,
+            // The parent's activation is not ingested yet
+            Err(DataApiError::NoDataReturned) => break false,
+            Err(err) => return Err(err),
+        };
+    };
+    println!(
+        "Found {} accounts in ancestry of {}, complete: {}",
+        accounts.len(),
+        account,
+        complete
+    );
+
+    Ok(Ancestry { accounts, complete })
+}
+
+// currency, counterparty, final_balance, ledger_index, tx_index, node_index, timestamp
+type BalanceRow = (
+    String,
+    Option<String>,
+    String,
+    i64,
+    BigInt,
+    BigInt,
+    DateTime<Utc>,
+);
+
+struct TransactionStats {
+    first_transaction_time: Option<DateTime<Utc>>,
+    last_transaction_time: Option<DateTime<Utc>>,
+    transaction_count: u64,
+    transaction_types: BTreeMap<String, u64>,
+}
+
+async fn get_account_summary(
+    session: &Session,
+    account: &str,
+    budget: ScanBudget,
+) -> Result<Arc<AccountSummary>, DataApiError> {
+    let since = Utc::now() - Duration::days(ACCOUNT_SUMMARY_WINDOW_DAYS);
+    let (min_ledger, _) = get_ledger_index_bounds(session, Some(since), None).await?;
+    let (activation, balances, transaction_stats, payment_counterparties) = tokio::try_join!(
+        get_account(session, account),
+        get_account_balances(session, account, min_ledger, &budget),
+        get_account_transaction_stats(session, account, min_ledger, &budget),
+        get_account_payment_counterparties(session, account, min_ledger, &budget),
+    )?;
+
+    Ok(Arc::new(AccountSummary {
+        activation,
+        since,
+        balances,
+        first_transaction_time: transaction_stats.first_transaction_time,
+        last_transaction_time: transaction_stats.last_transaction_time,
+        transaction_count: transaction_stats.transaction_count,
+        transaction_types: transaction_stats.transaction_types,
+        payment_counterparties,
+    }))
+}
+
+/// Balance per currency and counterparty, taken from the latest balance change from ledger
+/// `min_ledger` on.
+async fn get_account_balances(
+    session: &Session,
+    account: &str,
+    min_ledger: i64,
+    budget: &ScanBudget,
+) -> Result<Vec<AccountBalance>, DataApiError> {
+    let query = format!(
+        "SELECT currency, \
+            counterparty, \
+            final_balance, \
+            ledger_index, \
+            tx_index, \
+            node_index, \
+            timestamp \
+        from {} WHERE account=? AND ledger_index>=?;",
+        BALANCE_CHANGES_TABLE
+    );
+    println!("Query: {}", query);
+    let mut rows = session
+        .query_iter(query, (account, min_ledger))
+        .await?
+        .into_typed::<BalanceRow>();
+
+    let mut latest =
+        HashMap::<(String, Option<String>), (i64, BigInt, BigInt, AccountBalance)>::new();
+    while let Some(row) = next_row(&mut rows, budget).await? {
+        let (currency, counterparty, balance, ledger_index, tx_index, node_index, timestamp) = row;
+        let key = (currency.clone(), counterparty.clone());
+        let is_newer = latest
+            .get(&key)
+            .is_none_or(|(l, t, n, _)| (ledger_index, &tx_index, &node_index) > (*l, t, n));
+        if is_newer {
+            let balance = AccountBalance {
+                currency,
+                counterparty,
+                balance,
+                ledger_index,
+                timestamp,
+            };
+            latest.insert(key, (ledger_index, tx_index, node_index, balance));
+        }
+    }
+
+    let mut balances = latest
+        .into_values()
+        .map(|(_, _, _, balance)| balance)
+        .collect::<Vec<AccountBalance>>();
+    balances.sort_by(|a, b| (&a.currency, &a.counterparty).cmp(&(&b.currency, &b.counterparty)));
+
+    Ok(balances)
+}
+
+async fn get_account_transaction_stats(
+    session: &Session,
+    account: &str,
+    min_ledger: i64,
+    budget: &ScanBudget,
+) -> Result<TransactionStats, DataApiError> {
+    let query = format!(
+        "SELECT tx_type, timestamp from {} WHERE account=? AND ledger_index>=?;",
+        TRANSACTIONS_ACCOUNT_MV_TABLE
+    );
+    println!("Query: {}", query);
+    let mut rows = session
+        .query_iter(query, (account, min_ledger))
+        .await?
+        .into_typed::<(String, DateTime<Utc>)>();
+
+    let mut stats = TransactionStats {
+        first_transaction_time: None,
+        last_transaction_time: None,
+        transaction_count: 0,
+        transaction_types: BTreeMap::new(),
+    };
+    while let Some((tx_type, timestamp)) = next_row(&mut rows, budget).await? {
+        stats.transaction_count += 1;
+        *stats.transaction_types.entry(tx_type).or_insert(0) += 1;
+        stats.first_transaction_time = Some(
+            stats
+                .first_transaction_time
+                .map_or(timestamp, |first| first.min(timestamp)),
+        );
+        stats.last_transaction_time = Some(
+            stats
+                .last_transaction_time
+                .map_or(timestamp, |last| last.max(timestamp)),
+        );
+    }
+
+    Ok(stats)
+}
+
+/// Number of distinct accounts the account has sent payments to or received payments from, from
+/// ledger `min_ledger` on.
+async fn get_account_payment_counterparties(
+    session: &Session,
+    account: &str,
+    min_ledger: i64,
+    budget: &ScanBudget,
+) -> Result<u64, DataApiError> {
+    let mut counterparties = HashSet::new();
+    for (counterparty, table, field) in [
+        ("destination", PAYMENTS_TABLE, "source"),
+        ("source", PAYMENTS_DESTINATION_MV_TABLE, "destination"),
+    ] {
+        let query = format!(
+            "SELECT {} from {} WHERE {}=? AND ledger_index>=?;",
+            counterparty, table, field
+        );
+        println!("Query: {}", query);
+        let mut rows = session
+            .query_iter(query, (account, min_ledger))
+            .await?
+            .into_typed::<(String,)>();
+
+        while let Some((counterparty,)) = next_row(&mut rows, budget).await? {
+            counterparties.insert(counterparty);
+        }
+    }
+    // Payments to itself, such as currency conversions, have no counterparty
+    counterparties.remove(account);
+
+    Ok(counterparties.len() as u64)
+}
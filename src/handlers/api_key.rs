@@ -0,0 +1,212 @@
+use crate::models::api_key::{ApiKey, ApiKeyRequest, ApiKeyUsage, CreatedApiKey, Tier};
+use crate::utils::auth::hash_secret;
+use crate::utils::consts::{API_KEYS_TABLE, API_KEY_USAGE_TABLE};
+use crate::utils::errors::{map_error_to_status_code, DataApiError};
+use crate::utils::rows::{collect_rows, ScanBudget};
+use crate::AppState;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use chrono::{NaiveDate, Utc};
+use scylla::frame::value::Counter;
+use scylla::Session;
+use std::sync::Arc;
+use uuid::Uuid;
+
+// ! NOTE: select column order is important
+static API_KEY_COLUMNS: &str = "id, name, tier, secret_hash, created_at, revoked_at";
+
+#[utoipa::path(
+    post,
+    path = "/admin/keys",
+    tag = "admin",
+    request_body = ApiKeyRequest,
+    responses(
+        (status = 201, description = "The created key, including its secret", body = CreatedApiKey),
+        (status = 400, description = "Unknown tier"),
+    ),
+)]
+pub async fn create_api_key_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ApiKeyRequest>,
+) -> Result<(StatusCode, Json<CreatedApiKey>), StatusCode> {
+    match create_api_key(&state.scylla_session, request).await {
+        Ok(created) => Ok((StatusCode::CREATED, Json(created))),
+        Err(err) => {
+            eprintln!("{}", err);
+            Err(map_error_to_status_code(&err))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/keys",
+    tag = "admin",
+    responses((status = 200, description = "Stored keys", body = [ApiKey])),
+)]
+pub async fn get_api_keys_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(budget): Extension<ScanBudget>,
+) -> Result<Json<Vec<ApiKey>>, StatusCode> {
+    let query = format!("SELECT {} from {};", API_KEY_COLUMNS, API_KEYS_TABLE);
+    println!("Query: {}", query);
+    let query = scylla::query::Query::new(query);
+    match collect_rows(&state.scylla_session, query, (), &budget).await {
+        Ok(api_keys) => Ok(Json(api_keys)),
+        Err(err) => {
+            eprintln!("{}", err);
+            Err(map_error_to_status_code(&err))
+        }
+    }
+}
+
+/// Revokes a key. Other instances keep accepting it until their cached copy expires.
+#[utoipa::path(
+    delete,
+    path = "/admin/keys/{id}",
+    tag = "admin",
+    params(("id" = Uuid, Path, description = "Key id")),
+    responses(
+        (status = 204, description = "Key revoked"),
+        (status = 404, description = "Key not found"),
+    ),
+)]
+pub async fn revoke_api_key_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    match revoke_api_key(&state.scylla_session, id).await {
+        Ok(()) => {
+            state.api_key_cache.invalidate(&id).await;
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            Err(map_error_to_status_code(&err))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/keys/{id}/usage",
+    tag = "admin",
+    params(("id" = Uuid, Path, description = "Key id")),
+    responses((status = 200, description = "Requests per day and route", body = [ApiKeyUsage])),
+)]
+pub async fn get_api_key_usage_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Extension(budget): Extension<ScanBudget>,
+) -> Result<Json<Vec<ApiKeyUsage>>, StatusCode> {
+    let query = format!(
+        "SELECT day, route, requests from {} WHERE key_id=?;",
+        API_KEY_USAGE_TABLE
+    );
+    println!("Query: {}", query);
+    match collect_rows(
+        &state.scylla_session,
+        scylla::query::Query::new(query),
+        (id,),
+        &budget,
+    )
+    .await
+    {
+        Ok(usage) => Ok(Json(usage)),
+        Err(err) => {
+            eprintln!("{}", err);
+            Err(map_error_to_status_code(&err))
+        }
+    }
+}
+
+async fn create_api_key(
+    session: &Session,
+    request: ApiKeyRequest,
+) -> Result<CreatedApiKey, DataApiError> {
+    if Tier::find(&request.tier).is_none() {
+        return Err(DataApiError::InvalidParameter(format!(
+            "unknown tier {}",
+            request.tier
+        )));
+    }
+
+    let secret = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let api_key = ApiKey {
+        id: Uuid::new_v4(),
+        name: request.name,
+        tier: request.tier,
+        secret_hash: hash_secret(&secret),
+        created_at: Utc::now(),
+        revoked_at: None,
+    };
+
+    let query = format!(
+        "INSERT INTO {} ({}) VALUES (?, ?, ?, ?, ?, ?);",
+        API_KEYS_TABLE, API_KEY_COLUMNS
+    );
+    println!("Query: {}", query);
+    session
+        .query(
+            query,
+            (
+                api_key.id,
+                &api_key.name,
+                &api_key.tier,
+                &api_key.secret_hash,
+                api_key.created_at,
+                api_key.revoked_at,
+            ),
+        )
+        .await?;
+
+    Ok(CreatedApiKey {
+        key: format!("{}.{}", api_key.id, secret),
+        api_key,
+    })
+}
+
+async fn revoke_api_key(session: &Session, id: Uuid) -> Result<(), DataApiError> {
+    if get_api_key(session, id).await?.is_none() {
+        return Err(DataApiError::NoDataReturned);
+    }
+
+    let query = format!("UPDATE {} SET revoked_at=? WHERE id=?;", API_KEYS_TABLE);
+    println!("Query: {}", query);
+    session.query(query, (Utc::now(), id)).await?;
+
+    Ok(())
+}
+
+pub async fn get_api_key(session: &Session, id: Uuid) -> Result<Option<ApiKey>, DataApiError> {
+    let query = format!(
+        "SELECT {} from {} WHERE id=?;",
+        API_KEY_COLUMNS, API_KEYS_TABLE
+    );
+    let query_result = session.query(query, (id,)).await?;
+
+    // todo: better row error handling
+    Ok(query_result
+        .rows_typed_or_empty::<ApiKey>()
+        .filter_map(|row| row.ok())
+        .next())
+}
+
+pub async fn add_api_key_usage(
+    session: &Session,
+    key_id: Uuid,
+    day: NaiveDate,
+    route: &str,
+    requests: i64,
+) -> Result<(), DataApiError> {
+    let query = format!(
+        "UPDATE {} SET requests = requests + ? WHERE key_id=? AND day=? AND route=?;",
+        API_KEY_USAGE_TABLE
+    );
+    session
+        .query(query, (Counter(requests), key_id, day, route))
+        .await?;
+
+    Ok(())
+}
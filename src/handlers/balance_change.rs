@@ -1,23 +1,76 @@
 use crate::models::balance_change::BalanceChange;
-use crate::utils::consts::{BALANCE_CHANGES_TABLE, DEFAULT_RESULT_LIMIT, KEYSPACE};
+use crate::utils::consts::{
+    BALANCE_CHANGES_TABLE, BALANCE_CHANGES_TX_MV_TABLE, DEFAULT_RESULT_LIMIT, STREAM_PAGE_SIZE,
+};
 use crate::utils::errors::{map_error_to_status_code, DataApiError};
+use crate::utils::format::{Formatted, OutputFormat};
+use crate::utils::pagination::{encode_marker, Paginated};
 use crate::utils::params::DataApiQueryParams;
+use crate::utils::rows::{collect_rows, ScanBudget};
+use crate::utils::stream::stream_rows;
 use crate::AppState;
 use axum::extract::{Path, State};
 use axum::http::StatusCode;
-use axum::Json;
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use bytes::Bytes;
+use scylla::transport::iterator::TypedRowIterator;
 use scylla::Session;
 use std::sync::Arc;
 
+// ! NOTE: select column order is important. Must match the order of the struct fields
+pub const BALANCE_CHANGE_COLUMNS: &str = "ledger_index, \
+    tx_index, \
+    node_index, \
+    account, \
+    change, \
+    change_type, \
+    counterparty, \
+    currency, \
+    final_balance, \
+    timestamp, \
+    tx_hash";
+
+#[utoipa::path(
+    get,
+    path = "/account/{account}/balance_changes",
+    tag = "accounts",
+    params(("account" = String, Path, description = "Account address"), DataApiQueryParams, ("format" = Option<OutputFormat>, Query, description = "Output format, overrides the `Accept` header")),
+    responses(
+        (status = 200, description = "Balance changes of the account", body = [BalanceChange]),
+        (status = 400, description = "Invalid limit"),
+    ),
+)]
 pub async fn get_account_balance_changes_handler(
     State(state): State<Arc<AppState>>,
     Path(account): Path<String>,
+    Extension(budget): Extension<ScanBudget>,
     params: axum::extract::Query<DataApiQueryParams>,
-) -> anyhow::Result<Json<Vec<BalanceChange>>, StatusCode> {
+    format: OutputFormat,
+) -> anyhow::Result<Response, StatusCode> {
+    if params.stream == Some(true) {
+        let limit = params.limit.map(|limit| limit.max(0) as usize);
+        return match stream_balance_changes(
+            &state.scylla_session,
+            "account",
+            &account,
+            limit,
+            format,
+            budget,
+        )
+        .await
+        {
+            Ok(response) => Ok(response),
+            Err(err) => {
+                eprintln!("{}", err);
+                Err(map_error_to_status_code(&err))
+            }
+        };
+    }
     let limit = params.limit.unwrap_or(DEFAULT_RESULT_LIMIT);
 
     match get_balance_changes(&state.scylla_session, "account", &account, limit).await {
-        Ok(account) => Ok(Json(account)),
+        Ok(balance_changes) => Ok(Formatted::new(format, balance_changes).into_response()),
         Err(err) => {
             eprintln!("{}", err);
             Err(map_error_to_status_code(&err))
@@ -25,26 +78,15 @@ pub async fn get_account_balance_changes_handler(
     }
 }
 
-async fn get_balance_changes(
+pub async fn get_balance_changes(
     session: &Session,
     field: &str,
     value: &str,
     limit: i32,
 ) -> Result<Vec<BalanceChange>, DataApiError> {
     let query = format!(
-        "SELECT ledger_index, \
-            tx_index, \
-            node_index, \
-            account, \
-            change, \
-            change_type, \
-            counterparty, \
-            currency, \
-            final_balance, \
-            timestamp, \
-            tx_hash \
-        from {} WHERE {}=?;",
-        BALANCE_CHANGES_TABLE, field
+        "SELECT {} from {} WHERE {}=?;",
+        BALANCE_CHANGE_COLUMNS, BALANCE_CHANGES_TABLE, field
     );
     let mut query = scylla::query::Query::new(query);
     query.set_page_size(limit);
@@ -65,3 +107,115 @@ async fn get_balance_changes(
 
     Ok(balance_changes)
 }
+
+async fn stream_balance_changes(
+    session: &Session,
+    field: &str,
+    value: &str,
+    limit: Option<usize>,
+    format: OutputFormat,
+    budget: ScanBudget,
+) -> Result<Response, DataApiError> {
+    let rows = balance_change_rows(session, field, value).await?;
+    Ok(stream_rows(rows, format, limit, budget))
+}
+
+/// Lazily paged balance changes, read one page at a time as they are consumed.
+pub async fn balance_change_rows(
+    session: &Session,
+    field: &str,
+    value: &str,
+) -> Result<TypedRowIterator<BalanceChange>, DataApiError> {
+    let query = format!(
+        "SELECT {} from {} WHERE {}=?;",
+        BALANCE_CHANGE_COLUMNS, BALANCE_CHANGES_TABLE, field
+    );
+    let mut query = scylla::query::Query::new(query);
+    query.set_page_size(STREAM_PAGE_SIZE);
+
+    println!("Streaming query: {}", query.contents);
+    Ok(session
+        .query_iter(query, (value,))
+        .await?
+        .into_typed::<BalanceChange>())
+}
+
+/// Balance changes in ledgers `after_ledger` (exclusive) to `up_to_ledger` (inclusive), across all
+/// pages.
+pub async fn get_balance_changes_in_ledger_range(
+    session: &Session,
+    field: &str,
+    value: &str,
+    after_ledger: i64,
+    up_to_ledger: i64,
+    budget: &ScanBudget,
+) -> Result<Vec<BalanceChange>, DataApiError> {
+    let query = format!(
+        "SELECT {} from {} WHERE {}=? AND ledger_index>? AND ledger_index<=?;",
+        BALANCE_CHANGE_COLUMNS, BALANCE_CHANGES_TABLE, field
+    );
+    let mut query = scylla::query::Query::new(query);
+    query.set_page_size(STREAM_PAGE_SIZE);
+
+    println!("Query: {}", query.contents);
+    collect_rows(session, query, (value, after_ledger, up_to_ledger), budget).await
+}
+
+/// A page of an account's balance changes in ledgers `min_ledger` to `max_ledger`, newest first
+/// when `descending`. Pages may be empty and still be followed by more.
+pub async fn get_balance_changes_page(
+    session: &Session,
+    account: &str,
+    (min_ledger, max_ledger): (i64, i64),
+    descending: bool,
+    limit: i32,
+    paging_state: Option<Bytes>,
+) -> Result<Paginated<BalanceChange>, DataApiError> {
+    let query = format!(
+        "SELECT {} from {} WHERE account=? AND ledger_index>=? AND ledger_index<=? \
+        ORDER BY ledger_index {};",
+        BALANCE_CHANGE_COLUMNS,
+        BALANCE_CHANGES_TABLE,
+        if descending { "DESC" } else { "ASC" }
+    );
+    let mut query = scylla::query::Query::new(query);
+    query.set_page_size(limit);
+
+    println!("Query: {}", query.contents);
+    let query_result = session
+        .query_paged(query, (account, min_ledger, max_ledger), paging_state)
+        .await?;
+    let marker = encode_marker(query_result.paging_state.clone());
+
+    // todo: better row error handling
+    let balance_changes = query_result
+        .rows_typed_or_empty::<BalanceChange>()
+        .filter_map(|row| row.ok())
+        .collect::<Vec<BalanceChange>>();
+    println!("Returning {} balance changes", balance_changes.len());
+
+    Ok(Paginated {
+        items: balance_changes,
+        marker,
+    })
+}
+
+/// Every balance change of a transaction.
+pub async fn get_transaction_balance_changes(
+    session: &Session,
+    tx_hash: &str,
+) -> Result<Vec<BalanceChange>, DataApiError> {
+    let query = format!(
+        "SELECT {} from {} WHERE tx_hash=?;",
+        BALANCE_CHANGE_COLUMNS, BALANCE_CHANGES_TX_MV_TABLE
+    );
+    let query_result = session.query(query, (tx_hash,)).await?;
+
+    // todo: better row error handling
+    let changes = query_result
+        .rows_typed_or_empty::<BalanceChange>()
+        .filter_map(|row| row.ok())
+        .collect::<Vec<BalanceChange>>();
+
+    Ok(changes)
+}
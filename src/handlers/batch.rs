@@ -0,0 +1,139 @@
+use crate::handlers::account::get_account;
+use crate::handlers::ledger::{get_ledger, get_ledger_at_time, parse_ledger_identifier};
+use crate::handlers::transaction::get_transactions;
+use crate::models::batch::{BatchItem, BatchRequest};
+use crate::utils::auth::Client;
+use crate::utils::consts::{BATCH_CONCURRENCY, BATCH_IDS_PER_TOKEN, BATCH_MAX_IDS};
+use crate::utils::errors::DataApiError;
+use crate::AppState;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::Serialize;
+use std::future::Future;
+use std::sync::Arc;
+
+#[utoipa::path(
+    post,
+    path = "/batch/transactions",
+    tag = "batch",
+    request_body(content = BatchRequest, description = "Transaction hashes"),
+    responses(
+        (status = 200, description = "A result for every hash, in the order of the request", body = [TransactionBatchItem]),
+        (status = 400, description = "More hashes than the caller's maximum"),
+        (status = 429, description = "Rate limited"),
+    ),
+)]
+pub async fn batch_transactions_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(client): Extension<Client>,
+    Json(request): Json<BatchRequest>,
+) -> Result<Response, StatusCode> {
+    let session = &state.scylla_session;
+    lookup_batch(&state, &client, request.ids, |hash| async move {
+        let mut transactions = get_transactions(session, "hash", (&hash,)).await?;
+        transactions.pop().ok_or(DataApiError::NoDataReturned)
+    })
+    .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/batch/ledgers",
+    tag = "batch",
+    request_body(content = BatchRequest, description = "Ledger indexes, ledger hashes or close times"),
+    responses(
+        (status = 200, description = "A result for every identifier, in the order of the request", body = [LedgerBatchItem]),
+        (status = 400, description = "More identifiers than the caller's maximum"),
+        (status = 429, description = "Rate limited"),
+    ),
+)]
+pub async fn batch_ledgers_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(client): Extension<Client>,
+    Json(request): Json<BatchRequest>,
+) -> Result<Response, StatusCode> {
+    let session = &state.scylla_session;
+    lookup_batch(
+        &state,
+        &client,
+        request.ids,
+        |ledger_identifier| async move {
+            if let Ok(close_time) = ledger_identifier.parse::<DateTime<Utc>>() {
+                return get_ledger_at_time(session, close_time).await;
+            }
+            let (field, value) = parse_ledger_identifier(ledger_identifier);
+            get_ledger(session, field, &value).await
+        },
+    )
+    .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/batch/accounts",
+    tag = "batch",
+    request_body(content = BatchRequest, description = "Account addresses"),
+    responses(
+        (status = 200, description = "A result for every address, in the order of the request", body = [AccountBatchItem]),
+        (status = 400, description = "More addresses than the caller's maximum"),
+        (status = 429, description = "Rate limited"),
+    ),
+)]
+pub async fn batch_accounts_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(client): Extension<Client>,
+    Json(request): Json<BatchRequest>,
+) -> Result<Response, StatusCode> {
+    let session = &state.scylla_session;
+    lookup_batch(&state, &client, request.ids, |account| async move {
+        get_account(session, &account).await
+    })
+    .await
+}
+
+/// Looks up every identifier with at most `BATCH_CONCURRENCY` lookups in flight. Failed
+/// lookups only fail their own item. Batches take tokens for their identifiers on top of the
+/// route's cost, which is taken before the body is read.
+async fn lookup_batch<T, F, Fut>(
+    state: &AppState,
+    client: &Client,
+    ids: Vec<String>,
+    lookup: F,
+) -> Result<Response, StatusCode>
+where
+    T: Serialize,
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<T, DataApiError>>,
+{
+    let max_ids = BATCH_MAX_IDS.min(client.tier.max_limit.max(0) as usize);
+    if ids.len() > max_ids {
+        println!("Batch of {} exceeds the maximum of {}", ids.len(), max_ids);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let cost = ids.len().div_ceil(BATCH_IDS_PER_TOKEN) as u32;
+    if cost > 0 {
+        let decision = state
+            .rate_limiter
+            .acquire(&client.bucket, client.tier, cost);
+        if !decision.allowed {
+            println!("Client {} is rate limited on a batch", client.bucket);
+            return Ok((StatusCode::TOO_MANY_REQUESTS, decision.headers()).into_response());
+        }
+    }
+
+    println!("Looking up a batch of {}", ids.len());
+    let items = futures::stream::iter(ids)
+        .map(|id| {
+            let result = lookup(id.clone());
+            async move { BatchItem::new(id, result.await) }
+        })
+        .buffered(BATCH_CONCURRENCY)
+        .collect::<Vec<BatchItem<T>>>()
+        .await;
+
+    Ok(Json(items).into_response())
+}
@@ -0,0 +1,308 @@
+use crate::handlers::exchange::{get_pair_exchanges, parse_pair};
+use crate::models::exchange::{Candle, Currency, Exchange};
+use crate::utils::consts::{
+    CANDLE_EXCHANGES_PAGE_SIZE, CANDLE_FINALITY_GRACE_SECS, DEFAULT_CANDLE_COUNT, MAX_CANDLE_COUNT,
+};
+use crate::utils::errors::{map_error_to_status_code, DataApiError};
+use crate::utils::format::{Formatted, OutputFormat};
+use crate::utils::pagination::decode_position;
+use crate::utils::params::{CandleInterval, CandleQueryParams};
+use crate::utils::rows::ScanBudget;
+use crate::AppState;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Extension;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+/// Cache key of a single candle: pair, interval and bucket start as a unix timestamp
+pub type CandleCacheKey = (String, CandleInterval, i64);
+
+#[utoipa::path(
+    get,
+    path = "/exchanges/{base}/{counter}/candles",
+    tag = "exchanges",
+    params(
+        ("base" = String, Path, description = "`XRP` or `code+issuer`"),
+        ("counter" = String, Path, description = "`XRP` or `code+issuer`"),
+        CandleQueryParams,
+        ("format" = Option<OutputFormat>, Query, description = "Output format, overrides the `Accept` header"),
+    ),
+    responses(
+        (status = 200, description = "Candles of the pair, buckets without trades are left out. Closed buckets are cached for up to an hour", body = [Candle]),
+        (status = 400, description = "Invalid pair or range"),
+    ),
+)]
+pub async fn get_candles_handler(
+    State(state): State<Arc<AppState>>,
+    Path((base, counter)): Path<(String, String)>,
+    Extension(budget): Extension<ScanBudget>,
+    params: axum::extract::Query<CandleQueryParams>,
+    format: OutputFormat,
+) -> Result<Formatted<Candle>, StatusCode> {
+    let candles = match parse_pair(&base, &counter) {
+        Ok(pair) => get_candles(&state, &pair, &params, &budget).await,
+        Err(err) => Err(err),
+    };
+    match candles {
+        Ok(candles) => Ok(Formatted::new(format, candles)),
+        Err(err) => {
+            eprintln!("{}", err);
+            Err(map_error_to_status_code(&err))
+        }
+    }
+}
+
+/// Builds the candles covering the requested range. Buckets that closed before the finality
+/// grace period are cached for `CANDLE_CACHE_TTL_SECS`, so only recent or uncached buckets are
+/// rebuilt from trades. Trades ingested later, such as a backfilled gap, only show in a cached
+/// bucket once it expires. All reads of a request, trades and their lookups, share its scan
+/// budget.
+async fn get_candles(
+    state: &AppState,
+    pair: &(Currency, Currency),
+    params: &CandleQueryParams,
+    budget: &ScanBudget,
+) -> Result<Vec<Candle>, DataApiError> {
+    let interval = params.interval.seconds();
+    let end = params.end.unwrap_or_else(Utc::now);
+    let start = params
+        .start
+        .unwrap_or(end - Duration::seconds(interval * DEFAULT_CANDLE_COUNT));
+    if start > end {
+        return Err(DataApiError::InvalidParameter(format!(
+            "start {} is after end {}",
+            start, end
+        )));
+    }
+
+    let first_bucket = start.timestamp().div_euclid(interval) * interval;
+    let last_bucket = end.timestamp().div_euclid(interval) * interval;
+    if (last_bucket - first_bucket) / interval + 1 > MAX_CANDLE_COUNT {
+        return Err(DataApiError::InvalidParameter(format!(
+            "range covers more than {} candles",
+            MAX_CANDLE_COUNT
+        )));
+    }
+
+    let pair_key = format!("{}/{}", pair.0, pair.1);
+    let mut candles = BTreeMap::new();
+    let mut missing = Vec::new();
+    for bucket in (first_bucket..=last_bucket).step_by(interval as usize) {
+        let key = (pair_key.clone(), params.interval, bucket);
+        match state.candle_cache.get(&key).await {
+            Some(candle) => {
+                candles.insert(bucket, candle);
+            }
+            None => missing.push(bucket),
+        }
+    }
+
+    if let (Some(first_missing), Some(last_missing)) = (missing.first(), missing.last()) {
+        let exchanges = get_all_pair_exchanges(
+            state,
+            pair,
+            from_timestamp(*first_missing)?,
+            from_timestamp(*last_missing + interval)?,
+            budget,
+        )
+        .await?;
+        let mut built = build_candles(exchanges, interval);
+
+        let now = Utc::now();
+        for bucket in missing {
+            let candle = built.remove(&bucket);
+            if is_final(bucket, interval, now) {
+                let key = (pair_key.clone(), params.interval, bucket);
+                state.candle_cache.insert(key, candle.clone()).await;
+            }
+            candles.insert(bucket, candle);
+        }
+    }
+    println!("Returning {} candle buckets", candles.len());
+
+    Ok(candles.into_values().flatten().collect())
+}
+
+// Whether a bucket closed before the finality grace period, so its trades are all ingested
+fn is_final(bucket: i64, interval: i64, now: DateTime<Utc>) -> bool {
+    bucket + interval <= (now - Duration::seconds(CANDLE_FINALITY_GRACE_SECS)).timestamp()
+}
+
+fn from_timestamp(timestamp: i64) -> Result<DateTime<Utc>, DataApiError> {
+    DateTime::from_timestamp(timestamp, 0)
+        .ok_or_else(|| DataApiError::InvalidParameter(format!("invalid time {}", timestamp)))
+}
+
+async fn get_all_pair_exchanges(
+    state: &AppState,
+    pair: &(Currency, Currency),
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    budget: &ScanBudget,
+) -> Result<Vec<Exchange>, DataApiError> {
+    let mut exchanges = Vec::new();
+    let mut seen = HashSet::new();
+    let mut after = None;
+    loop {
+        let page = match get_pair_exchanges(
+            &state.scylla_session,
+            pair,
+            (Some(start), Some(end)),
+            after,
+            CANDLE_EXCHANGES_PAGE_SIZE,
+            budget,
+        )
+        .await
+        {
+            Ok(page) => page,
+            Err(DataApiError::NoDataReturned) => break,
+            Err(err) => return Err(err),
+        };
+        // A transaction's trades all come in one page, so a hash seen on an earlier page is a
+        // transaction read twice, whose trades would be counted twice
+        let fresh = page
+            .items
+            .into_iter()
+            .filter(|exchange| !seen.contains(&exchange.tx_hash))
+            .collect::<Vec<Exchange>>();
+        seen.extend(fresh.iter().map(|exchange| exchange.tx_hash.clone()));
+        exchanges.extend(fresh);
+        after = match page.marker {
+            Some(marker) => decode_position(Some(&marker))?,
+            None => break,
+        };
+    }
+
+    Ok(exchanges)
+}
+
+fn build_candles(mut exchanges: Vec<Exchange>, interval: i64) -> BTreeMap<i64, Candle> {
+    exchanges.sort_by(|a, b| (a.ledger_index, &a.tx_index).cmp(&(b.ledger_index, &b.tx_index)));
+
+    let mut candles = BTreeMap::<i64, Candle>::new();
+    for exchange in exchanges {
+        let bucket = exchange.timestamp.timestamp().div_euclid(interval) * interval;
+        let rate = exchange.rate;
+
+        match candles.get_mut(&bucket) {
+            Some(candle) => {
+                candle.high = candle.high.clone().max(rate.clone());
+                candle.low = candle.low.clone().min(rate.clone());
+                candle.close = rate;
+                candle.base_volume = candle.base_volume.add(&exchange.base_amount);
+                candle.counter_volume = candle.counter_volume.add(&exchange.counter_amount);
+                candle.trade_count += 1;
+            }
+            None => {
+                let start = match DateTime::from_timestamp(bucket, 0) {
+                    Some(start) => start,
+                    None => continue,
+                };
+                candles.insert(
+                    bucket,
+                    Candle {
+                        start,
+                        open: rate.clone(),
+                        high: rate.clone(),
+                        low: rate.clone(),
+                        close: rate,
+                        base_volume: exchange.base_amount,
+                        counter_volume: exchange.counter_amount,
+                        trade_count: 1,
+                    },
+                );
+            }
+        }
+    }
+
+    candles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_candles, is_final};
+    use crate::models::exchange::Exchange;
+    use crate::utils::consts::CANDLE_FINALITY_GRACE_SECS;
+    use crate::utils::decimal::Decimal;
+    use chrono::DateTime;
+    use num_bigint::BigInt;
+
+    const MINUTE: i64 = 60;
+
+    // A trade of `base_amount` at `rate`, the counter amount is their product
+    fn exchange(timestamp: i64, ledger_index: i64, rate: &str, base_amount: &str) -> Exchange {
+        let counter_amount = rate.parse::<i64>().unwrap() * base_amount.parse::<i64>().unwrap();
+        Exchange {
+            base: "XRP".parse().unwrap(),
+            counter: "USD+rIssuer".parse().unwrap(),
+            base_amount: Decimal::parse(base_amount).unwrap(),
+            counter_amount: Decimal::new(counter_amount, 0),
+            rate: Decimal::parse(rate).unwrap(),
+            buyer: "rBuyer".to_string(),
+            seller: "rSeller".to_string(),
+            taker: "rBuyer".to_string(),
+            maker: "rSeller".to_string(),
+            ledger_index,
+            tx_index: BigInt::from(0),
+            tx_hash: ledger_index.to_string(),
+            timestamp: DateTime::from_timestamp(timestamp, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn buckets_trades_by_interval() {
+        let exchanges = vec![
+            exchange(120, 1, "2", "10"),
+            exchange(179, 2, "4", "5"),
+            // Nothing is traded in the bucket at 180
+            exchange(240, 3, "3", "1"),
+        ];
+
+        let candles = build_candles(exchanges, MINUTE);
+        assert_eq!(candles.keys().copied().collect::<Vec<i64>>(), [120, 240]);
+
+        let candle = &candles[&120];
+        assert_eq!(candle.start.timestamp(), 120);
+        assert_eq!(candle.trade_count, 2);
+        assert_eq!(candle.base_volume.to_string(), "15");
+        assert_eq!(candle.counter_volume.to_string(), "40");
+        assert_eq!(candles[&240].trade_count, 1);
+    }
+
+    #[test]
+    fn opens_and_closes_in_ledger_order() {
+        // Listed by time, in ledger order the rates are 3, 1 and 5
+        let exchanges = vec![
+            exchange(60, 11, "1", "1"),
+            exchange(61, 12, "5", "1"),
+            exchange(62, 10, "3", "1"),
+        ];
+
+        let candle = &build_candles(exchanges, MINUTE)[&60];
+        assert_eq!(candle.open.to_string(), "3");
+        assert_eq!(candle.close.to_string(), "5");
+        assert_eq!(candle.high.to_string(), "5");
+        assert_eq!(candle.low.to_string(), "1");
+    }
+
+    #[test]
+    fn finalizes_buckets_after_the_grace_period() {
+        let bucket = 600;
+        let closed_at = bucket + MINUTE;
+        let at = |timestamp| DateTime::from_timestamp(timestamp, 0).unwrap();
+
+        assert!(!is_final(bucket, MINUTE, at(closed_at)));
+        assert!(!is_final(
+            bucket,
+            MINUTE,
+            at(closed_at + CANDLE_FINALITY_GRACE_SECS - 1)
+        ));
+        assert!(is_final(
+            bucket,
+            MINUTE,
+            at(closed_at + CANDLE_FINALITY_GRACE_SECS)
+        ));
+    }
+}
@@ -1,24 +1,47 @@
 use crate::models::daily_ledger::DailyLedger;
-use crate::utils::consts::DAILY_LEDGERS_TABLE;
+use crate::utils::cache::CachedResponse;
+use crate::utils::consts::{DAILY_LEDGERS_TABLE, DAY_FINALITY_GRACE_SECS};
 use crate::utils::errors::{map_error_to_status_code, DataApiError};
+use crate::utils::format::{render, OutputFormat};
 use crate::AppState;
 use axum::extract::{Path, State};
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::Response;
 use axum::Json;
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use scylla::Session;
 use std::sync::Arc;
 
+#[utoipa::path(
+    get,
+    path = "/daily_ledgers/{close_day}",
+    tag = "ledgers",
+    params(("close_day" = String, Path, description = "Day as `YYYY-MM-DD`"), ("format" = Option<OutputFormat>, Query, description = "Output format, overrides the `Accept` header")),
+    responses(
+        (status = 200, description = "Ledgers closed on the day", body = [DailyLedger]),
+        (status = 304, description = "The ledgers did not change since the given ETag"),
+        (status = 400, description = "Invalid day"),
+    ),
+)]
 pub async fn get_daily_ledgers_handler(
     State(state): State<Arc<AppState>>,
     Path(close_day): Path<String>,
-) -> Result<Json<Vec<DailyLedger>>, StatusCode> {
+    format: OutputFormat,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
     let parsed_day = close_day.parse::<NaiveDate>();
     match parsed_day {
         Ok(day) => {
             println!("Finding ledgers closed in day: {}", day.format("%Y-%m-%d"));
-            match get_ledgers_on_day(&state.scylla_session, day).await {
-                Ok(ledgers) => Ok(Json(ledgers)),
+            let key = format!("daily_ledgers/{}/{:?}", day, format);
+            let session = state.scylla_session.clone();
+            let response = state.response_cache.respond(key, &headers, async move {
+                let ledgers = get_ledgers_on_day(&session, day).await?;
+                let (content_type, body) = render(format, &ledgers)?;
+                Ok(CachedResponse::new(content_type, body, is_day_final(day)))
+            });
+            match response.await {
+                Ok(response) => Ok(response),
                 Err(err) => {
                     eprintln!("{}", err);
                     Err(map_error_to_status_code(&err))
@@ -32,6 +55,17 @@ pub async fn get_daily_ledgers_handler(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/closed_ledger/{close_time}",
+    tag = "ledgers",
+    params(("close_time" = String, Path, description = "RFC 3339 time")),
+    responses(
+        (status = 200, description = "Last ledger closed at or before the time", body = DailyLedger),
+        (status = 400, description = "Invalid time"),
+        (status = 404, description = "No ledger closed before the time"),
+    ),
+)]
 pub async fn get_latest_closed_ledger_handler(
     State(state): State<Arc<AppState>>,
     Path(close_time): Path<String>,
@@ -55,15 +89,21 @@ pub async fn get_latest_closed_ledger_handler(
     }
 }
 
-async fn get_ledgers_on_day(
+/// Checks whether a day ended long enough ago for ingestion to have written all its ledgers.
+pub fn is_day_final(day: NaiveDate) -> bool {
+    let day_closed_at = day.and_hms_opt(0, 0, 0).map(|midnight| {
+        midnight.and_utc() + Duration::days(1) + Duration::seconds(DAY_FINALITY_GRACE_SECS)
+    });
+    day_closed_at.is_some_and(|closed_at| closed_at <= Utc::now())
+}
+
+pub async fn get_ledgers_on_day(
     session: &Session,
     day: NaiveDate,
 ) -> Result<Vec<DailyLedger>, DataApiError> {
     let query = format!(
-        "SELECT ledger_close_day,\
-            ledger_index,\
-            close_time, \
-            toUnixTimestamp(close_time) \
+        "SELECT ledger_index,\
+            close_time \
         from {} WHERE ledger_close_day=?",
         DAILY_LEDGERS_TABLE,
     );
@@ -89,10 +129,8 @@ pub async fn get_last_closed_ledger(
     close_time: DateTime<Utc>,
 ) -> Result<DailyLedger, DataApiError> {
     let query = format!(
-        "SELECT ledger_close_day, \
-            ledger_index, \
-            close_time, \
-            toUnixTimestamp(close_time) \
+        "SELECT ledger_index, \
+            close_time \
         from {} WHERE ledger_close_day = ?",
         DAILY_LEDGERS_TABLE,
         // close_time.date_naive()
@@ -112,3 +150,49 @@ pub async fn get_last_closed_ledger(
         None => Err(DataApiError::NoDataReturned),
     }
 }
+
+/// Translates an optional close time range into the range of ledger indexes closed within it.
+/// Days that were not ingested leave the corresponding side of the range open.
+pub async fn get_ledger_index_bounds(
+    session: &Session,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+) -> Result<(i64, i64), DataApiError> {
+    if let (Some(start), Some(end)) = (start, end) {
+        if start > end {
+            return Err(DataApiError::InvalidParameter(format!(
+                "start {} is after end {}",
+                start, end
+            )));
+        }
+    }
+
+    let min_ledger_index = match start {
+        Some(start) => match get_ledgers_on_day(session, start.date_naive()).await {
+            Ok(ledgers) => ledgers
+                .iter()
+                .filter(|ledger| ledger.close_time >= start)
+                .map(|ledger| ledger.ledger_index)
+                .min()
+                .unwrap_or_else(|| ledgers.iter().map(|l| l.ledger_index).max().unwrap_or(0) + 1),
+            Err(DataApiError::NoDataReturned) => 0,
+            Err(err) => return Err(err),
+        },
+        None => 0,
+    };
+    let max_ledger_index = match end {
+        Some(end) => match get_ledgers_on_day(session, end.date_naive()).await {
+            Ok(ledgers) => ledgers
+                .iter()
+                .filter(|ledger| ledger.close_time <= end)
+                .map(|ledger| ledger.ledger_index)
+                .max()
+                .unwrap_or_else(|| ledgers.iter().map(|l| l.ledger_index).min().unwrap_or(0) - 1),
+            Err(DataApiError::NoDataReturned) => i64::MAX,
+            Err(err) => return Err(err),
+        },
+        None => i64::MAX,
+    };
+
+    Ok((min_ledger_index, max_ledger_index))
+}
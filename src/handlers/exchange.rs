@@ -0,0 +1,798 @@
+use crate::handlers::balance_change::get_transaction_balance_changes;
+use crate::handlers::daily_ledger::get_ledger_index_bounds;
+use crate::models::balance_change::BalanceChange;
+use crate::models::exchange::{Currency, Exchange};
+use crate::utils::consts::{
+    BALANCE_CHANGES_TABLE, DEFAULT_RESULT_LIMIT, EXCHANGE_CHANGE_TYPE, EXCHANGE_LOOKUP_CONCURRENCY,
+    EXCHANGE_PAIRS_TABLE, TRANSACTIONS_TABLE, XRP_CURRENCY,
+};
+use crate::utils::decimal::Decimal;
+use crate::utils::errors::{map_error_to_status_code, DataApiError};
+use crate::utils::format::{Formatted, OutputFormat};
+use crate::utils::pagination::{decode_position, encode_position, Paginated};
+use crate::utils::params::DataApiQueryParams;
+use crate::utils::rows::{next_row, ScanBudget};
+use crate::AppState;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Extension;
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt, TryStreamExt};
+use num_bigint::BigInt;
+use scylla::query::Query;
+use scylla::transport::iterator::NextRowError;
+use scylla::Session;
+use std::sync::Arc;
+
+#[utoipa::path(
+    get,
+    path = "/account/{account}/exchanges",
+    tag = "exchanges",
+    params(("account" = String, Path, description = "Account address"), DataApiQueryParams, ("format" = Option<OutputFormat>, Query, description = "Output format, overrides the `Accept` header")),
+    responses(
+        (status = 200, description = "Trades of the account, the next page marker is in `x-next-marker`", body = [Exchange]),
+        (status = 400, description = "Invalid limit or marker"),
+    ),
+)]
+pub async fn get_account_exchanges_handler(
+    State(state): State<Arc<AppState>>,
+    Path(account): Path<String>,
+    Extension(budget): Extension<ScanBudget>,
+    params: axum::extract::Query<DataApiQueryParams>,
+    format: OutputFormat,
+) -> anyhow::Result<Formatted<Exchange>, StatusCode> {
+    let limit = params.limit.unwrap_or(DEFAULT_RESULT_LIMIT);
+
+    let exchanges = match decode_position(params.marker.as_deref()) {
+        Ok(after) => {
+            get_account_exchanges(
+                &state.scylla_session,
+                &account,
+                &params,
+                after,
+                limit,
+                &budget,
+            )
+            .await
+        }
+        Err(err) => Err(err),
+    };
+    match exchanges {
+        Ok(exchanges) => Ok(Formatted::page(format, exchanges)),
+        Err(err) => {
+            eprintln!("{}", err);
+            Err(map_error_to_status_code(&err))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/exchanges/{base}/{counter}",
+    tag = "exchanges",
+    params(
+        ("base" = String, Path, description = "`XRP` or `code+issuer`"),
+        ("counter" = String, Path, description = "`XRP` or `code+issuer`"),
+        DataApiQueryParams,
+        ("format" = Option<OutputFormat>, Query, description = "Output format, overrides the `Accept` header"),
+    ),
+    responses(
+        (status = 200, description = "Trades of the pair, the next page marker is in `x-next-marker`", body = [Exchange]),
+        (status = 400, description = "Invalid pair, limit or marker"),
+    ),
+)]
+pub async fn get_exchanges_handler(
+    State(state): State<Arc<AppState>>,
+    Path((base, counter)): Path<(String, String)>,
+    Extension(budget): Extension<ScanBudget>,
+    params: axum::extract::Query<DataApiQueryParams>,
+    format: OutputFormat,
+) -> anyhow::Result<Formatted<Exchange>, StatusCode> {
+    let limit = params.limit.unwrap_or(DEFAULT_RESULT_LIMIT);
+
+    let exchanges = match (
+        parse_pair(&base, &counter),
+        decode_position(params.marker.as_deref()),
+    ) {
+        (Ok(pair), Ok(after)) => {
+            get_pair_exchanges(
+                &state.scylla_session,
+                &pair,
+                (params.start, params.end),
+                after,
+                limit,
+                &budget,
+            )
+            .await
+        }
+        (Err(err), _) | (_, Err(err)) => Err(err),
+    };
+    match exchanges {
+        Ok(exchanges) => Ok(Formatted::page(format, exchanges)),
+        Err(err) => {
+            eprintln!("{}", err);
+            Err(map_error_to_status_code(&err))
+        }
+    }
+}
+
+pub fn parse_pair(base: &str, counter: &str) -> Result<(Currency, Currency), DataApiError> {
+    let base = base.parse::<Currency>()?;
+    let counter = counter.parse::<Currency>()?;
+    if base == counter {
+        return Err(DataApiError::InvalidParameter(format!(
+            "base and counter are both {}",
+            base
+        )));
+    }
+    Ok((base, counter))
+}
+
+/// A page of an account's trades within the requested time range, newest first. `after` is the
+/// (ledger index, transaction index) of the last transaction of the previous page.
+async fn get_account_exchanges(
+    session: &Session,
+    account: &str,
+    params: &DataApiQueryParams,
+    after: Option<(i64, BigInt)>,
+    limit: i32,
+    budget: &ScanBudget,
+) -> Result<Paginated<Exchange>, DataApiError> {
+    let (min_ledger_index, max_ledger_index) =
+        get_ledger_index_bounds(session, params.start, params.end).await?;
+    let range = match after {
+        // Single and multi column restrictions can not be mixed, so the bound is a tuple too
+        Some(_) => "(ledger_index, tx_index)<(?, ?) AND (ledger_index)>=(?)",
+        None => "ledger_index>=? AND ledger_index<=?",
+    };
+    let query = format!(
+        "SELECT tx_hash, ledger_index, tx_index, change_type \
+        from {} WHERE account=? AND {};",
+        BALANCE_CHANGES_TABLE, range
+    );
+    let mut query = Query::new(query);
+    query.set_page_size(limit);
+
+    println!("Query: {}", query.contents);
+    let rows = match after {
+        Some((ledger_index, tx_index)) => {
+            let values = (account, ledger_index, tx_index, min_ledger_index);
+            session.query_iter(query, values).await?
+        }
+        None => {
+            let values = (account, min_ledger_index, max_ledger_index);
+            session.query_iter(query, values).await?
+        }
+    };
+    let mut rows = rows.into_typed::<(String, i64, BigInt, String)>();
+    let (tx_hashes, marker) = page_transactions(&mut rows, limit, budget, |row| {
+        let (tx_hash, ledger_index, tx_index, change_type) = row;
+        (change_type == EXCHANGE_CHANGE_TYPE).then_some((tx_hash, (ledger_index, tx_index)))
+    })
+    .await?;
+
+    let exchanges = get_exchanges_for_transactions(session, tx_hashes, budget, |tx_changes| {
+        account_pairs(tx_changes, account)
+    })
+    .await?
+    .into_iter()
+    .filter(|exchange| exchange.buyer == account || exchange.seller == account)
+    .collect();
+
+    finish_page(exchanges, (params.start, params.end), marker)
+}
+
+/// A page of a pair's trades within `start` to `end`, oldest first. `after` is the (ledger
+/// index, transaction index) of the last transaction of the previous page.
+pub async fn get_pair_exchanges(
+    session: &Session,
+    pair: &(Currency, Currency),
+    (start, end): (Option<DateTime<Utc>>, Option<DateTime<Utc>>),
+    after: Option<(i64, BigInt)>,
+    limit: i32,
+    budget: &ScanBudget,
+) -> Result<Paginated<Exchange>, DataApiError> {
+    let (min_ledger_index, max_ledger_index) = get_ledger_index_bounds(session, start, end).await?;
+    let range = match after {
+        // Single and multi column restrictions can not be mixed, so the bound is a tuple too
+        Some(_) => "(ledger_index, tx_index)>(?, ?) AND (ledger_index)<=(?)",
+        None => "ledger_index>=? AND ledger_index<=?",
+    };
+    let query = format!(
+        "SELECT tx_hash, ledger_index, tx_index \
+        from {} WHERE base=? AND counter=? AND {};",
+        EXCHANGE_PAIRS_TABLE, range
+    );
+    let mut query = Query::new(query);
+    query.set_page_size(limit);
+
+    println!("Query: {}", query.contents);
+    let (base, counter) = pair_key(pair);
+    let rows = match after {
+        Some((ledger_index, tx_index)) => {
+            let values = (base, counter, ledger_index, tx_index, max_ledger_index);
+            session.query_iter(query, values).await?
+        }
+        None => {
+            let values = (base, counter, min_ledger_index, max_ledger_index);
+            session.query_iter(query, values).await?
+        }
+    };
+    let mut rows = rows.into_typed::<(String, i64, BigInt)>();
+    let (tx_hashes, marker) = page_transactions(&mut rows, limit, budget, |row| {
+        let (tx_hash, ledger_index, tx_index) = row;
+        Some((tx_hash, (ledger_index, tx_index)))
+    })
+    .await?;
+
+    let exchanges =
+        get_exchanges_for_transactions(session, tx_hashes, budget, |_| vec![pair.clone()]).await?;
+
+    finish_page(exchanges, (start, end), marker)
+}
+
+/// Reads the hashes of the first `limit` transactions from `rows`, which are sorted by
+/// position and may hold several rows per transaction, along with the marker of the next page.
+/// `transaction_of` gives a row's transaction hash and position, or `None` to skip the row.
+/// Pages end between transactions, so a transaction is never split across two pages.
+async fn page_transactions<T, S, F>(
+    rows: &mut S,
+    limit: i32,
+    budget: &ScanBudget,
+    transaction_of: F,
+) -> Result<(Vec<String>, Option<String>), DataApiError>
+where
+    S: Stream<Item = Result<T, NextRowError>> + Unpin,
+    F: Fn(T) -> Option<(String, (i64, BigInt))>,
+{
+    let mut tx_hashes = Vec::new();
+    let mut last: Option<(i64, BigInt)> = None;
+    while let Some(row) = next_row(rows, budget).await? {
+        let (tx_hash, position) = match transaction_of(row) {
+            Some(transaction) => transaction,
+            None => continue,
+        };
+        if last.as_ref() == Some(&position) {
+            continue;
+        }
+        // A further transaction, so the page is followed by more
+        if tx_hashes.len() >= limit.max(0) as usize {
+            return Ok((tx_hashes, last.as_ref().map(encode_position)));
+        }
+        last = Some(position);
+        tx_hashes.push(tx_hash);
+    }
+    Ok((tx_hashes, None))
+}
+
+/// Loads the exchange balance changes of every transaction and pairs them into trades, charging
+/// the rows read to `budget`. `pairs_for` picks the currency pairs to build trades for from the
+/// transaction's changes.
+async fn get_exchanges_for_transactions<F>(
+    session: &Session,
+    tx_hashes: Vec<String>,
+    budget: &ScanBudget,
+    pairs_for: F,
+) -> Result<Vec<Exchange>, DataApiError>
+where
+    F: Fn(&[BalanceChange]) -> Vec<(Currency, Currency)>,
+{
+    let exchanges = futures::stream::iter(tx_hashes)
+        .map(|tx_hash| async move {
+            let (changes, sender) = tokio::try_join!(
+                get_transaction_exchange_changes(session, &tx_hash),
+                get_transaction_sender(session, &tx_hash),
+            )?;
+            budget.charge(changes.len() + 1)?;
+            Ok::<_, DataApiError>((changes, sender))
+        })
+        .buffered(EXCHANGE_LOOKUP_CONCURRENCY)
+        .map_ok(|(changes, sender)| {
+            pairs_for(&changes)
+                .iter()
+                .flat_map(|(base, counter)| {
+                    pair_exchanges(&changes, base, counter, sender.as_deref())
+                })
+                .collect::<Vec<Exchange>>()
+        })
+        .try_collect::<Vec<Vec<Exchange>>>()
+        .await?;
+
+    Ok(exchanges.into_iter().flatten().collect())
+}
+
+fn finish_page(
+    exchanges: Vec<Exchange>,
+    (start, end): (Option<DateTime<Utc>>, Option<DateTime<Utc>>),
+    marker: Option<String>,
+) -> Result<Paginated<Exchange>, DataApiError> {
+    let exchanges = exchanges
+        .into_iter()
+        .filter(|exchange| start.is_none_or(|start| exchange.timestamp >= start))
+        .filter(|exchange| end.is_none_or(|end| exchange.timestamp <= end))
+        .collect::<Vec<Exchange>>();
+
+    // An empty page can still be followed by more results
+    if exchanges.is_empty() && marker.is_none() {
+        return Err(DataApiError::NoDataReturned);
+    }
+    println!("Returning {} exchanges", exchanges.len());
+
+    Ok(Paginated {
+        items: exchanges,
+        marker,
+    })
+}
+
+async fn get_transaction_exchange_changes(
+    session: &Session,
+    tx_hash: &str,
+) -> Result<Vec<BalanceChange>, DataApiError> {
+    let changes = get_transaction_balance_changes(session, tx_hash)
+        .await?
+        .into_iter()
+        .filter(|change| change.change_type == EXCHANGE_CHANGE_TYPE)
+        .collect::<Vec<BalanceChange>>();
+
+    Ok(changes)
+}
+
+async fn get_transaction_sender(
+    session: &Session,
+    tx_hash: &str,
+) -> Result<Option<String>, DataApiError> {
+    let query = format!("SELECT account from {} WHERE hash=?;", TRANSACTIONS_TABLE);
+    let query_result = session.query(query, (tx_hash,)).await?;
+
+    // todo: better row error handling
+    let sender = query_result
+        .rows_typed_or_empty::<(String,)>()
+        .filter_map(|row| row.ok())
+        .map(|(account,)| account)
+        .next();
+
+    Ok(sender)
+}
+
+fn change_currency(change: &BalanceChange) -> Currency {
+    let currency = Currency {
+        code: change.currency.clone(),
+        issuer: None,
+    };
+    if currency.is_xrp() {
+        return currency;
+    }
+    Currency {
+        issuer: change.counterparty.clone(),
+        ..currency
+    }
+}
+
+/// The pairs an account traded in a transaction, with XRP as the base when it is involved and
+/// the currencies ordered alphabetically otherwise. The taker of an autobridged trade traded each
+/// of its currencies against XRP.
+fn account_pairs(changes: &[BalanceChange], account: &str) -> Vec<(Currency, Currency)> {
+    let mut currencies = changes
+        .iter()
+        .filter(|change| change.account == account)
+        .map(change_currency)
+        .collect::<Vec<Currency>>();
+    currencies.sort_by_key(|currency| (!currency.is_xrp(), currency.clone()));
+    currencies.dedup();
+
+    let xrp = changes
+        .iter()
+        .map(change_currency)
+        .find(|currency| currency.is_xrp());
+    match (currencies.as_slice(), xrp) {
+        ([base, counter], Some(xrp)) if !base.is_xrp() => {
+            vec![(xrp.clone(), base.clone()), (xrp, counter.clone())]
+        }
+        ([base, counter], _) => vec![(base.clone(), counter.clone())],
+        _ => Vec::new(),
+    }
+}
+
+// Partition of a pair in the exchange pairs table, with XRP as the base when it is involved and
+// the currencies ordered alphabetically otherwise, as the ingest binary writes them
+fn pair_key((base, counter): &(Currency, Currency)) -> (String, String) {
+    let (base, counter) = (base.to_string(), counter.to_string());
+    if counter == XRP_CURRENCY || (base != XRP_CURRENCY && counter < base) {
+        return (counter, base);
+    }
+    (base, counter)
+}
+
+struct Leg {
+    value: Decimal,
+}
+
+impl Leg {
+    fn add(leg: Option<Leg>, change: &str) -> Option<Leg> {
+        let value = Decimal::parse(change)?;
+        let sum = leg.map_or_else(Decimal::zero, |leg| leg.value);
+        Some(Leg {
+            value: sum.add(&value),
+        })
+    }
+}
+
+struct Participant<'a> {
+    account: &'a str,
+    base: Option<Leg>,
+    counter: Option<Leg>,
+}
+
+/// Pairs the exchange balance changes of one transaction into trades between the taker and each
+/// maker whose offer was consumed. The taker is the transaction sender when it took part in the
+/// exchange, otherwise the only account on its side of the book. Autobridged trades are paired
+/// as their two trades with XRP.
+fn pair_exchanges(
+    changes: &[BalanceChange],
+    base: &Currency,
+    counter: &Currency,
+    sender: Option<&str>,
+) -> Vec<Exchange> {
+    let first = match changes.first() {
+        Some(first) => first,
+        None => return Vec::new(),
+    };
+
+    let mut participants: Vec<Participant> = Vec::new();
+    for change in changes {
+        let index = match participants
+            .iter()
+            .position(|p| p.account == change.account)
+        {
+            Some(index) => index,
+            None => {
+                participants.push(Participant {
+                    account: &change.account,
+                    base: None,
+                    counter: None,
+                });
+                participants.len() - 1
+            }
+        };
+        let participant = &mut participants[index];
+        let counterparty = change.counterparty.as_deref();
+        if base.matches(&change.currency, counterparty) {
+            participant.base = Leg::add(participant.base.take(), &change.change);
+        } else if counter.matches(&change.currency, counterparty) {
+            participant.counter = Leg::add(participant.counter.take(), &change.change);
+        }
+    }
+
+    // Accounts with a single leg in the pair, such as the taker of an autobridged trade
+    let one_legged = participants
+        .iter()
+        .filter(|p| p.base.is_some() != p.counter.is_some())
+        .map(|p| p.account)
+        .collect::<Vec<&str>>();
+    let (buyers, sellers): (Vec<Participant>, Vec<Participant>) = participants
+        .into_iter()
+        .filter(|p| match (&p.base, &p.counter) {
+            (Some(base), Some(counter)) => {
+                !base.value.is_zero()
+                    && !counter.value.is_zero()
+                    && base.value.is_negative() != counter.value.is_negative()
+            }
+            _ => false,
+        })
+        .partition(|p| {
+            p.base
+                .as_ref()
+                .is_some_and(|base| !base.value.is_negative())
+        });
+
+    // The taker of an autobridged trade paid or received XRP through the bridge, so it trades
+    // with the makers of the pair's book, who are all on the other side
+    let bridged = (base.is_xrp() || counter.is_xrp())
+        && sender.is_some_and(|sender| one_legged.contains(&sender));
+    let (taker, taker_is_buyer) = match sender {
+        Some(sender) if buyers.iter().any(|p| p.account == sender) => (sender, true),
+        Some(sender) if sellers.iter().any(|p| p.account == sender) => (sender, false),
+        Some(sender) if bridged && buyers.is_empty() => (sender, true),
+        Some(sender) if bridged && sellers.is_empty() => (sender, false),
+        _ if buyers.len() == 1 => (buyers[0].account, true),
+        _ if sellers.len() == 1 => (sellers[0].account, false),
+        _ => return Vec::new(),
+    };
+    let makers = if taker_is_buyer { &sellers } else { &buyers };
+
+    makers
+        .iter()
+        .filter_map(|maker| {
+            let (maker_base, maker_counter) = (maker.base.as_ref()?, maker.counter.as_ref()?);
+            let rate = maker_counter.value.div(&maker_base.value)?.abs();
+            let (buyer, seller) = if taker_is_buyer {
+                (taker, maker.account)
+            } else {
+                (maker.account, taker)
+            };
+            Some(Exchange {
+                base: base.clone(),
+                counter: counter.clone(),
+                base_amount: maker_base.value.abs(),
+                counter_amount: maker_counter.value.abs(),
+                rate,
+                buyer: buyer.to_string(),
+                seller: seller.to_string(),
+                taker: taker.to_string(),
+                maker: maker.account.to_string(),
+                ledger_index: first.ledger_index,
+                tx_index: first.tx_index.clone(),
+                tx_hash: first.tx_hash.clone(),
+                timestamp: first.timestamp,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{account_pairs, page_transactions, pair_exchanges, pair_key};
+    use crate::models::balance_change::BalanceChange;
+    use crate::models::exchange::{Currency, Exchange};
+    use crate::utils::rows::ScanBudget;
+    use chrono::DateTime;
+    use num_bigint::BigInt;
+    use scylla::transport::iterator::NextRowError;
+
+    const TAKER: &str = "rTaker";
+    const MAKER: &str = "rMaker";
+    const OTHER_MAKER: &str = "rOtherMaker";
+    const ISSUER: &str = "rIssuer";
+
+    fn xrp() -> Currency {
+        "XRP".parse().unwrap()
+    }
+
+    fn usd() -> Currency {
+        format!("USD+{}", ISSUER).parse().unwrap()
+    }
+
+    fn eur() -> Currency {
+        format!("EUR+{}", ISSUER).parse().unwrap()
+    }
+
+    fn change(account: &str, currency: &Currency, change: &str) -> BalanceChange {
+        BalanceChange {
+            ledger_index: 90000000,
+            tx_index: BigInt::from(3),
+            node_index: BigInt::from(0),
+            account: account.to_string(),
+            change: change.to_string(),
+            change_type: "exchange".to_string(),
+            counterparty: currency.issuer.clone(),
+            currency: currency.code.clone(),
+            final_balance: "0".to_string(),
+            timestamp: DateTime::from_timestamp(0, 0).unwrap(),
+            tx_hash: "33".to_string(),
+        }
+    }
+
+    // (buyer, seller, taker, maker, base amount, counter amount, rate)
+    fn trades(exchanges: &[Exchange]) -> Vec<(&str, &str, &str, &str, String, String, String)> {
+        exchanges
+            .iter()
+            .map(|exchange| {
+                (
+                    exchange.buyer.as_str(),
+                    exchange.seller.as_str(),
+                    exchange.taker.as_str(),
+                    exchange.maker.as_str(),
+                    exchange.base_amount.to_string(),
+                    exchange.counter_amount.to_string(),
+                    exchange.rate.to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pairs_a_taker_buying_with_each_maker() {
+        // The taker buys 30 XRP for 15 USD from two offers
+        let changes = [
+            change(TAKER, &xrp(), "30"),
+            change(TAKER, &usd(), "-15"),
+            change(MAKER, &xrp(), "-10"),
+            change(MAKER, &usd(), "5"),
+            change(OTHER_MAKER, &xrp(), "-20"),
+            change(OTHER_MAKER, &usd(), "10"),
+        ];
+
+        let exchanges = pair_exchanges(&changes, &xrp(), &usd(), Some(TAKER));
+        assert_eq!(
+            trades(&exchanges),
+            [
+                (
+                    TAKER,
+                    MAKER,
+                    TAKER,
+                    MAKER,
+                    "10".into(),
+                    "5".into(),
+                    "0.5".into()
+                ),
+                (
+                    TAKER,
+                    OTHER_MAKER,
+                    TAKER,
+                    OTHER_MAKER,
+                    "20".into(),
+                    "10".into(),
+                    "0.5".into()
+                ),
+            ]
+        );
+        assert_eq!(exchanges[0].tx_index, BigInt::from(3));
+    }
+
+    #[test]
+    fn pairs_a_taker_selling_in_either_orientation() {
+        // The taker sells 10 XRP for 5 USD
+        let changes = [
+            change(TAKER, &xrp(), "-10"),
+            change(TAKER, &usd(), "5"),
+            change(MAKER, &xrp(), "10"),
+            change(MAKER, &usd(), "-5"),
+        ];
+
+        let exchanges = pair_exchanges(&changes, &xrp(), &usd(), Some(TAKER));
+        assert_eq!(
+            trades(&exchanges),
+            [(
+                MAKER,
+                TAKER,
+                TAKER,
+                MAKER,
+                "10".into(),
+                "5".into(),
+                "0.5".into()
+            )]
+        );
+
+        // Seen from USD, the taker buys USD
+        let exchanges = pair_exchanges(&changes, &usd(), &xrp(), Some(TAKER));
+        assert_eq!(
+            trades(&exchanges),
+            [(
+                TAKER,
+                MAKER,
+                TAKER,
+                MAKER,
+                "5".into(),
+                "10".into(),
+                "2".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn takes_the_only_account_on_its_side_without_a_sender() {
+        let changes = [
+            change(TAKER, &xrp(), "-10"),
+            change(TAKER, &usd(), "5"),
+            change(MAKER, &xrp(), "10"),
+            change(MAKER, &usd(), "-5"),
+        ];
+
+        let exchanges = pair_exchanges(&changes, &xrp(), &usd(), None);
+        assert_eq!(exchanges.len(), 1);
+        assert_eq!(exchanges[0].buyer, MAKER);
+        assert_eq!(exchanges[0].seller, TAKER);
+    }
+
+    // The taker sells 10 USD for 4 EUR through XRP, from MAKER's offer buying USD for 20 XRP and
+    // OTHER_MAKER's offer selling 4 EUR for 20 XRP
+    fn autobridged() -> Vec<BalanceChange> {
+        vec![
+            change(TAKER, &usd(), "-10"),
+            change(TAKER, &eur(), "4"),
+            change(MAKER, &usd(), "10"),
+            change(MAKER, &xrp(), "-20"),
+            change(OTHER_MAKER, &xrp(), "20"),
+            change(OTHER_MAKER, &eur(), "-4"),
+        ]
+    }
+
+    #[test]
+    fn pairs_both_trades_of_an_autobridged_exchange() {
+        let changes = autobridged();
+
+        let exchanges = pair_exchanges(&changes, &xrp(), &usd(), Some(TAKER));
+        assert_eq!(
+            trades(&exchanges),
+            [(
+                TAKER,
+                MAKER,
+                TAKER,
+                MAKER,
+                "20".into(),
+                "10".into(),
+                "0.5".into()
+            )]
+        );
+        let exchanges = pair_exchanges(&changes, &xrp(), &eur(), Some(TAKER));
+        assert_eq!(
+            trades(&exchanges),
+            [(
+                OTHER_MAKER,
+                TAKER,
+                TAKER,
+                OTHER_MAKER,
+                "20".into(),
+                "4".into(),
+                "0.2".into()
+            )]
+        );
+        // The taker's own pair has no offers
+        assert!(pair_exchanges(&changes, &eur(), &usd(), Some(TAKER)).is_empty());
+    }
+
+    #[test]
+    fn finds_the_pairs_an_account_traded() {
+        let changes = autobridged();
+
+        assert_eq!(
+            account_pairs(&changes, TAKER),
+            [(xrp(), eur()), (xrp(), usd())]
+        );
+        assert_eq!(account_pairs(&changes, MAKER), [(xrp(), usd())]);
+        assert!(account_pairs(&changes, ISSUER).is_empty());
+
+        let changes = [change(TAKER, &usd(), "-10"), change(TAKER, &eur(), "4")];
+        assert_eq!(account_pairs(&changes, TAKER), [(eur(), usd())]);
+    }
+
+    #[test]
+    fn keys_pairs_in_either_order() {
+        let key = ("XRP".to_string(), format!("USD+{}", ISSUER));
+        assert_eq!(pair_key(&(xrp(), usd())), key);
+        assert_eq!(pair_key(&(usd(), xrp())), key);
+
+        let key = (format!("EUR+{}", ISSUER), format!("USD+{}", ISSUER));
+        assert_eq!(pair_key(&(usd(), eur())), key);
+        assert_eq!(pair_key(&(eur(), usd())), key);
+    }
+
+    type Row = (String, i64, i64);
+
+    async fn page(rows: &[(&str, i64, i64)], limit: i32) -> (Vec<String>, Option<String>) {
+        let rows = rows
+            .iter()
+            .map(|(tx_hash, ledger_index, tx_index)| {
+                Ok::<Row, NextRowError>((tx_hash.to_string(), *ledger_index, *tx_index))
+            })
+            .collect::<Vec<_>>();
+        let mut rows = futures::stream::iter(rows);
+        page_transactions(&mut rows, limit, &ScanBudget::default(), |row| {
+            let (tx_hash, ledger_index, tx_index) = row;
+            (tx_hash != "skipped").then_some((tx_hash, (ledger_index, BigInt::from(tx_index))))
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn pages_whole_transactions() {
+        let rows = [
+            ("a", 10, 0),
+            ("a", 10, 0),
+            ("skipped", 10, 1),
+            ("b", 10, 2),
+            ("b", 10, 2),
+            ("c", 11, 0),
+        ];
+
+        let (tx_hashes, marker) = page(&rows, 2).await;
+        assert_eq!(tx_hashes, ["a", "b"]);
+        assert_eq!(marker.as_deref(), Some("10.2"));
+
+        // A page that ends with the rows has no marker
+        let (tx_hashes, marker) = page(&rows, 3).await;
+        assert_eq!(tx_hashes, ["a", "b", "c"]);
+        assert_eq!(marker, None);
+    }
+}
@@ -0,0 +1,60 @@
+use crate::gaps::{find_gaps, latest_ledger_index, LedgerGaps};
+use crate::utils::consts::GAP_SCAN_MAX_LEDGERS;
+use crate::utils::errors::{map_error_to_status_code, DataApiError};
+use crate::utils::params::GapsParams;
+use crate::AppState;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+use scylla::Session;
+use std::sync::Arc;
+
+#[utoipa::path(
+    get,
+    path = "/admin/gaps",
+    tag = "admin",
+    params(GapsParams),
+    responses(
+        (status = 200, description = "Missing, unprocessed and unindexed ledgers of the range, as ranges", body = LedgerGaps),
+        (status = 400, description = "Start after end, or a range longer than a request may scan"),
+        (status = 404, description = "No ledgers were ingested, so there is no default end"),
+    ),
+)]
+pub async fn get_gaps_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<GapsParams>,
+) -> Result<Json<LedgerGaps>, StatusCode> {
+    match get_gaps(&state.scylla_session, &params).await {
+        Ok(gaps) => Ok(Json(gaps)),
+        Err(err) => {
+            eprintln!("{}", err);
+            Err(map_error_to_status_code(&err))
+        }
+    }
+}
+
+async fn get_gaps(session: &Session, params: &GapsParams) -> Result<LedgerGaps, DataApiError> {
+    let end = match params.end {
+        Some(end) => end,
+        None => latest_ledger_index(session)
+            .await?
+            .ok_or(DataApiError::NoDataReturned)?,
+    };
+    let start = params
+        .start
+        .unwrap_or_else(|| (end - GAP_SCAN_MAX_LEDGERS + 1).max(1));
+    if start > end {
+        return Err(DataApiError::InvalidParameter(format!(
+            "start {} is after end {}",
+            start, end
+        )));
+    }
+    if end - start + 1 > GAP_SCAN_MAX_LEDGERS {
+        return Err(DataApiError::InvalidParameter(format!(
+            "at most {} ledgers are scanned per request, use the gaps binary for more",
+            GAP_SCAN_MAX_LEDGERS
+        )));
+    }
+
+    Ok(find_gaps(session, start, end).await?)
+}
@@ -0,0 +1,45 @@
+use crate::graphql::with_request_data;
+use crate::utils::auth::Client;
+use crate::utils::rows::ScanBudget;
+use crate::AppState;
+use async_graphql::http::GraphiQLSource;
+use axum::extract::{MatchedPath, State};
+use axum::response::Html;
+use axum::{Extension, Json};
+use std::sync::Arc;
+
+#[utoipa::path(
+    post,
+    path = "/graphql",
+    tag = "graphql",
+    request_body(content = Object, description = "GraphQL request with `query`, `operationName` and `variables`"),
+    responses(
+        (status = 200, description = "GraphQL response, errors included, queries above the depth or complexity limit are rejected with errors", body = Object),
+    ),
+)]
+pub async fn graphql_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(client): Extension<Client>,
+    Extension(budget): Extension<ScanBudget>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    let request = with_request_data(request, &state.scylla_session, client, budget);
+    Json(state.graphql_schema.execute(request).await)
+}
+
+#[utoipa::path(
+    get,
+    path = "/graphql",
+    tag = "graphql",
+    responses(
+        (status = 200, description = "GraphiQL page for exploring the schema", content_type = "text/html"),
+    ),
+)]
+pub async fn graphiql_handler(matched_path: MatchedPath) -> Html<String> {
+    // The page posts back to the path it was served from, versioned or not
+    Html(
+        GraphiQLSource::build()
+            .endpoint(matched_path.as_str())
+            .finish(),
+    )
+}
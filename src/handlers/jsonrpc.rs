@@ -0,0 +1,26 @@
+use crate::jsonrpc::{dispatch, RpcRequest};
+use crate::utils::auth::Client;
+use crate::utils::rows::ScanBudget;
+use crate::AppState;
+use axum::extract::State;
+use axum::{Extension, Json};
+use serde_json::Value;
+use std::sync::Arc;
+
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "jsonrpc",
+    request_body(content = Object, description = "rippled JSON-RPC request, `{\"method\": ..., \"params\": [{...}]}`. Supports `tx`, `account_tx`, `ledger`, `ledger_closed` and `account_info`"),
+    responses(
+        (status = 200, description = "rippled JSON-RPC response, errors are results with an `error` status", body = Object),
+    ),
+)]
+pub async fn jsonrpc_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(client): Extension<Client>,
+    Extension(budget): Extension<ScanBudget>,
+    Json(request): Json<RpcRequest>,
+) -> Json<Value> {
+    Json(dispatch(&state, &client, &budget, request).await)
+}
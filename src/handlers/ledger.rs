@@ -4,22 +4,47 @@ use std::sync::Arc;
 use crate::handlers::daily_ledger::get_last_closed_ledger;
 use crate::models::ledger::Ledger;
 use crate::utils::consts::LEDGER_TABLE;
+use crate::utils::cache::CachedResponse;
 use crate::utils::errors::{map_error_to_status_code, DataApiError};
+use crate::utils::format::JSON_CONTENT_TYPE;
 use axum::extract::{Path, State};
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
 use axum::Json;
+use std::fmt;
 use chrono::{DateTime, Utc};
 use scylla::Session;
 
-enum LedgerIdentifier {
+pub enum LedgerIdentifier {
     BigInt(i64),
     String(String),
 }
 
+impl fmt::Display for LedgerIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LedgerIdentifier::BigInt(value) => write!(f, "{}", value),
+            LedgerIdentifier::String(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/ledger/{ledger_identifier}",
+    tag = "ledgers",
+    params(("ledger_identifier" = String, Path, description = "Ledger index, ledger hash or a close time")),
+    responses(
+        (status = 200, description = "The ledger", body = Ledger),
+        (status = 304, description = "The ledger did not change since the given ETag"),
+        (status = 404, description = "Ledger not found"),
+    ),
+)]
 pub async fn get_ledger_handler(
     State(state): State<Arc<AppState>>,
     Path(ledger_identifier): Path<String>,
-) -> Result<Json<Ledger>, StatusCode> {
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
     let time_parsed = ledger_identifier.parse::<DateTime<Utc>>();
     if let Ok(close_time) = time_parsed {
         println!(
@@ -27,21 +52,26 @@ pub async fn get_ledger_handler(
             close_time.format("%Y-%m-%d")
         );
         match get_ledger_at_time(&state.scylla_session, close_time).await {
-            Ok(ledger) => Ok(Json(ledger)),
+            Ok(ledger) => Ok(Json(ledger).into_response()),
             Err(err) => {
                 eprintln!("{}", err);
                 Err(map_error_to_status_code(&err))
             }
         }
     } else {
-        let search_terms = match ledger_identifier.parse::<i64>() {
-            Ok(ledger_index) => ("ledger_index", LedgerIdentifier::BigInt(ledger_index)),
-            Err(_) => ("ledger_hash", LedgerIdentifier::String(ledger_identifier)),
-        };
+        let search_terms = parse_ledger_identifier(ledger_identifier);
         println!("Finding ledger with {}", search_terms.0);
 
-        match get_ledger(&state.scylla_session, search_terms.0, &search_terms.1).await {
-            Ok(ledger) => Ok(Json(ledger)),
+        // Ledgers never change once processed, by index or by hash
+        let key = format!("ledger/{}/{}", search_terms.0, search_terms.1);
+        let session = state.scylla_session.clone();
+        let response = state.response_cache.respond(key, &headers, async move {
+            let ledger = get_ledger(&session, search_terms.0, &search_terms.1).await?;
+            let body = serde_json::to_vec(&ledger)?;
+            Ok(CachedResponse::new(JSON_CONTENT_TYPE, body, ledger.ledger_processed))
+        });
+        match response.await {
+            Ok(response) => Ok(response),
             Err(err) => {
                 eprintln!("{}", err);
                 Err(map_error_to_status_code(&err))
@@ -50,6 +80,14 @@ pub async fn get_ledger_handler(
     }
 }
 
+/// Column and value to find a ledger by, an index or otherwise a hash.
+pub fn parse_ledger_identifier(ledger_identifier: String) -> (&'static str, LedgerIdentifier) {
+    match ledger_identifier.parse::<i64>() {
+        Ok(ledger_index) => ("ledger_index", LedgerIdentifier::BigInt(ledger_index)),
+        Err(_) => ("ledger_hash", LedgerIdentifier::String(ledger_identifier)),
+    }
+}
+
 pub async fn get_ledger_at_time(
     session: &Session,
     close_time: DateTime<Utc>,
@@ -65,7 +103,7 @@ pub async fn get_ledger_at_time(
     Ok(ledger_result)
 }
 
-async fn get_ledger(
+pub async fn get_ledger(
     session: &Session,
     field: &str,
     value: &LedgerIdentifier,
@@ -0,0 +1,315 @@
+use crate::handlers::balance_change::{get_balance_changes_page, get_transaction_balance_changes};
+use crate::handlers::daily_ledger::get_ledger_index_bounds;
+use crate::handlers::ledger::{get_ledger, get_ledger_at_time, parse_ledger_identifier};
+use crate::handlers::payment::{get_payments_page, PaymentDirection};
+use crate::handlers::transaction::{get_ledger_transactions, get_transactions};
+use crate::models::legacy::{
+    LegacyBalanceChange, LegacyBalanceChangesResponse, LegacyError, LegacyLedger,
+    LegacyLedgerResponse, LegacyLedgerTransactions, LegacyPayment, LegacyPaymentsResponse,
+    LegacyTransaction, LegacyTransactionResponse, LEGACY_ERROR, LEGACY_SUCCESS,
+};
+use crate::models::payment::Payment;
+use crate::utils::auth::Client;
+use crate::utils::consts::{LEGACY_DEFAULT_LIMIT, LEGACY_LOOKUP_CONCURRENCY};
+use crate::utils::errors::{map_error_to_status_code, DataApiError};
+use crate::utils::pagination::{decode_marker, decode_position};
+use crate::utils::params::{
+    LegacyBalanceChangesParams, LegacyLedgerParams, LegacyPaymentType, LegacyPaymentsParams,
+    LegacyTransactionParams,
+};
+use crate::utils::rows::ScanBudget;
+use crate::AppState;
+use axum::extract::{Path, Query, State};
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
+use chrono::{DateTime, Utc};
+use futures::{StreamExt, TryStreamExt};
+use scylla::Session;
+use std::sync::Arc;
+
+#[utoipa::path(
+    get,
+    path = "/legacy/v2/ledgers/{ledger_identifier}",
+    tag = "legacy",
+    params(("ledger_identifier" = String, Path, description = "Ledger index, ledger hash or a close time"), LegacyLedgerParams),
+    responses(
+        (status = 200, description = "The ledger, in the Data API v2 format", body = LegacyLedgerResponse),
+        (status = 400, description = "Binary transactions were asked for", body = LegacyError),
+        (status = 404, description = "Ledger not found", body = LegacyError),
+    ),
+)]
+pub async fn get_legacy_ledger_handler(
+    State(state): State<Arc<AppState>>,
+    Path(ledger_identifier): Path<String>,
+    Extension(budget): Extension<ScanBudget>,
+    Query(params): Query<LegacyLedgerParams>,
+) -> Response {
+    let ledger = get_legacy_ledger(&state.scylla_session, ledger_identifier, &params, &budget);
+    match ledger.await {
+        Ok(ledger) => Json(LegacyLedgerResponse {
+            result: LEGACY_SUCCESS.to_string(),
+            ledger,
+        })
+        .into_response(),
+        Err(err) => {
+            eprintln!("{}", err);
+            legacy_error(&err, "ledger not found")
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/legacy/v2/transactions/{tx_hash}",
+    tag = "legacy",
+    params(("tx_hash" = String, Path, description = "Transaction hash"), LegacyTransactionParams),
+    responses(
+        (status = 200, description = "The transaction, in the Data API v2 format", body = LegacyTransactionResponse),
+        (status = 400, description = "The binary format was asked for", body = LegacyError),
+        (status = 404, description = "Transaction not found", body = LegacyError),
+    ),
+)]
+pub async fn get_legacy_transaction_handler(
+    State(state): State<Arc<AppState>>,
+    Path(tx_hash): Path<String>,
+    Query(params): Query<LegacyTransactionParams>,
+) -> Response {
+    let transaction = async {
+        reject_binary(params.binary)?;
+        let mut transactions = get_transactions(&state.scylla_session, "hash", (&tx_hash,)).await?;
+        transactions.pop().ok_or(DataApiError::NoDataReturned)
+    };
+    match transaction.await {
+        Ok(transaction) => Json(LegacyTransactionResponse {
+            result: LEGACY_SUCCESS.to_string(),
+            transaction: LegacyTransaction::from(&transaction),
+        })
+        .into_response(),
+        Err(err) => {
+            eprintln!("{}", err);
+            legacy_error(&err, "transaction not found")
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/legacy/v2/accounts/{account}/payments",
+    tag = "legacy",
+    params(("account" = String, Path, description = "Account address"), LegacyPaymentsParams),
+    responses(
+        (status = 200, description = "Payments sent or received by the account, in the Data API v2 format. Filters apply within each page, so pages may be short and still have a `marker`", body = LegacyPaymentsResponse),
+        (status = 400, description = "Invalid parameter or marker", body = LegacyError),
+    ),
+)]
+pub async fn get_legacy_payments_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(client): Extension<Client>,
+    Path(account): Path<String>,
+    Extension(budget): Extension<ScanBudget>,
+    Query(params): Query<LegacyPaymentsParams>,
+) -> Response {
+    let limit = legacy_limit(params.limit, &client);
+    let payments = get_legacy_payments(&state.scylla_session, &account, &params, limit, &budget);
+    match payments.await {
+        Ok((payments, marker)) => Json(LegacyPaymentsResponse {
+            result: LEGACY_SUCCESS.to_string(),
+            count: payments.len(),
+            marker,
+            payments,
+        })
+        .into_response(),
+        Err(err) => {
+            eprintln!("{}", err);
+            legacy_error(&err, "account not found")
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/legacy/v2/accounts/{account}/balance_changes",
+    tag = "legacy",
+    params(("account" = String, Path, description = "Account address"), LegacyBalanceChangesParams),
+    responses(
+        (status = 200, description = "Balance changes of the account, in the Data API v2 format. Filters apply within each page, so pages may be short and still have a `marker`", body = LegacyBalanceChangesResponse),
+        (status = 400, description = "Invalid parameter or marker", body = LegacyError),
+    ),
+)]
+pub async fn get_legacy_balance_changes_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(client): Extension<Client>,
+    Path(account): Path<String>,
+    Query(params): Query<LegacyBalanceChangesParams>,
+) -> Response {
+    let limit = legacy_limit(params.limit, &client);
+    match get_legacy_balance_changes(&state.scylla_session, &account, &params, limit).await {
+        Ok((balance_changes, marker)) => Json(LegacyBalanceChangesResponse {
+            result: LEGACY_SUCCESS.to_string(),
+            count: balance_changes.len(),
+            marker,
+            balance_changes,
+        })
+        .into_response(),
+        Err(err) => {
+            eprintln!("{}", err);
+            legacy_error(&err, "account not found")
+        }
+    }
+}
+
+async fn get_legacy_ledger(
+    session: &Session,
+    ledger_identifier: String,
+    params: &LegacyLedgerParams,
+    budget: &ScanBudget,
+) -> Result<LegacyLedger, DataApiError> {
+    reject_binary(params.binary)?;
+    let ledger = match ledger_identifier.parse::<DateTime<Utc>>() {
+        Ok(close_time) => get_ledger_at_time(session, close_time).await?,
+        Err(_) => {
+            let (field, value) = parse_ledger_identifier(ledger_identifier);
+            get_ledger(session, field, &value).await?
+        }
+    };
+
+    let mut legacy_ledger = LegacyLedger::from(&ledger);
+    let expand = params.expand == Some(true);
+    if expand || params.transactions == Some(true) {
+        let transactions = get_ledger_transactions(session, ledger.ledger_index, budget).await?;
+        legacy_ledger.transactions = Some(if expand {
+            LegacyLedgerTransactions::Expanded(
+                transactions.iter().map(LegacyTransaction::from).collect(),
+            )
+        } else {
+            LegacyLedgerTransactions::Hashes(
+                transactions
+                    .into_iter()
+                    .map(|transaction| transaction.hash)
+                    .collect(),
+            )
+        });
+    }
+    Ok(legacy_ledger)
+}
+
+async fn get_legacy_payments(
+    session: &Session,
+    account: &str,
+    params: &LegacyPaymentsParams,
+    limit: i32,
+    budget: &ScanBudget,
+) -> Result<(Vec<LegacyPayment>, Option<String>), DataApiError> {
+    let directions: &[PaymentDirection] = match params.payment_type {
+        Some(LegacyPaymentType::Sent) => &[PaymentDirection::Sent],
+        Some(LegacyPaymentType::Received) => &[PaymentDirection::Received],
+        None => PaymentDirection::ALL,
+    };
+    let after = decode_position(params.marker.as_deref())?;
+    let bounds = get_ledger_index_bounds(session, params.start, params.end).await?;
+    let descending = params.descending == Some(true);
+    let page = get_payments_page(
+        session, account, directions, bounds, after, descending, limit, budget,
+    )
+    .await?;
+
+    let payments = page
+        .items
+        .into_iter()
+        .filter(|payment| matches_payment(payment, params))
+        .collect::<Vec<Payment>>();
+    let payments = futures::stream::iter(payments)
+        .map(|payment| async move {
+            let changes = get_transaction_balance_changes(session, &payment.tx_hash).await?;
+            Ok::<_, DataApiError>(LegacyPayment::new(&payment, &changes))
+        })
+        .buffered(LEGACY_LOOKUP_CONCURRENCY)
+        .try_collect::<Vec<LegacyPayment>>()
+        .await?;
+
+    Ok((payments, page.marker))
+}
+
+async fn get_legacy_balance_changes(
+    session: &Session,
+    account: &str,
+    params: &LegacyBalanceChangesParams,
+    limit: i32,
+) -> Result<(Vec<LegacyBalanceChange>, Option<String>), DataApiError> {
+    let paging_state = decode_marker(params.marker.as_deref())?;
+    let bounds = get_ledger_index_bounds(session, params.start, params.end).await?;
+    let descending = params.descending == Some(true);
+    let page =
+        get_balance_changes_page(session, account, bounds, descending, limit, paging_state).await?;
+
+    let balance_changes = page
+        .items
+        .iter()
+        .filter(|change| {
+            params
+                .currency
+                .as_ref()
+                .is_none_or(|currency| change.currency.eq_ignore_ascii_case(currency))
+        })
+        .filter(|change| {
+            params
+                .counterparty
+                .as_ref()
+                .is_none_or(|counterparty| change.counterparty.as_ref() == Some(counterparty))
+        })
+        .filter(|change| {
+            params
+                .change_type
+                .as_ref()
+                .is_none_or(|change_type| &change.change_type == change_type)
+        })
+        .map(LegacyBalanceChange::from)
+        .collect::<Vec<LegacyBalanceChange>>();
+
+    Ok((balance_changes, page.marker))
+}
+
+fn matches_payment(payment: &Payment, params: &LegacyPaymentsParams) -> bool {
+    params
+        .currency
+        .as_ref()
+        .is_none_or(|currency| payment.destination_currency.eq_ignore_ascii_case(currency))
+        && params
+            .issuer
+            .as_ref()
+            .is_none_or(|issuer| &payment.destination_currency_issuer == issuer)
+        && params
+            .source_tag
+            .is_none_or(|source_tag| payment.source_tag == Some(source_tag))
+        && params
+            .destination_tag
+            .is_none_or(|destination_tag| payment.destination_tag == Some(destination_tag))
+}
+
+// Explicit limits are already clamped to the tier's maximum by `enforce_limits`
+fn legacy_limit(limit: Option<i32>, client: &Client) -> i32 {
+    limit.unwrap_or(LEGACY_DEFAULT_LIMIT.min(client.tier.max_limit))
+}
+
+// Transactions are stored as JSON, so there are no binary blobs to return
+fn reject_binary(binary: Option<bool>) -> Result<(), DataApiError> {
+    match binary {
+        Some(true) => Err(DataApiError::InvalidParameter(
+            "binary is not supported".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Error in the Data API v2 envelope, `not_found` is the message for missing data.
+fn legacy_error(err: &DataApiError, not_found: &str) -> Response {
+    let message = match err {
+        DataApiError::NoDataReturned => not_found.to_string(),
+        err => err.to_string(),
+    };
+    let body = LegacyError {
+        result: LEGACY_ERROR.to_string(),
+        message,
+    };
+    (map_error_to_status_code(err), Json(body)).into_response()
+}
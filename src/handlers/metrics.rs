@@ -0,0 +1,24 @@
+use crate::AppState;
+use axum::extract::State;
+use axum::http::header::CONTENT_TYPE;
+use axum::response::IntoResponse;
+use std::sync::Arc;
+
+// Version of the Prometheus text exposition format
+static METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Operational metrics for scraping, served without a key.
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "metrics",
+    responses(
+        (status = 200, description = "Metrics in the Prometheus text format, including the gaps among the latest ledgers", content_type = "text/plain"),
+    ),
+)]
+pub async fn get_metrics_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    (
+        [(CONTENT_TYPE, METRICS_CONTENT_TYPE)],
+        state.metrics.render(),
+    )
+}
@@ -1,6 +1,19 @@
 pub mod account;
+pub mod api_key;
 pub mod balance_change;
+pub mod batch;
+pub mod candle;
 pub mod daily_ledger;
+pub mod exchange;
+pub mod gaps;
+pub mod graphql;
+pub mod jsonrpc;
+pub mod legacy;
 pub mod ledger;
+pub mod metrics;
 pub mod payment;
+pub mod stats;
+pub mod stream;
+pub mod subscription;
 pub mod transaction;
+pub mod webhook;
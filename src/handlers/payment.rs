@@ -1,24 +1,109 @@
 use crate::models::payment::Payment;
-use crate::utils::consts::{DEFAULT_RESULT_LIMIT, PAYMENTS_TABLE};
+use crate::utils::consts::{
+    DEFAULT_RESULT_LIMIT, PAYMENTS_DESTINATION_MV_TABLE, PAYMENTS_LEDGER_MV_TABLE, PAYMENTS_TABLE,
+    STREAM_PAGE_SIZE,
+};
 use crate::utils::errors::{map_error_to_status_code, DataApiError};
+use crate::utils::format::{Formatted, OutputFormat};
+use crate::utils::pagination::{decode_position, encode_position, Paginated};
 use crate::utils::params::DataApiQueryParams;
+use crate::utils::rows::{collect_rows, merge_descending, ScanBudget};
+use crate::utils::stream::stream_rows;
 use crate::AppState;
 use axum::extract::{Path, State};
 use axum::http::StatusCode;
-use axum::Json;
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use futures::Stream;
+use num_bigint::BigInt;
+use scylla::transport::iterator::NextRowError;
 use scylla::Session;
 use std::sync::Arc;
 
+/// Side of a payment an account is on. Sent payments are read from the payments table,
+/// received ones from its view by destination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentDirection {
+    Sent,
+    Received,
+}
+
+impl PaymentDirection {
+    pub const ALL: &'static [PaymentDirection] =
+        &[PaymentDirection::Sent, PaymentDirection::Received];
+
+    fn table(&self) -> &'static str {
+        match self {
+            PaymentDirection::Sent => PAYMENTS_TABLE,
+            PaymentDirection::Received => PAYMENTS_DESTINATION_MV_TABLE,
+        }
+    }
+
+    fn field(&self) -> &'static str {
+        match self {
+            PaymentDirection::Sent => "source",
+            PaymentDirection::Received => "destination",
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/account/{account}/payments",
+    tag = "accounts",
+    params(("account" = String, Path, description = "Account address"), DataApiQueryParams, ("format" = Option<OutputFormat>, Query, description = "Output format, overrides the `Accept` header")),
+    responses(
+        (status = 200, description = "Payments sent by the account, newest first, the next page marker is in `x-next-marker`", body = [Payment]),
+        (status = 400, description = "Invalid limit or marker"),
+    ),
+)]
 pub async fn get_account_payments_handler(
     State(state): State<Arc<AppState>>,
     Path(account): Path<String>,
+    Extension(budget): Extension<ScanBudget>,
     params: axum::extract::Query<DataApiQueryParams>,
-) -> anyhow::Result<Json<Vec<Payment>>, StatusCode> {
+    format: OutputFormat,
+) -> anyhow::Result<Response, StatusCode> {
     println!("Params: {:?}", params.limit);
+    if params.stream == Some(true) {
+        let limit = params.limit.map(|limit| limit.max(0) as usize);
+        return match stream_payments(
+            &state.scylla_session,
+            &account,
+            &[PaymentDirection::Sent],
+            limit,
+            format,
+            budget,
+        )
+        .await
+        {
+            Ok(response) => Ok(response),
+            Err(err) => {
+                eprintln!("{}", err);
+                Err(map_error_to_status_code(&err))
+            }
+        };
+    }
     let limit = params.limit.unwrap_or(DEFAULT_RESULT_LIMIT);
 
-    match get_payments(&state.scylla_session, "source", &account, limit).await {
-        Ok(payments) => Ok(Json(payments)),
+    let payments = match decode_position(params.marker.as_deref()) {
+        Ok(after) => {
+            get_payments_page(
+                &state.scylla_session,
+                &account,
+                &[PaymentDirection::Sent],
+                (0, i64::MAX),
+                after,
+                true,
+                limit,
+                &budget,
+            )
+            .await
+        }
+        Err(err) => Err(err),
+    };
+    match payments {
+        Ok(payments) => Ok(Formatted::page(format, payments).into_response()),
         Err(err) => {
             eprintln!("{}", err);
             Err(map_error_to_status_code(&err))
@@ -26,13 +111,8 @@ pub async fn get_account_payments_handler(
     }
 }
 
-async fn get_payments(
-    session: &Session,
-    field: &str,
-    value: &str,
-    limit: i32,
-) -> Result<Vec<Payment>, DataApiError> {
-    let query = format!(
+fn payments_query(table: &str, filter: &str) -> String {
+    format!(
         "SELECT tx_hash, \
             ledger_index, \
             tx_index, \
@@ -48,25 +128,210 @@ async fn get_payments(
             destination_tag, \
             source_tag, \
             timestamp \
-        from {} WHERE {}=?;",
-        PAYMENTS_TABLE, field
-    );
-    let mut query = scylla::query::Query::new(query);
-    query.set_page_size(limit);
+        from {} WHERE {};",
+        table, filter
+    )
+}
 
-    println!("Query: {}", query.contents);
-    let query_result = session.query_paged(query, (value,), None).await?;
-    let payments_iter = query_result.rows_typed_or_empty();
+/// The latest `limit` payments of an account in each of `directions`, newest first.
+pub async fn get_payments(
+    session: &Session,
+    account: &str,
+    directions: &[PaymentDirection],
+    limit: i32,
+) -> Result<Vec<Payment>, DataApiError> {
+    let mut pages = Vec::new();
+    for direction in directions {
+        let filter = format!("{}=?", direction.field());
+        let mut query = scylla::query::Query::new(payments_query(direction.table(), &filter));
+        query.set_page_size(limit);
+
+        println!("Query: {}", query.contents);
+        let query_result = session.query_paged(query, (account,), None).await?;
 
-    // todo: better row error handling
-    let payments = payments_iter
-        .filter_map(|row| row.ok())
-        .collect::<Vec<Payment>>();
+        // todo: better row error handling
+        pages.push(
+            query_result
+                .rows_typed_or_empty::<Payment>()
+                .filter_map(|row| row.ok())
+                .collect::<Vec<Payment>>(),
+        );
+    }
+    let mut payments = newest_first(pages);
+    payments.truncate(limit.max(0) as usize);
 
     if payments.is_empty() {
         return Err(DataApiError::NoDataReturned);
     }
-    println!("Returning {} balance changes", payments.len());
+    println!("Returning {} payments", payments.len());
 
     Ok(payments)
 }
+
+/// A page of payments of an account in each of `directions` within ledgers `min_ledger` to
+/// `max_ledger` (inclusive), newest first when `descending`. `after` is the (ledger index,
+/// transaction index) of the last payment of the previous page.
+#[allow(clippy::too_many_arguments)]
+pub async fn get_payments_page(
+    session: &Session,
+    account: &str,
+    directions: &[PaymentDirection],
+    (min_ledger, max_ledger): (i64, i64),
+    after: Option<(i64, BigInt)>,
+    descending: bool,
+    limit: i32,
+    budget: &ScanBudget,
+) -> Result<Paginated<Payment>, DataApiError> {
+    let (order, position, bound) = if descending {
+        ("DESC", "<", ">=")
+    } else {
+        ("ASC", ">", "<=")
+    };
+    let range = match after {
+        // Single and multi column restrictions can not be mixed, so the bound is a tuple too
+        Some(_) => format!(
+            "(ledger_index, tx_index){}(?, ?) AND (ledger_index){}(?)",
+            position, bound
+        ),
+        None => "ledger_index>=? AND ledger_index<=?".to_string(),
+    };
+    let mut pages = Vec::new();
+    for direction in directions {
+        let filter = format!(
+            "{}=? AND {} ORDER BY ledger_index {}, tx_index {} LIMIT {}",
+            direction.field(),
+            range,
+            order,
+            order,
+            limit
+        );
+        let mut query = scylla::query::Query::new(payments_query(direction.table(), &filter));
+        query.set_page_size(limit);
+
+        println!("Query: {}", query.contents);
+        let page = match &after {
+            Some((ledger_index, tx_index)) => {
+                let last_ledger = if descending { min_ledger } else { max_ledger };
+                collect_rows(
+                    session,
+                    query,
+                    (account, ledger_index, tx_index, last_ledger),
+                    budget,
+                )
+                .await?
+            }
+            None => collect_rows(session, query, (account, min_ledger, max_ledger), budget).await?,
+        };
+        pages.push(page);
+    }
+    let mut payments = newest_first(pages);
+    if !descending {
+        payments.reverse();
+    }
+    payments.truncate(limit.max(0) as usize);
+    println!("Returning {} payments", payments.len());
+
+    // A full page may be followed by more
+    let marker = match payments.last() {
+        Some(payment) if payments.len() == limit.max(0) as usize => {
+            Some(encode_position(&payment_key(payment)))
+        }
+        _ => None,
+    };
+    Ok(Paginated {
+        items: payments,
+        marker,
+    })
+}
+
+async fn stream_payments(
+    session: &Session,
+    account: &str,
+    directions: &[PaymentDirection],
+    limit: Option<usize>,
+    format: OutputFormat,
+    budget: ScanBudget,
+) -> Result<Response, DataApiError> {
+    let rows = payment_rows(session, account, directions).await?;
+    Ok(stream_rows(rows, format, limit, budget))
+}
+
+/// Lazily paged payments of an account in each of `directions`, newest first, read one page at
+/// a time as they are consumed.
+pub async fn payment_rows(
+    session: &Session,
+    account: &str,
+    directions: &[PaymentDirection],
+) -> Result<impl Stream<Item = Result<Payment, NextRowError>> + Send + Unpin, DataApiError> {
+    let mut queries = Vec::new();
+    for direction in directions {
+        let filter = format!("{}=?", direction.field());
+        let mut query = scylla::query::Query::new(payments_query(direction.table(), &filter));
+        query.set_page_size(STREAM_PAGE_SIZE);
+
+        println!("Streaming query: {}", query.contents);
+        queries.push(session.query_iter(query, (account,)).await?.into_typed());
+    }
+    Ok(merge_descending(queries, payment_key))
+}
+
+/// Payments of an account in each of `directions` in ledgers `after_ledger` (exclusive) to
+/// `up_to_ledger` (inclusive), across all pages, newest first.
+pub async fn get_payments_in_ledger_range(
+    session: &Session,
+    account: &str,
+    directions: &[PaymentDirection],
+    after_ledger: i64,
+    up_to_ledger: i64,
+    budget: &ScanBudget,
+) -> Result<Vec<Payment>, DataApiError> {
+    let mut pages = Vec::new();
+    for direction in directions {
+        let filter = format!(
+            "{}=? AND ledger_index>? AND ledger_index<=?",
+            direction.field()
+        );
+        let mut query = scylla::query::Query::new(payments_query(direction.table(), &filter));
+        query.set_page_size(STREAM_PAGE_SIZE);
+
+        println!("Query: {}", query.contents);
+        pages.push(
+            collect_rows(
+                session,
+                query,
+                (account, after_ledger, up_to_ledger),
+                budget,
+            )
+            .await?,
+        );
+    }
+    Ok(newest_first(pages))
+}
+
+/// All payments in a single ledger, across all pages.
+pub async fn get_ledger_payments(
+    session: &Session,
+    ledger_index: i64,
+    budget: &ScanBudget,
+) -> Result<Vec<Payment>, DataApiError> {
+    let mut query =
+        scylla::query::Query::new(payments_query(PAYMENTS_LEDGER_MV_TABLE, "ledger_index=?"));
+    query.set_page_size(STREAM_PAGE_SIZE);
+
+    println!("Query: {}", query.contents);
+    collect_rows(session, query, (ledger_index,), budget).await
+}
+
+// Position of a payment, unique as a transaction makes at most one payment
+fn payment_key(payment: &Payment) -> (i64, BigInt) {
+    (payment.ledger_index, payment.tx_index.clone())
+}
+
+// Payments of several directions as one list, newest first. Payments an account made to
+// itself are found in both directions and kept once.
+fn newest_first(pages: Vec<Vec<Payment>>) -> Vec<Payment> {
+    let mut payments = pages.into_iter().flatten().collect::<Vec<Payment>>();
+    payments.sort_by_key(|payment| std::cmp::Reverse(payment_key(payment)));
+    payments.dedup_by(|a, b| payment_key(a) == payment_key(b));
+    payments
+}
@@ -0,0 +1,269 @@
+use crate::gaps::find_gaps;
+use crate::handlers::daily_ledger::{get_ledgers_on_day, is_day_final};
+use crate::models::daily_stats::DailyStats;
+use crate::utils::consts::{
+    ACCOUNTS_LEDGER_MV_TABLE, DAILY_STATS_LEDGER_CONCURRENCY, DAILY_STATS_TABLE,
+    PAYMENTS_LEDGER_MV_TABLE, TRANSACTIONS_TABLE, XRP_CURRENCY,
+};
+use crate::utils::decimal::Decimal;
+use crate::utils::errors::{map_error_to_status_code, DataApiError};
+use crate::utils::rows::{next_row, ScanBudget};
+use crate::AppState;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use chrono::{NaiveDate, Utc};
+use futures::{StreamExt, TryStreamExt};
+use num_bigint::BigInt;
+use scylla::Session;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+#[utoipa::path(
+    get,
+    path = "/stats/daily/{date}",
+    tag = "stats",
+    params(("date" = String, Path, description = "Day as `YYYY-MM-DD`")),
+    responses(
+        (status = 200, description = "Network statistics of the day", body = DailyStats),
+        (status = 400, description = "Invalid day"),
+        (status = 422, description = "Day too large to compute within the request, and not computed by the stats worker"),
+    ),
+)]
+pub async fn get_daily_stats_handler(
+    State(state): State<Arc<AppState>>,
+    Path(date): Path<String>,
+    Extension(budget): Extension<ScanBudget>,
+) -> Result<Json<Arc<DailyStats>>, StatusCode> {
+    let parsed_day = date.parse::<NaiveDate>();
+    match parsed_day {
+        Ok(day) => {
+            println!("Finding network stats for day: {}", day.format("%Y-%m-%d"));
+            // Today and yesterday are kept computed by the stats worker
+            if let Some(stats) = state.daily_stats_cache.get(&day).await {
+                return Ok(Json(stats));
+            }
+            match get_daily_stats(&state.scylla_session, day, &budget).await {
+                Ok(stats) => {
+                    let stats = Arc::new(stats);
+                    state.daily_stats_cache.insert(day, stats.clone()).await;
+                    Ok(Json(stats))
+                }
+                Err(err) => {
+                    eprintln!("{}", err);
+                    Err(map_error_to_status_code(&err))
+                }
+            }
+        }
+        Err(err) => {
+            println!("Failed to parse day: {}", err);
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+/// Returns the persisted stats of a closed day, computing and persisting them on first request.
+/// Days that may still receive ledgers, or whose ledgers have gaps, are computed every time.
+pub async fn get_daily_stats(
+    session: &Session,
+    day: NaiveDate,
+    budget: &ScanBudget,
+) -> Result<DailyStats, DataApiError> {
+    if let Some(stats) = get_persisted_daily_stats(session, day).await? {
+        return Ok(stats);
+    }
+
+    let stats = compute_daily_stats(session, day, budget).await?;
+
+    if is_day_final(day) && is_day_complete(session, &stats).await? {
+        persist_daily_stats(session, &stats).await?;
+    }
+
+    Ok(stats)
+}
+
+async fn get_persisted_daily_stats(
+    session: &Session,
+    day: NaiveDate,
+) -> Result<Option<DailyStats>, DataApiError> {
+    let query = format!("SELECT stats from {} WHERE day=?;", DAILY_STATS_TABLE);
+    println!("Query: {}", query);
+    let query_result = session.query(query, (day,)).await?;
+
+    // todo: better row error handling
+    let stats = query_result
+        .rows_typed_or_empty::<(String,)>()
+        .filter_map(|row| row.ok())
+        .next();
+
+    match stats {
+        Some((stats,)) => Ok(Some(serde_json::from_str(&stats)?)),
+        None => Ok(None),
+    }
+}
+
+// Stats of a day with missing or unprocessed ledgers would be persisted short, so they are only
+// persisted once the day's ledgers are all there. Ledgers missing at the edges of the day can
+// not be told apart from ledgers of the days around it.
+async fn is_day_complete(session: &Session, stats: &DailyStats) -> Result<bool, DataApiError> {
+    let (first, last) = match (stats.first_ledger_index, stats.last_ledger_index) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Ok(false),
+    };
+    let gaps = find_gaps(session, first, last).await?;
+    if gaps.gap_count() > 0 {
+        println!(
+            "Not persisting stats of {}, {} of its ledgers are missing or incomplete",
+            stats.date,
+            gaps.ledger_count()
+        );
+    }
+    Ok(gaps.gap_count() == 0)
+}
+
+async fn persist_daily_stats(session: &Session, stats: &DailyStats) -> Result<(), DataApiError> {
+    let query = format!(
+        "INSERT INTO {} (day, stats, computed_at) VALUES (?, ?, ?);",
+        DAILY_STATS_TABLE
+    );
+    println!("Query: {}", query);
+    session
+        .query(
+            query,
+            (stats.date, serde_json::to_string(stats)?, Utc::now()),
+        )
+        .await?;
+
+    Ok(())
+}
+
+struct LedgerStats {
+    transactions_by_type: BTreeMap<String, u64>,
+    transactions_by_result: BTreeMap<String, u64>,
+    fees_burned: BigInt,
+    new_accounts: u64,
+    payment_volume: BTreeMap<String, Decimal>,
+}
+
+async fn compute_daily_stats(
+    session: &Session,
+    day: NaiveDate,
+    budget: &ScanBudget,
+) -> Result<DailyStats, DataApiError> {
+    let ledgers = get_ledgers_on_day(session, day).await?;
+    let ledger_indexes = ledgers
+        .iter()
+        .map(|ledger| ledger.ledger_index)
+        .collect::<Vec<i64>>();
+
+    let ledger_stats = futures::stream::iter(ledger_indexes.iter().copied())
+        .map(|ledger_index| get_ledger_stats(session, ledger_index, budget))
+        .buffer_unordered(DAILY_STATS_LEDGER_CONCURRENCY)
+        .try_collect::<Vec<LedgerStats>>()
+        .await?;
+
+    let mut transactions_by_type = BTreeMap::new();
+    let mut transactions_by_result = BTreeMap::new();
+    let mut fees_burned = BigInt::from(0);
+    let mut new_accounts = 0;
+    let mut payment_volume = BTreeMap::new();
+    for stats in ledger_stats {
+        for (tx_type, count) in stats.transactions_by_type {
+            *transactions_by_type.entry(tx_type).or_insert(0) += count;
+        }
+        for (result, count) in stats.transactions_by_result {
+            *transactions_by_result.entry(result).or_insert(0) += count;
+        }
+        fees_burned += stats.fees_burned;
+        new_accounts += stats.new_accounts;
+        for (currency, volume) in stats.payment_volume {
+            let total = payment_volume.entry(currency).or_insert_with(Decimal::zero);
+            *total = total.add(&volume);
+        }
+    }
+
+    Ok(DailyStats {
+        date: day,
+        ledger_count: ledger_indexes.len() as u64,
+        first_ledger_index: ledger_indexes.iter().copied().min(),
+        last_ledger_index: ledger_indexes.iter().copied().max(),
+        transaction_count: transactions_by_type.values().sum(),
+        transactions_by_type,
+        transactions_by_result,
+        fees_burned: fees_burned.to_string(),
+        new_accounts,
+        payment_volume: payment_volume
+            .into_iter()
+            .map(|(currency, volume)| (currency, volume.to_string()))
+            .collect(),
+    })
+}
+
+async fn get_ledger_stats(
+    session: &Session,
+    ledger_index: i64,
+    budget: &ScanBudget,
+) -> Result<LedgerStats, DataApiError> {
+    let mut stats = LedgerStats {
+        transactions_by_type: BTreeMap::new(),
+        transactions_by_result: BTreeMap::new(),
+        fees_burned: BigInt::from(0),
+        new_accounts: 0,
+        payment_volume: BTreeMap::new(),
+    };
+
+    let query = format!(
+        "SELECT tx_type, result, fee from {} WHERE ledger_index=?;",
+        TRANSACTIONS_TABLE
+    );
+    let mut rows = session
+        .query_iter(query, (ledger_index,))
+        .await?
+        .into_typed::<(String, i16, BigInt)>();
+    while let Some((tx_type, result, fee)) = next_row(&mut rows, budget).await? {
+        *stats.transactions_by_type.entry(tx_type).or_insert(0) += 1;
+        *stats
+            .transactions_by_result
+            .entry(result.to_string())
+            .or_insert(0) += 1;
+        stats.fees_burned += fee;
+    }
+
+    let query = format!(
+        "SELECT account from {} WHERE ledger_index=?;",
+        ACCOUNTS_LEDGER_MV_TABLE
+    );
+    let mut rows = session
+        .query_iter(query, (ledger_index,))
+        .await?
+        .into_typed::<(String,)>();
+    while next_row(&mut rows, budget).await?.is_some() {
+        stats.new_accounts += 1;
+    }
+
+    let query = format!(
+        "SELECT destination_currency, destination_currency_issuer, delivered_amount \
+        from {} WHERE ledger_index=?;",
+        PAYMENTS_LEDGER_MV_TABLE
+    );
+    let mut rows = session
+        .query_iter(query, (ledger_index,))
+        .await?
+        .into_typed::<(String, String, String)>();
+    while let Some((currency, issuer, delivered_amount)) = next_row(&mut rows, budget).await? {
+        let currency = if currency == XRP_CURRENCY || issuer.is_empty() {
+            currency
+        } else {
+            format!("{}+{}", currency, issuer)
+        };
+        let delivered_amount = Decimal::parse(&delivered_amount)
+            .ok_or(DataApiError::InvalidAmount(delivered_amount))?;
+        let volume = stats
+            .payment_volume
+            .entry(currency)
+            .or_insert_with(Decimal::zero);
+        *volume = volume.add(&delivered_amount);
+    }
+
+    Ok(stats)
+}
@@ -0,0 +1,93 @@
+use crate::models::ledger::Ledger;
+use crate::AppState;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::Response;
+use futures::Stream;
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+#[utoipa::path(
+    get,
+    path = "/stream/ledgers",
+    tag = "streams",
+    responses(
+        (status = 200, description = "Server-sent `ledger` events, one per closed ledger", content_type = "text/event-stream", body = Ledger),
+    ),
+)]
+pub async fn ledgers_sse_handler(
+    State(state): State<Arc<AppState>>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let receiver = state.ledger_feed.subscribe();
+    let events = futures::stream::unfold(receiver, |mut receiver| async move {
+        let ledger = next_ledger(&mut receiver).await?;
+        let event = Event::default()
+            .event("ledger")
+            .id(ledger.ledger_index.to_string())
+            .json_data(&*ledger)
+            .unwrap_or_else(|err| Event::default().event("error").data(err.to_string()));
+        Some((Ok(event), receiver))
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+#[utoipa::path(
+    get,
+    path = "/stream/ledgers/ws",
+    tag = "streams",
+    responses(
+        (status = 101, description = "WebSocket sending a message per closed ledger", body = Ledger),
+    ),
+)]
+pub async fn ledgers_ws_handler(
+    State(state): State<Arc<AppState>>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let receiver = state.ledger_feed.subscribe();
+    ws.on_upgrade(|socket| send_ledgers(socket, receiver))
+}
+
+async fn send_ledgers(mut socket: WebSocket, mut receiver: broadcast::Receiver<Arc<Ledger>>) {
+    loop {
+        tokio::select! {
+            ledger = next_ledger(&mut receiver) => {
+                let ledger = match ledger {
+                    Some(ledger) => ledger,
+                    None => break,
+                };
+                let message = match serde_json::to_string(&*ledger) {
+                    Ok(message) => message,
+                    Err(err) => {
+                        eprintln!("Failed to serialize ledger: {}", err);
+                        continue;
+                    }
+                };
+                if socket.send(Message::Text(message)).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    let _ = socket.close().await;
+}
+
+/// Waits for the next ledger of the feed. Returns `None` once the client fell behind by more
+/// than its buffer, which disconnects it instead of stalling the feed.
+pub async fn next_ledger(receiver: &mut broadcast::Receiver<Arc<Ledger>>) -> Option<Arc<Ledger>> {
+    match receiver.recv().await {
+        Ok(ledger) => Some(ledger),
+        Err(RecvError::Lagged(missed)) => {
+            println!("Dropping ledger subscriber that missed {} ledgers", missed);
+            None
+        }
+        Err(RecvError::Closed) => None,
+    }
+}
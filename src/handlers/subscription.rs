@@ -0,0 +1,318 @@
+use crate::gaps::latest_ledger_index;
+use crate::handlers::balance_change::get_balance_changes_in_ledger_range;
+use crate::handlers::payment::{get_payments_in_ledger_range, PaymentDirection};
+use crate::handlers::transaction::get_transactions_in_ledger_range;
+use crate::models::subscription::{AccountEvent, AccountEventKind, SubscriptionRequest};
+use crate::utils::consts::{SUBSCRIPTION_CATCH_UP_LEDGERS, SUBSCRIPTION_MAX_ACCOUNTS};
+use crate::utils::errors::DataApiError;
+use crate::utils::rows::ScanBudget;
+use crate::AppState;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::Response;
+use num_bigint::BigInt;
+use scylla::Session;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+
+struct AccountSubscription {
+    events: HashSet<AccountEventKind>,
+    // Every event up to and including this ledger was sent to the client
+    delivered_up_to: i64,
+}
+
+type Subscriptions = BTreeMap<String, AccountSubscription>;
+
+#[utoipa::path(
+    get,
+    path = "/stream/accounts/ws",
+    tag = "streams",
+    responses(
+        (status = 101, description = "WebSocket accepting `subscribe` and `unsubscribe` commands and sending account events"),
+    ),
+)]
+pub async fn accounts_ws_handler(
+    State(state): State<Arc<AppState>>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    ws.on_upgrade(|socket| send_account_events(socket, state))
+}
+
+/// Delivers account events ledger by ledger. Each account keeps its own cursor, so ledgers
+/// missed while the client was slow or disconnected are caught up with range queries instead
+/// of being skipped.
+async fn send_account_events(mut socket: WebSocket, state: Arc<AppState>) {
+    let session = &state.scylla_session;
+    let mut receiver = state.ledger_feed.subscribe();
+    let mut subscriptions = Subscriptions::new();
+    let mut latest_ledger: Option<i64> = None;
+
+    loop {
+        tokio::select! {
+            ledger = receiver.recv() => match ledger {
+                Ok(ledger) => latest_ledger = Some(ledger.ledger_index),
+                // Cursors pick up the skipped ledgers together with the next one
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            },
+            message = socket.recv() => {
+                let message = match message {
+                    Some(Ok(Message::Text(message))) => message,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                };
+                if latest_ledger.is_none() {
+                    latest_ledger = latest_ledger_index(session).await.ok().flatten();
+                }
+                let reply = handle_request(&message, &mut subscriptions, latest_ledger);
+                if send_event(&mut socket, &reply).await.is_err() {
+                    break;
+                }
+            },
+        }
+
+        if let Some(latest_ledger) = latest_ledger {
+            if catch_up(&mut socket, session, &mut subscriptions, latest_ledger)
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+    }
+    let _ = socket.close().await;
+}
+
+fn handle_request(
+    message: &str,
+    subscriptions: &mut Subscriptions,
+    latest_ledger: Option<i64>,
+) -> AccountEvent {
+    let request = match serde_json::from_str::<SubscriptionRequest>(message) {
+        Ok(request) => request,
+        Err(err) => {
+            return AccountEvent::Error {
+                message: format!("invalid request ({})", err),
+            }
+        }
+    };
+
+    match request {
+        SubscriptionRequest::Subscribe {
+            accounts,
+            events,
+            since_ledger,
+        } => {
+            let new_accounts = accounts
+                .iter()
+                .filter(|account| !subscriptions.contains_key(*account))
+                .count();
+            if subscriptions.len() + new_accounts > SUBSCRIPTION_MAX_ACCOUNTS {
+                return AccountEvent::Error {
+                    message: format!(
+                        "at most {} accounts can be subscribed",
+                        SUBSCRIPTION_MAX_ACCOUNTS
+                    ),
+                };
+            }
+            let delivered_up_to = match (since_ledger, latest_ledger) {
+                (Some(since_ledger), Some(latest_ledger))
+                    if since_ledger < latest_ledger - SUBSCRIPTION_CATCH_UP_LEDGERS =>
+                {
+                    return AccountEvent::Error {
+                        message: format!(
+                            "since_ledger may be at most {} ledgers before the latest ledger {}",
+                            SUBSCRIPTION_CATCH_UP_LEDGERS, latest_ledger
+                        ),
+                    }
+                }
+                (Some(since_ledger), Some(_)) => since_ledger,
+                (None, Some(latest_ledger)) => latest_ledger,
+                (_, None) => {
+                    return AccountEvent::Error {
+                        message: "no closed ledger to start from".to_string(),
+                    }
+                }
+            };
+            let events = events.map_or_else(
+                || {
+                    HashSet::from([
+                        AccountEventKind::Payments,
+                        AccountEventKind::BalanceChanges,
+                        AccountEventKind::Transactions,
+                    ])
+                },
+                |events| events.into_iter().collect(),
+            );
+            for account in &accounts {
+                subscriptions.insert(
+                    account.clone(),
+                    AccountSubscription {
+                        events: events.clone(),
+                        delivered_up_to,
+                    },
+                );
+            }
+            AccountEvent::Subscribed {
+                accounts,
+                ledger_index: Some(delivered_up_to),
+            }
+        }
+        SubscriptionRequest::Unsubscribe { accounts } => {
+            for account in &accounts {
+                subscriptions.remove(account);
+            }
+            AccountEvent::Unsubscribed { accounts }
+        }
+    }
+}
+
+/// Sends every subscribed account's events up to `latest_ledger`, then confirms the ledger.
+/// Failed queries are reported to the client and retried with the next ledger.
+async fn catch_up(
+    socket: &mut WebSocket,
+    session: &Session,
+    subscriptions: &mut Subscriptions,
+    latest_ledger: i64,
+) -> Result<(), axum::Error> {
+    let mut delivered_all = true;
+    for (account, subscription) in subscriptions.iter_mut() {
+        while subscription.delivered_up_to < latest_ledger {
+            let up_to =
+                latest_ledger.min(subscription.delivered_up_to + SUBSCRIPTION_CATCH_UP_LEDGERS);
+            let events = get_account_events(
+                session,
+                account,
+                &subscription.events,
+                subscription.delivered_up_to,
+                up_to,
+            )
+            .await;
+            match events {
+                Ok(events) => {
+                    for event in events {
+                        send_event(socket, &event).await?;
+                    }
+                    subscription.delivered_up_to = up_to;
+                }
+                Err(err) => {
+                    eprintln!("Failed to load events of {}: {}", account, err);
+                    let message = format!("failed to load events of {}, retrying", account);
+                    send_event(socket, &AccountEvent::Error { message }).await?;
+                    delivered_all = false;
+                    break;
+                }
+            }
+        }
+    }
+
+    if delivered_all && !subscriptions.is_empty() {
+        let ledger_closed = AccountEvent::LedgerClosed {
+            ledger_index: latest_ledger,
+        };
+        send_event(socket, &ledger_closed).await?;
+    }
+    Ok(())
+}
+
+/// Loads the subscribed kinds of events of an account in ledgers `(after_ledger, up_to]`,
+/// ordered by ledger and transaction with each transaction before its balance changes and payment.
+async fn get_account_events(
+    session: &Session,
+    account: &str,
+    events: &HashSet<AccountEventKind>,
+    after_ledger: i64,
+    up_to: i64,
+) -> Result<Vec<AccountEvent>, DataApiError> {
+    // A socket lives for long, so each load of an account's events gets its own budget
+    let budget = ScanBudget::default();
+    let (transactions, balance_changes, payments) = tokio::try_join!(
+        async {
+            if !events.contains(&AccountEventKind::Transactions) {
+                return Ok(Vec::new());
+            }
+            get_transactions_in_ledger_range(
+                session,
+                "account",
+                account,
+                after_ledger,
+                up_to,
+                &budget,
+            )
+            .await
+        },
+        async {
+            if !events.contains(&AccountEventKind::BalanceChanges) {
+                return Ok(Vec::new());
+            }
+            get_balance_changes_in_ledger_range(
+                session,
+                "account",
+                account,
+                after_ledger,
+                up_to,
+                &budget,
+            )
+            .await
+        },
+        async {
+            if !events.contains(&AccountEventKind::Payments) {
+                return Ok(Vec::new());
+            }
+            get_payments_in_ledger_range(
+                session,
+                account,
+                PaymentDirection::ALL,
+                after_ledger,
+                up_to,
+                &budget,
+            )
+            .await
+        },
+    )?;
+
+    let mut ordered: Vec<((i64, BigInt, u8), AccountEvent)> = Vec::new();
+    for transaction in transactions {
+        let key = (transaction.ledger_index, transaction.tx_index.clone(), 0);
+        let account = account.to_string();
+        ordered.push((
+            key,
+            AccountEvent::Transaction {
+                account,
+                transaction,
+            },
+        ));
+    }
+    for balance_change in balance_changes {
+        let key = (
+            balance_change.ledger_index,
+            balance_change.tx_index.clone(),
+            1,
+        );
+        let account = account.to_string();
+        let event = AccountEvent::BalanceChange {
+            account,
+            balance_change,
+        };
+        ordered.push((key, event));
+    }
+    for payment in payments {
+        let key = (payment.ledger_index, payment.tx_index.clone(), 2);
+        let account = account.to_string();
+        ordered.push((key, AccountEvent::Payment { account, payment }));
+    }
+    ordered.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    Ok(ordered.into_iter().map(|(_, event)| event).collect())
+}
+
+async fn send_event(socket: &mut WebSocket, event: &AccountEvent) -> Result<(), axum::Error> {
+    match serde_json::to_string(event) {
+        Ok(message) => socket.send(Message::Text(message)).await,
+        Err(err) => {
+            eprintln!("Failed to serialize account event: {}", err);
+            Ok(())
+        }
+    }
+}
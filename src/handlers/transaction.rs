@@ -1,21 +1,54 @@
+use crate::handlers::daily_ledger::get_ledger_index_bounds;
 use crate::models::transaction::Transaction;
-use crate::utils::consts::{TRANSACTIONS_ACCOUNT_MV_TABLE, TRANSACTIONS_TABLE};
+use crate::utils::cache::CachedResponse;
+use crate::utils::consts::{
+    DEFAULT_RESULT_LIMIT, STREAM_PAGE_SIZE, TRANSACTIONS_ACCOUNT_MV_TABLE, TRANSACTIONS_TABLE,
+};
 use crate::utils::errors::{map_error_to_status_code, DataApiError};
+use crate::utils::format::{Formatted, OutputFormat, JSON_CONTENT_TYPE};
+use crate::utils::pagination::{decode_position, encode_position, Paginated};
+use crate::utils::params::DataApiQueryParams;
+use crate::utils::rows::{collect_rows, ScanBudget};
+use crate::utils::stream::stream_rows;
 use crate::AppState;
 use axum::extract::{Path, State};
-use axum::http::StatusCode;
-use axum::Json;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use num_bigint::BigInt;
 use scylla::_macro_internal::SerializeRow;
 use scylla::query::Query;
+use scylla::transport::iterator::TypedRowIterator;
 use scylla::Session;
 use std::sync::Arc;
 
+#[utoipa::path(
+    get,
+    path = "/transaction/hash/{tx_hash}",
+    tag = "transactions",
+    params(("tx_hash" = String, Path, description = "Transaction hash")),
+    responses(
+        (status = 200, description = "The transaction", body = Transaction),
+        (status = 304, description = "The transaction did not change since the given ETag"),
+        (status = 404, description = "Transaction not found"),
+    ),
+)]
 pub async fn get_transaction_by_hash(
     State(state): State<Arc<AppState>>,
     Path(tx_hash): Path<String>,
-) -> anyhow::Result<Json<Transaction>, StatusCode> {
-    match get_transactions(&state.scylla_session, "hash", (&tx_hash, )).await {
-        Ok(mut transactions) => Ok(Json(transactions.pop().unwrap())),
+    headers: HeaderMap,
+) -> anyhow::Result<Response, StatusCode> {
+    // Transactions are only written once validated, so they never change
+    let key = format!("transaction/{}", tx_hash);
+    let session = state.scylla_session.clone();
+    let response = state.response_cache.respond(key, &headers, async move {
+        let mut transactions = get_transactions(&session, "hash", (&tx_hash, )).await?;
+        let transaction = transactions.pop().ok_or(DataApiError::NoDataReturned)?;
+        let body = serde_json::to_vec(&transaction)?;
+        Ok(CachedResponse::new(JSON_CONTENT_TYPE, body, true))
+    });
+    match response.await {
+        Ok(response) => Ok(response),
         Err(err) => {
             eprintln!("{}", err);
             Err(map_error_to_status_code(&err))
@@ -23,12 +56,39 @@ pub async fn get_transaction_by_hash(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/transaction/account/{account}",
+    tag = "transactions",
+    params(("account" = String, Path, description = "Account address"), DataApiQueryParams, ("format" = Option<OutputFormat>, Query, description = "Output format, overrides the `Accept` header")),
+    responses(
+        (status = 200, description = "Transactions of the account, the next page marker is in `x-next-marker`", body = [Transaction]),
+        (status = 400, description = "Invalid limit or marker"),
+    ),
+)]
 pub async fn get_transaction_by_account(
     State(state): State<Arc<AppState>>,
     Path(account): Path<String>,
-) -> anyhow::Result<Json<Vec<Transaction>>, StatusCode> {
-    match get_transactions(&state.scylla_session, "account", (&account, )).await {
-        Ok(transactions) => Ok(Json(transactions)),
+    Extension(budget): Extension<ScanBudget>,
+    params: axum::extract::Query<DataApiQueryParams>,
+    format: OutputFormat,
+) -> anyhow::Result<Response, StatusCode> {
+    if params.stream == Some(true) {
+        let limit = params.limit.map(|limit| limit.max(0) as usize);
+        let rows = stream_transactions(&state.scylla_session, "account", (&account, ), limit, format, budget);
+        return match rows.await {
+            Ok(response) => Ok(response),
+            Err(err) => {
+                eprintln!("{}", err);
+                Err(map_error_to_status_code(&err))
+            }
+        };
+    }
+    let limit = params.limit.unwrap_or(DEFAULT_RESULT_LIMIT);
+
+    let transactions = get_account_transactions(&state.scylla_session, &account, &params, limit, &budget);
+    match transactions.await {
+        Ok(transactions) => Ok(Formatted::page(format, transactions).into_response()),
         Err(err) => {
             eprintln!("{}", err);
             Err(map_error_to_status_code(&err))
@@ -36,12 +96,30 @@ pub async fn get_transaction_by_account(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/transaction/ledger/{ledger_index}",
+    tag = "transactions",
+    params(("ledger_index" = i64, Path, description = "Ledger index"), ("format" = Option<OutputFormat>, Query, description = "Output format, overrides the `Accept` header")),
+    responses(
+        (status = 200, description = "Transactions of the ledger", body = [Transaction]),
+        (status = 404, description = "Ledger has no transactions"),
+        (status = 422, description = "The ledger has more transactions than the scan budget"),
+    ),
+)]
 pub async fn get_transaction_by_ledger_index(
     State(state): State<Arc<AppState>>,
     Path(ledger_index): Path<i64>,
-) -> anyhow::Result<Json<Vec<Transaction>>, StatusCode> {
-    match get_transactions(&state.scylla_session, "ledger_index", (ledger_index, )).await {
-        Ok(transactions) => Ok(Json(transactions)),
+    Extension(budget): Extension<ScanBudget>,
+    format: OutputFormat,
+) -> anyhow::Result<Formatted<Transaction>, StatusCode> {
+    let transactions = get_ledger_transactions(&state.scylla_session, ledger_index, &budget);
+    let transactions = match transactions.await {
+        Ok(transactions) if transactions.is_empty() => Err(DataApiError::NoDataReturned),
+        result => result,
+    };
+    match transactions {
+        Ok(transactions) => Ok(Formatted::new(format, transactions)),
         Err(err) => {
             eprintln!("{}", err);
             Err(map_error_to_status_code(&err))
@@ -49,18 +127,14 @@ pub async fn get_transaction_by_ledger_index(
     }
 }
 
-async fn get_transactions(
-    session: &Session,
-    field: &str,
-    values: impl SerializeRow,
-) -> Result<Vec<Transaction>, DataApiError> {
+fn transactions_query(field: &str, filter: &str) -> String {
     let table = if field == "account" {
         TRANSACTIONS_ACCOUNT_MV_TABLE
     } else {
         TRANSACTIONS_TABLE
     };
     // ! NOTE: select column order is important. Must match the order of the struct fields
-    let query = format!(
+    format!(
         "SELECT account, \
             hash, \
             ctid, \
@@ -74,10 +148,17 @@ async fn get_transactions(
             result, \
             meta, \
             tx \
-        from {} WHERE {}=?;",
-        table, field
-    );
-    let mut query: Query = Query::new(query);
+        from {} WHERE {};",
+        table, filter
+    )
+}
+
+pub async fn get_transactions(
+    session: &Session,
+    field: &str,
+    values: impl SerializeRow,
+) -> Result<Vec<Transaction>, DataApiError> {
+    let mut query: Query = Query::new(transactions_query(field, &format!("{}=?", field)));
     query.set_page_size(100);
 
     println!("Query: {}", query.contents);
@@ -96,3 +177,138 @@ async fn get_transactions(
 
     Ok(transactions)
 }
+
+async fn stream_transactions(
+    session: &Session,
+    field: &str,
+    values: impl SerializeRow,
+    limit: Option<usize>,
+    format: OutputFormat,
+    budget: ScanBudget,
+) -> Result<Response, DataApiError> {
+    let rows = transaction_rows(session, field, values).await?;
+    Ok(stream_rows(rows, format, limit, budget))
+}
+
+/// Lazily paged transactions, read one page at a time as they are consumed.
+pub async fn transaction_rows(
+    session: &Session,
+    field: &str,
+    values: impl SerializeRow,
+) -> Result<TypedRowIterator<Transaction>, DataApiError> {
+    let mut query: Query = Query::new(transactions_query(field, &format!("{}=?", field)));
+    query.set_page_size(STREAM_PAGE_SIZE);
+
+    println!("Streaming query: {}", query.contents);
+    Ok(session
+        .query_iter(query, values)
+        .await?
+        .into_typed::<Transaction>())
+}
+
+/// All transactions of a single ledger, across all pages.
+pub async fn get_ledger_transactions(
+    session: &Session,
+    ledger_index: i64,
+    budget: &ScanBudget,
+) -> Result<Vec<Transaction>, DataApiError> {
+    let mut query: Query = Query::new(transactions_query("ledger_index", "ledger_index=?"));
+    query.set_page_size(STREAM_PAGE_SIZE);
+
+    println!("Query: {}", query.contents);
+    collect_rows(session, query, (ledger_index,), budget).await
+}
+
+/// Transactions in ledgers `after_ledger` (exclusive) to `up_to_ledger` (inclusive), across all
+/// pages.
+pub async fn get_transactions_in_ledger_range(
+    session: &Session,
+    field: &str,
+    value: &str,
+    after_ledger: i64,
+    up_to_ledger: i64,
+    budget: &ScanBudget,
+) -> Result<Vec<Transaction>, DataApiError> {
+    let filter = format!("{}=? AND ledger_index>? AND ledger_index<=?", field);
+    let mut query: Query = Query::new(transactions_query(field, &filter));
+    query.set_page_size(STREAM_PAGE_SIZE);
+
+    println!("Query: {}", query.contents);
+    collect_rows(session, query, (value, after_ledger, up_to_ledger), budget).await
+}
+
+/// A page of an account's transactions closed between the `start` and `end` of `params`, newest
+/// first, continuing after the position in its `marker`.
+async fn get_account_transactions(
+    session: &Session,
+    account: &str,
+    params: &DataApiQueryParams,
+    limit: i32,
+    budget: &ScanBudget,
+) -> Result<Paginated<Transaction>, DataApiError> {
+    let after = decode_position(params.marker.as_deref())?;
+    let bounds = get_ledger_index_bounds(session, params.start, params.end).await?;
+    let transactions =
+        get_account_transactions_page(session, account, bounds, after, false, limit, budget).await?;
+    println!("Returning {} transactions", transactions.len());
+
+    // A full page may be followed by more
+    let marker = match transactions.last() {
+        Some(transaction) if transactions.len() == limit.max(0) as usize => Some(encode_position(
+            &(transaction.ledger_index, transaction.tx_index.clone()),
+        )),
+        _ => None,
+    };
+    Ok(Paginated {
+        items: transactions,
+        marker,
+    })
+}
+
+/// A page of an account's transactions within ledgers `min_ledger` to `max_ledger` (inclusive),
+/// newest first unless `forward`. `after` is the (ledger index, transaction index) of the last
+/// transaction of the previous page.
+pub async fn get_account_transactions_page(
+    session: &Session,
+    account: &str,
+    (min_ledger, max_ledger): (i64, i64),
+    after: Option<(i64, BigInt)>,
+    forward: bool,
+    limit: i32,
+    budget: &ScanBudget,
+) -> Result<Vec<Transaction>, DataApiError> {
+    let (direction, position, bound) = if forward {
+        ("ASC", ">", "<=")
+    } else {
+        ("DESC", "<", ">=")
+    };
+    let range = match after {
+        // Single and multi column restrictions can not be mixed, so the bound is a tuple too
+        Some(_) => format!(
+            "(ledger_index, tx_index){}(?, ?) AND (ledger_index){}(?)",
+            position, bound
+        ),
+        None => "ledger_index>=? AND ledger_index<=?".to_string(),
+    };
+    let filter = format!(
+        "account=? AND {} ORDER BY ledger_index {}, tx_index {} LIMIT {}",
+        range, direction, direction, limit
+    );
+    let mut query: Query = Query::new(transactions_query("account", &filter));
+    query.set_page_size(limit);
+
+    println!("Query: {}", query.contents);
+    match after {
+        Some((ledger_index, tx_index)) => {
+            let last_ledger = if forward { max_ledger } else { min_ledger };
+            collect_rows(
+                session,
+                query,
+                (account, ledger_index, tx_index, last_ledger),
+                budget,
+            )
+            .await
+        }
+        None => collect_rows(session, query, (account, min_ledger, max_ledger), budget).await,
+    }
+}
@@ -0,0 +1,644 @@
+use crate::models::exchange::Currency;
+use crate::models::webhook::{
+    DeliveryStatus, Webhook, WebhookDelivery, WebhookDeliveryAttempt, WebhookDeliveryDetails,
+    WebhookRequest,
+};
+use crate::utils::auth::Client;
+use crate::utils::consts::{
+    DEFAULT_RESULT_LIMIT, STREAM_PAGE_SIZE, WEBHOOKS_TABLE, WEBHOOK_DELETE_MAX_SCANNED_ROWS,
+    WEBHOOK_DELIVERIES_TABLE, WEBHOOK_DELIVERY_ATTEMPTS_TABLE, WEBHOOK_MAX_ACCOUNTS,
+    WEBHOOK_PENDING_DELIVERIES_TABLE,
+};
+use crate::utils::errors::{map_error_to_status_code, DataApiError};
+use crate::utils::format::{Formatted, OutputFormat};
+use crate::utils::pagination::{decode_marker, encode_marker, Paginated};
+use crate::utils::params::DataApiQueryParams;
+use crate::utils::rows::{collect_rows, next_row, ScanBudget};
+use crate::workers::webhook::queue_delivery;
+use crate::AppState;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use chrono::Utc;
+use scylla::Session;
+use std::sync::Arc;
+use uuid::Uuid;
+
+// ! NOTE: select column order is important
+static WEBHOOK_COLUMNS: &str =
+    "id, url, secret, accounts, currency, destination_tag, created_at, key_id";
+static DELIVERY_COLUMNS: &str = "webhook_id, \
+    delivery_id, \
+    tx_hash, \
+    payload, \
+    status, \
+    attempts, \
+    last_status_code, \
+    last_error, \
+    created_at, \
+    updated_at";
+
+#[utoipa::path(
+    post,
+    path = "/webhooks",
+    tag = "webhooks",
+    request_body = WebhookRequest,
+    responses(
+        (status = 201, description = "The registered webhook", body = Webhook),
+        (status = 400, description = "Invalid url, accounts or currency"),
+    ),
+)]
+pub async fn create_webhook_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(client): Extension<Client>,
+    Json(request): Json<WebhookRequest>,
+) -> Result<(StatusCode, Json<Webhook>), StatusCode> {
+    let webhook = match validate_webhook_request(&request) {
+        Ok(()) => {
+            let webhook = Webhook {
+                id: Uuid::now_v7(),
+                url: request.url,
+                secret: request.secret,
+                accounts: request.accounts,
+                currency: request.currency,
+                destination_tag: request.destination_tag,
+                created_at: Utc::now(),
+                key_id: client.key_id,
+            };
+            save_webhook(&state.scylla_session, &webhook)
+                .await
+                .map(|_| webhook)
+        }
+        Err(err) => Err(err),
+    };
+    match webhook {
+        Ok(webhook) => Ok((StatusCode::CREATED, Json(webhook))),
+        Err(err) => {
+            eprintln!("{}", err);
+            Err(map_error_to_status_code(&err))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks",
+    tag = "webhooks",
+    responses((status = 200, description = "Webhooks registered with the caller's key", body = [Webhook])),
+)]
+pub async fn get_webhooks_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(client): Extension<Client>,
+    Extension(budget): Extension<ScanBudget>,
+) -> Result<Json<Vec<Webhook>>, StatusCode> {
+    match get_webhooks(&state.scylla_session, &budget).await {
+        Ok(webhooks) => Ok(Json(
+            webhooks
+                .into_iter()
+                .filter(|webhook| webhook.key_id == client.key_id)
+                .collect(),
+        )),
+        Err(err) => {
+            eprintln!("{}", err);
+            Err(map_error_to_status_code(&err))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "Webhook id")),
+    responses(
+        (status = 200, description = "The webhook", body = Webhook),
+        (status = 404, description = "Webhook not found"),
+    ),
+)]
+pub async fn get_webhook_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(client): Extension<Client>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Webhook>, StatusCode> {
+    match get_owned_webhook(&state.scylla_session, id, client.key_id).await {
+        Ok(webhook) => Ok(Json(webhook)),
+        Err(err) => {
+            eprintln!("{}", err);
+            Err(map_error_to_status_code(&err))
+        }
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "Webhook id")),
+    request_body = WebhookRequest,
+    responses(
+        (status = 200, description = "The updated webhook", body = Webhook),
+        (status = 404, description = "Webhook not found"),
+    ),
+)]
+pub async fn update_webhook_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(client): Extension<Client>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<WebhookRequest>,
+) -> Result<Json<Webhook>, StatusCode> {
+    match update_webhook(&state.scylla_session, id, client.key_id, request).await {
+        Ok(webhook) => Ok(Json(webhook)),
+        Err(err) => {
+            eprintln!("{}", err);
+            Err(map_error_to_status_code(&err))
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "Webhook id")),
+    responses(
+        (status = 204, description = "Webhook deleted"),
+        (status = 404, description = "Webhook not found"),
+    ),
+)]
+pub async fn delete_webhook_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(client): Extension<Client>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    match delete_webhook(&state.scylla_session, id, client.key_id).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(err) => {
+            eprintln!("{}", err);
+            Err(map_error_to_status_code(&err))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks/{id}/deliveries",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "Webhook id"), DataApiQueryParams, ("format" = Option<OutputFormat>, Query, description = "Output format, overrides the `Accept` header")),
+    responses(
+        (status = 200, description = "Deliveries of the webhook, the next page marker is in `x-next-marker`", body = [WebhookDelivery]),
+        (status = 400, description = "Invalid limit or marker"),
+        (status = 404, description = "Webhook not found"),
+    ),
+)]
+pub async fn get_webhook_deliveries_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(client): Extension<Client>,
+    Path(id): Path<Uuid>,
+    params: axum::extract::Query<DataApiQueryParams>,
+    format: OutputFormat,
+) -> Result<Formatted<WebhookDelivery>, StatusCode> {
+    let limit = params.limit.unwrap_or(DEFAULT_RESULT_LIMIT);
+    let session = &state.scylla_session;
+
+    let deliveries = match decode_marker(params.marker.as_deref()) {
+        Ok(paging_state) => match get_owned_webhook(session, id, client.key_id).await {
+            Ok(_) => get_webhook_deliveries(session, id, limit, paging_state).await,
+            Err(err) => Err(err),
+        },
+        Err(err) => Err(err),
+    };
+    match deliveries {
+        Ok(deliveries) => Ok(Formatted::page(format, deliveries)),
+        Err(err) => {
+            eprintln!("{}", err);
+            Err(map_error_to_status_code(&err))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks/{id}/deliveries/{delivery_id}",
+    tag = "webhooks",
+    params(
+        ("id" = Uuid, Path, description = "Webhook id"),
+        ("delivery_id" = Uuid, Path, description = "Delivery id"),
+    ),
+    responses(
+        (status = 200, description = "The delivery and its attempts", body = WebhookDeliveryDetails),
+        (status = 404, description = "Delivery not found"),
+    ),
+)]
+pub async fn get_webhook_delivery_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(client): Extension<Client>,
+    Path((id, delivery_id)): Path<(Uuid, Uuid)>,
+    Extension(budget): Extension<ScanBudget>,
+) -> Result<Json<WebhookDeliveryDetails>, StatusCode> {
+    let session = &state.scylla_session;
+    let delivery = match get_owned_webhook(session, id, client.key_id).await {
+        Ok(_) => get_webhook_delivery(session, id, delivery_id).await,
+        Err(err) => Err(err),
+    };
+    let details = match delivery {
+        Ok(delivery) => get_delivery_attempts(session, delivery_id, &budget)
+            .await
+            .map(|attempts_log| WebhookDeliveryDetails {
+                delivery,
+                attempts_log,
+            }),
+        Err(err) => Err(err),
+    };
+    match details {
+        Ok(details) => Ok(Json(details)),
+        Err(err) => {
+            eprintln!("{}", err);
+            Err(map_error_to_status_code(&err))
+        }
+    }
+}
+
+/// Queues a delivery again with a fresh set of retries, unless it is still being attempted.
+#[utoipa::path(
+    post,
+    path = "/webhooks/{id}/deliveries/{delivery_id}/redeliver",
+    tag = "webhooks",
+    params(
+        ("id" = Uuid, Path, description = "Webhook id"),
+        ("delivery_id" = Uuid, Path, description = "Delivery id"),
+    ),
+    responses(
+        (status = 202, description = "Delivery scheduled again", body = WebhookDelivery),
+        (status = 404, description = "Delivery not found"),
+        (status = 409, description = "Delivery is still pending"),
+    ),
+)]
+pub async fn redeliver_webhook_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(client): Extension<Client>,
+    Path((id, delivery_id)): Path<(Uuid, Uuid)>,
+) -> Result<(StatusCode, Json<WebhookDelivery>), StatusCode> {
+    let session = &state.scylla_session;
+    let delivery = match get_owned_webhook(session, id, client.key_id).await {
+        Ok(_) => get_webhook_delivery(session, id, delivery_id).await,
+        Err(err) => Err(err),
+    };
+    let delivery = match delivery {
+        // Its retries are still running, a second set would deliver it twice
+        Ok(delivery) if delivery.status == DeliveryStatus::Pending.as_str() => Err(
+            DataApiError::Conflict(format!("delivery {} is still pending", delivery_id)),
+        ),
+        Ok(mut delivery) => {
+            delivery.status = DeliveryStatus::Pending.as_str().to_string();
+            delivery.updated_at = Utc::now();
+            queue_delivery(session, &state.webhook_client, delivery.clone())
+                .await
+                .map(|_| delivery)
+        }
+        Err(err) => Err(err),
+    };
+    match delivery {
+        Ok(delivery) => Ok((StatusCode::ACCEPTED, Json(delivery))),
+        Err(err) => {
+            eprintln!("{}", err);
+            Err(map_error_to_status_code(&err))
+        }
+    }
+}
+
+fn validate_webhook_request(request: &WebhookRequest) -> Result<(), DataApiError> {
+    match reqwest::Url::parse(&request.url) {
+        Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {}
+        _ => {
+            return Err(DataApiError::InvalidParameter(format!(
+                "invalid webhook url {}",
+                request.url
+            )))
+        }
+    }
+    if request.secret.is_empty() {
+        return Err(DataApiError::InvalidParameter(
+            "webhook secret is empty".to_string(),
+        ));
+    }
+    if request.accounts.is_empty() || request.accounts.len() > WEBHOOK_MAX_ACCOUNTS {
+        return Err(DataApiError::InvalidParameter(format!(
+            "webhooks need between 1 and {} accounts",
+            WEBHOOK_MAX_ACCOUNTS
+        )));
+    }
+    if let Some(currency) = &request.currency {
+        currency.parse::<Currency>()?;
+    }
+    Ok(())
+}
+
+async fn update_webhook(
+    session: &Session,
+    id: Uuid,
+    key_id: Option<Uuid>,
+    request: WebhookRequest,
+) -> Result<Webhook, DataApiError> {
+    validate_webhook_request(&request)?;
+    let existing = get_owned_webhook(session, id, key_id).await?;
+    let webhook = Webhook {
+        id,
+        url: request.url,
+        secret: request.secret,
+        accounts: request.accounts,
+        currency: request.currency,
+        destination_tag: request.destination_tag,
+        created_at: existing.created_at,
+        key_id: existing.key_id,
+    };
+    save_webhook(session, &webhook).await?;
+
+    Ok(webhook)
+}
+
+async fn save_webhook(session: &Session, webhook: &Webhook) -> Result<(), DataApiError> {
+    let query = format!(
+        "INSERT INTO {} ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?);",
+        WEBHOOKS_TABLE, WEBHOOK_COLUMNS
+    );
+    println!("Query: {}", query);
+    session
+        .query(
+            query,
+            (
+                webhook.id,
+                &webhook.url,
+                &webhook.secret,
+                &webhook.accounts,
+                &webhook.currency,
+                webhook.destination_tag,
+                webhook.created_at,
+                webhook.key_id,
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Deletes a webhook together with its delivery history. Retries still in flight stop
+/// before their next attempt. The webhook itself is deleted last, so a deletion that failed
+/// part way can be retried.
+async fn delete_webhook(
+    session: &Session,
+    id: Uuid,
+    key_id: Option<Uuid>,
+) -> Result<(), DataApiError> {
+    get_owned_webhook(session, id, key_id).await?;
+
+    // Attempts are partitioned by delivery, so the deliveries are read to find them
+    let query = format!(
+        "SELECT delivery_id from {} WHERE webhook_id=?;",
+        WEBHOOK_DELIVERIES_TABLE
+    );
+    let mut query = scylla::query::Query::new(query);
+    query.set_page_size(STREAM_PAGE_SIZE);
+
+    println!("Query: {}", query.contents);
+    let budget = ScanBudget::new(WEBHOOK_DELETE_MAX_SCANNED_ROWS);
+    let mut rows = session
+        .query_iter(query, (id,))
+        .await?
+        .into_typed::<(Uuid,)>();
+    let query = format!(
+        "DELETE FROM {} WHERE delivery_id=?;",
+        WEBHOOK_DELIVERY_ATTEMPTS_TABLE
+    );
+    println!("Query: {}", query);
+    while let Some((delivery_id,)) = next_row(&mut rows, &budget).await? {
+        session.query(query.as_str(), (delivery_id,)).await?;
+    }
+
+    for table in [WEBHOOK_PENDING_DELIVERIES_TABLE, WEBHOOK_DELIVERIES_TABLE] {
+        let query = format!("DELETE FROM {} WHERE webhook_id=?;", table);
+        println!("Query: {}", query);
+        session.query(query, (id,)).await?;
+    }
+
+    let query = format!("DELETE FROM {} WHERE id=?;", WEBHOOKS_TABLE);
+    println!("Query: {}", query);
+    session.query(query, (id,)).await?;
+
+    Ok(())
+}
+
+/// Every registered webhook. The registry is small enough to be read in full for each ledger.
+pub async fn get_webhooks(
+    session: &Session,
+    budget: &ScanBudget,
+) -> Result<Vec<Webhook>, DataApiError> {
+    let query = format!("SELECT {} from {};", WEBHOOK_COLUMNS, WEBHOOKS_TABLE);
+    println!("Query: {}", query);
+    collect_rows(session, scylla::query::Query::new(query), (), budget).await
+}
+
+pub async fn get_webhook(session: &Session, id: Uuid) -> Result<Webhook, DataApiError> {
+    let query = format!(
+        "SELECT {} from {} WHERE id=?;",
+        WEBHOOK_COLUMNS, WEBHOOKS_TABLE
+    );
+    println!("Query: {}", query);
+    let query_result = session.query(query, (id,)).await?;
+
+    // todo: better row error handling
+    query_result
+        .rows_typed_or_empty::<Webhook>()
+        .filter_map(|row| row.ok())
+        .next()
+        .ok_or(DataApiError::NoDataReturned)
+}
+
+/// A webhook registered with the key `key_id`. Webhooks of other keys are not found, rather
+/// than forbidden, so their ids are not confirmed to exist.
+async fn get_owned_webhook(
+    session: &Session,
+    id: Uuid,
+    key_id: Option<Uuid>,
+) -> Result<Webhook, DataApiError> {
+    let webhook = get_webhook(session, id).await?;
+    if webhook.key_id != key_id {
+        return Err(DataApiError::NoDataReturned);
+    }
+    Ok(webhook)
+}
+
+async fn get_webhook_deliveries(
+    session: &Session,
+    id: Uuid,
+    limit: i32,
+    paging_state: Option<bytes::Bytes>,
+) -> Result<Paginated<WebhookDelivery>, DataApiError> {
+    let query = format!(
+        "SELECT {} from {} WHERE webhook_id=?;",
+        DELIVERY_COLUMNS, WEBHOOK_DELIVERIES_TABLE
+    );
+    let mut query = scylla::query::Query::new(query);
+    query.set_page_size(limit);
+
+    println!("Query: {}", query.contents);
+    let query_result = session.query_paged(query, (id,), paging_state).await?;
+    let marker = encode_marker(query_result.paging_state.clone());
+
+    // todo: better row error handling
+    let deliveries = query_result
+        .rows_typed_or_empty::<WebhookDelivery>()
+        .filter_map(|row| row.ok())
+        .collect::<Vec<WebhookDelivery>>();
+
+    if deliveries.is_empty() {
+        return Err(DataApiError::NoDataReturned);
+    }
+    println!("Returning {} webhook deliveries", deliveries.len());
+
+    Ok(Paginated {
+        items: deliveries,
+        marker,
+    })
+}
+
+pub async fn get_webhook_delivery(
+    session: &Session,
+    id: Uuid,
+    delivery_id: Uuid,
+) -> Result<WebhookDelivery, DataApiError> {
+    let query = format!(
+        "SELECT {} from {} WHERE webhook_id=? AND delivery_id=?;",
+        DELIVERY_COLUMNS, WEBHOOK_DELIVERIES_TABLE
+    );
+    println!("Query: {}", query);
+    let query_result = session.query(query, (id, delivery_id)).await?;
+
+    // todo: better row error handling
+    query_result
+        .rows_typed_or_empty::<WebhookDelivery>()
+        .filter_map(|row| row.ok())
+        .next()
+        .ok_or(DataApiError::NoDataReturned)
+}
+
+pub async fn save_delivery(
+    session: &Session,
+    delivery: &WebhookDelivery,
+) -> Result<(), DataApiError> {
+    let query = format!(
+        "INSERT INTO {} ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?);",
+        WEBHOOK_DELIVERIES_TABLE, DELIVERY_COLUMNS
+    );
+    println!("Query: {}", query);
+    session
+        .query(
+            query,
+            (
+                delivery.webhook_id,
+                delivery.delivery_id,
+                &delivery.tx_hash,
+                &delivery.payload,
+                &delivery.status,
+                delivery.attempts,
+                delivery.last_status_code,
+                &delivery.last_error,
+                delivery.created_at,
+                delivery.updated_at,
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn get_delivery_attempts(
+    session: &Session,
+    delivery_id: Uuid,
+    budget: &ScanBudget,
+) -> Result<Vec<WebhookDeliveryAttempt>, DataApiError> {
+    let query = format!(
+        "SELECT delivery_id, attempt, attempted_at, status_code, error, duration_ms \
+        from {} WHERE delivery_id=?;",
+        WEBHOOK_DELIVERY_ATTEMPTS_TABLE
+    );
+    println!("Query: {}", query);
+    let query = scylla::query::Query::new(query);
+    collect_rows(session, query, (delivery_id,), budget).await
+}
+
+pub async fn record_delivery_attempt(
+    session: &Session,
+    attempt: &WebhookDeliveryAttempt,
+) -> Result<(), DataApiError> {
+    let query = format!(
+        "INSERT INTO {} (delivery_id, attempt, attempted_at, status_code, error, duration_ms) \
+        VALUES (?, ?, ?, ?, ?, ?);",
+        WEBHOOK_DELIVERY_ATTEMPTS_TABLE
+    );
+    println!("Query: {}", query);
+    session
+        .query(
+            query,
+            (
+                attempt.delivery_id,
+                attempt.attempt,
+                attempt.attempted_at,
+                attempt.status_code,
+                &attempt.error,
+                attempt.duration_ms,
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Marks a delivery as being attempted up to `last_attempt`, so its retries resume after a
+/// restart.
+pub async fn save_pending_delivery(
+    session: &Session,
+    delivery: &WebhookDelivery,
+    last_attempt: i32,
+) -> Result<(), DataApiError> {
+    let query = format!(
+        "INSERT INTO {} (webhook_id, delivery_id, last_attempt) VALUES (?, ?, ?);",
+        WEBHOOK_PENDING_DELIVERIES_TABLE
+    );
+    println!("Query: {}", query);
+    session
+        .query(
+            query,
+            (delivery.webhook_id, delivery.delivery_id, last_attempt),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn delete_pending_delivery(
+    session: &Session,
+    webhook_id: Uuid,
+    delivery_id: Uuid,
+) -> Result<(), DataApiError> {
+    let query = format!(
+        "DELETE FROM {} WHERE webhook_id=? AND delivery_id=?;",
+        WEBHOOK_PENDING_DELIVERIES_TABLE
+    );
+    println!("Query: {}", query);
+    session.query(query, (webhook_id, delivery_id)).await?;
+
+    Ok(())
+}
+
+/// Every delivery still being attempted, as webhook id, delivery id and last attempt.
+pub async fn get_pending_deliveries(
+    session: &Session,
+    budget: &ScanBudget,
+) -> Result<Vec<(Uuid, Uuid, i32)>, DataApiError> {
+    let query = format!(
+        "SELECT webhook_id, delivery_id, last_attempt from {};",
+        WEBHOOK_PENDING_DELIVERIES_TABLE
+    );
+    println!("Query: {}", query);
+    collect_rows(session, scylla::query::Query::new(query), (), budget).await
+}
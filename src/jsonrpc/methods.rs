@@ -0,0 +1,348 @@
+use crate::handlers::account::get_account;
+use crate::handlers::daily_ledger::get_last_closed_ledger;
+use crate::handlers::ledger::{get_ledger, LedgerIdentifier};
+use crate::handlers::transaction::{
+    get_account_transactions_page, get_ledger_transactions, get_transactions,
+};
+use crate::jsonrpc::RpcError;
+use crate::models::ledger::Ledger;
+use crate::models::transaction::Transaction;
+use crate::utils::auth::Client;
+use crate::utils::consts::{JSON_RPC_DEFAULT_LIMIT, RIPPLE_EPOCH_OFFSET};
+use crate::utils::errors::DataApiError;
+use crate::utils::rows::ScanBudget;
+use crate::AppState;
+use chrono::{DateTime, Duration, Utc};
+use num_bigint::BigInt;
+use scylla::Session;
+use serde_json::{json, Map, Value};
+
+type Params = Map<String, Value>;
+
+/// A transaction by hash, in the shape of API version 1.
+pub async fn tx(state: &AppState, params: &Params) -> Result<Value, RpcError> {
+    reject_binary(params)?;
+    let hash = params
+        .get("transaction")
+        .and_then(Value::as_str)
+        .ok_or(RpcError::InvalidParams)?;
+
+    let transactions = get_transactions(&state.scylla_session, "hash", (hash,)).await;
+    let transaction = transactions
+        .and_then(|mut transactions| transactions.pop().ok_or(DataApiError::NoDataReturned))
+        .map_err(|err| RpcError::from_data_api_error(err, RpcError::TxnNotFound))?;
+
+    let meta = transaction.meta_json();
+    let mut result = transaction_json(&transaction);
+    result.insert("meta".to_string(), meta);
+    result.insert("validated".to_string(), json!(true));
+    Ok(Value::Object(result))
+}
+
+/// Transactions of an account. Markers are `{"ledger", "seq"}` of the last transaction returned,
+/// like rippled's.
+pub async fn account_tx(
+    state: &AppState,
+    client: &Client,
+    budget: &ScanBudget,
+    params: &Params,
+) -> Result<Value, RpcError> {
+    reject_binary(params)?;
+    let session = &state.scylla_session;
+    let account = params
+        .get("account")
+        .and_then(Value::as_str)
+        .ok_or(RpcError::ActMalformed)?;
+    let forward = params
+        .get("forward")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let limit = match params.get("limit") {
+        Some(limit) => limit
+            .as_i64()
+            .filter(|limit| *limit > 0)
+            .ok_or(RpcError::InvalidParams)?,
+        None => JSON_RPC_DEFAULT_LIMIT.into(),
+    };
+    let limit = limit.min(client.tier.max_limit.into()) as i32;
+    let marker = match params.get("marker") {
+        Some(marker) => Some(parse_marker(marker)?),
+        None => None,
+    };
+
+    // A single ledger takes precedence over a range, as in rippled
+    let (min_ledger, max_ledger) =
+        if params.contains_key("ledger_index") || params.contains_key("ledger_hash") {
+            let ledger = find_ledger(session, params).await?;
+            (ledger.ledger_index, ledger.ledger_index)
+        } else {
+            let min_ledger = ledger_bound(params.get("ledger_index_min"))?.unwrap_or(0);
+            let max_ledger = match ledger_bound(params.get("ledger_index_max"))? {
+                Some(max_ledger) => max_ledger,
+                None => latest_ledger_index(session).await?,
+            };
+            (min_ledger, max_ledger)
+        };
+    if min_ledger > max_ledger {
+        return Err(RpcError::InvalidParams);
+    }
+
+    // One more transaction than the limit tells whether there is a next page
+    let page = get_account_transactions_page(
+        session,
+        account,
+        (min_ledger, max_ledger),
+        marker.clone(),
+        forward,
+        limit + 1,
+        budget,
+    )
+    .await;
+    let mut transactions = match page {
+        Ok(transactions) => transactions,
+        Err(err) => return Err(RpcError::from_data_api_error(err, RpcError::Internal)),
+    };
+    let next_marker = if transactions.len() > limit as usize {
+        transactions.truncate(limit as usize);
+        transactions.last().map(|last| {
+            json!({
+                "ledger": last.ledger_index,
+                "seq": last.tx_index.to_string().parse::<i64>().unwrap_or_default(),
+            })
+        })
+    } else {
+        None
+    };
+    // rippled tells unknown accounts apart from accounts without transactions
+    if transactions.is_empty() && marker.is_none() {
+        get_account(session, account)
+            .await
+            .map_err(|err| RpcError::from_data_api_error(err, RpcError::ActNotFound))?;
+    }
+
+    let transactions = transactions
+        .iter()
+        .map(|transaction| {
+            json!({
+                "meta": transaction.meta_json(),
+                "tx": transaction_json(transaction),
+                "validated": true,
+            })
+        })
+        .collect::<Vec<Value>>();
+    let mut result = json!({
+        "account": account,
+        "ledger_index_min": min_ledger,
+        "ledger_index_max": max_ledger,
+        "limit": limit,
+        "transactions": transactions,
+        "validated": true,
+    });
+    if let Some(next_marker) = next_marker {
+        result["marker"] = next_marker;
+    }
+    Ok(result)
+}
+
+/// A ledger header, with its transaction hashes or expanded transactions when asked for.
+pub async fn ledger(
+    state: &AppState,
+    budget: &ScanBudget,
+    params: &Params,
+) -> Result<Value, RpcError> {
+    reject_binary(params)?;
+    let session = &state.scylla_session;
+    let ledger = find_ledger(session, params).await?;
+    let with_transactions = params
+        .get("transactions")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let expand = params
+        .get("expand")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    let mut header = ledger_json(&ledger);
+    if with_transactions {
+        let transactions = get_ledger_transactions(session, ledger.ledger_index, budget)
+            .await
+            .map_err(|err| RpcError::from_data_api_error(err, RpcError::LgrNotFound))?;
+        let transactions = transactions
+            .iter()
+            .map(|transaction| {
+                if !expand {
+                    return json!(transaction.hash);
+                }
+                let mut expanded = transaction_json(transaction);
+                expanded.insert("metaData".to_string(), transaction.meta_json());
+                Value::Object(expanded)
+            })
+            .collect::<Vec<Value>>();
+        header.insert("transactions".to_string(), json!(transactions));
+    }
+
+    Ok(json!({
+        "ledger": header,
+        "ledger_hash": ledger.ledger_hash,
+        "ledger_index": ledger.ledger_index,
+        "validated": true,
+    }))
+}
+
+/// The latest ingested ledger.
+pub async fn ledger_closed(state: &AppState) -> Result<Value, RpcError> {
+    let session = &state.scylla_session;
+    let ledger_index = latest_ledger_index(session).await?;
+    let ledger = get_ledger(
+        session,
+        "ledger_index",
+        &LedgerIdentifier::BigInt(ledger_index),
+    )
+    .await
+    .map_err(|err| RpcError::from_data_api_error(err, RpcError::LgrNotFound))?;
+
+    Ok(json!({
+        "ledger_hash": ledger.ledger_hash,
+        "ledger_index": ledger.ledger_index,
+    }))
+}
+
+/// How an account was activated. Balances and other ledger state are not stored, so only the
+/// account itself is in `account_data` and the activation is under `activation`.
+pub async fn account_info(state: &AppState, params: &Params) -> Result<Value, RpcError> {
+    let address = params
+        .get("account")
+        .and_then(Value::as_str)
+        .ok_or(RpcError::ActMalformed)?;
+    let account = get_account(&state.scylla_session, address)
+        .await
+        .map_err(|err| RpcError::from_data_api_error(err, RpcError::ActNotFound))?;
+
+    Ok(json!({
+        "account_data": {
+            "Account": account.account,
+        },
+        "activation": account,
+        "ledger_index": account.ledger_index,
+        "validated": true,
+    }))
+}
+
+// Transactions are stored as JSON, so there are no binary blobs to return
+fn reject_binary(params: &Params) -> Result<(), RpcError> {
+    match params.get("binary").and_then(Value::as_bool) {
+        Some(true) => Err(RpcError::NotSupported),
+        _ => Ok(()),
+    }
+}
+
+/// Finds the ledger of `ledger_hash` or `ledger_index`, which is a number or one of
+/// "validated", "closed" and "current", all meaning the latest ingested ledger.
+async fn find_ledger(session: &Session, params: &Params) -> Result<Ledger, RpcError> {
+    let (field, identifier) = match (params.get("ledger_hash"), params.get("ledger_index")) {
+        (Some(Value::String(ledger_hash)), _) => {
+            ("ledger_hash", LedgerIdentifier::String(ledger_hash.clone()))
+        }
+        (Some(_), _) => return Err(RpcError::InvalidParams),
+        (None, Some(Value::Number(ledger_index))) => {
+            let ledger_index = ledger_index.as_i64().ok_or(RpcError::LgrIdxMalformed)?;
+            ("ledger_index", LedgerIdentifier::BigInt(ledger_index))
+        }
+        (None, Some(Value::String(ledger_index))) => {
+            let ledger_index = match ledger_index.as_str() {
+                "validated" | "closed" | "current" => latest_ledger_index(session).await?,
+                ledger_index => ledger_index
+                    .parse::<i64>()
+                    .map_err(|_| RpcError::LgrIdxMalformed)?,
+            };
+            ("ledger_index", LedgerIdentifier::BigInt(ledger_index))
+        }
+        (None, Some(_)) => return Err(RpcError::LgrIdxMalformed),
+        (None, None) => {
+            let ledger_index = latest_ledger_index(session).await?;
+            ("ledger_index", LedgerIdentifier::BigInt(ledger_index))
+        }
+    };
+
+    get_ledger(session, field, &identifier)
+        .await
+        .map_err(|err| RpcError::from_data_api_error(err, RpcError::LgrNotFound))
+}
+
+/// Index of the latest ingested ledger, looking back a day when none closed today yet.
+async fn latest_ledger_index(session: &Session) -> Result<i64, RpcError> {
+    let now = Utc::now();
+    let latest = match get_last_closed_ledger(session, now).await {
+        Err(DataApiError::NoDataReturned) => {
+            get_last_closed_ledger(session, now - Duration::days(1)).await
+        }
+        latest => latest,
+    };
+    latest
+        .map(|latest| latest.ledger_index)
+        .map_err(|err| RpcError::from_data_api_error(err, RpcError::LgrNotFound))
+}
+
+// -1 leaves a side of the range open
+fn ledger_bound(bound: Option<&Value>) -> Result<Option<i64>, RpcError> {
+    match bound {
+        None => Ok(None),
+        Some(bound) => match bound.as_i64() {
+            Some(-1) => Ok(None),
+            Some(bound) if bound >= 0 => Ok(Some(bound)),
+            _ => Err(RpcError::LgrIdxMalformed),
+        },
+    }
+}
+
+fn parse_marker(marker: &Value) -> Result<(i64, BigInt), RpcError> {
+    let ledger = marker.get("ledger").and_then(Value::as_i64);
+    let seq = marker.get("seq").and_then(Value::as_i64);
+    match (ledger, seq) {
+        (Some(ledger), Some(seq)) => Ok((ledger, BigInt::from(seq))),
+        _ => Err(RpcError::InvalidParams),
+    }
+}
+
+fn ripple_time(time: DateTime<Utc>) -> i64 {
+    time.timestamp() - RIPPLE_EPOCH_OFFSET
+}
+
+/// The transaction's fields plus the fields rippled adds to validated transactions.
+fn transaction_json(transaction: &Transaction) -> Map<String, Value> {
+    let mut fields = match transaction.tx_json() {
+        Value::Object(fields) => fields,
+        _ => Map::new(),
+    };
+    fields.insert("hash".to_string(), json!(transaction.hash));
+    fields.insert("ctid".to_string(), json!(transaction.ctid));
+    fields.insert(
+        "date".to_string(),
+        json!(ripple_time(transaction.timestamp)),
+    );
+    fields.insert("ledger_index".to_string(), json!(transaction.ledger_index));
+    fields.insert("inLedger".to_string(), json!(transaction.ledger_index));
+    fields
+}
+
+// API version 1 returns the index and coins as strings in the header
+fn ledger_json(ledger: &Ledger) -> Map<String, Value> {
+    let header = json!({
+        "account_hash": ledger.account_hash,
+        "close_flags": ledger.close_flags,
+        "close_time": ripple_time(ledger.close_time),
+        "close_time_human": ledger.close_time.format("%Y-%b-%d %H:%M:%S%.9f UTC").to_string(),
+        "close_time_iso": ledger.close_time.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        "closed": true,
+        "ledger_hash": ledger.ledger_hash,
+        "ledger_index": ledger.ledger_index.to_string(),
+        "parent_close_time": ripple_time(ledger.parent_close_time),
+        "parent_hash": ledger.parent_hash,
+        "total_coins": ledger.total_coins.to_string(),
+        "transaction_hash": ledger.transaction_hash,
+    });
+    match header {
+        Value::Object(header) => header,
+        _ => Map::new(),
+    }
+}
@@ -0,0 +1,167 @@
+pub mod methods;
+
+use crate::utils::auth::Client;
+use crate::utils::errors::DataApiError;
+use crate::utils::rows::ScanBudget;
+use crate::AppState;
+use scylla::transport::errors::{DbError, QueryError};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+/// rippled JSON-RPC request. Parameters are passed as an array holding a single object.
+#[derive(Debug, Deserialize)]
+pub struct RpcRequest {
+    pub method: Option<String>,
+    #[serde(default)]
+    pub params: Vec<Value>,
+    // Echoed back when set, for JSON-RPC 2.0 clients
+    pub id: Option<Value>,
+    pub jsonrpc: Option<Value>,
+    pub ripplerpc: Option<Value>,
+}
+
+/// Errors as rippled reports them, with its tokens, codes and messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RpcError {
+    TooBusy,
+    ActNotFound,
+    LgrNotFound,
+    TxnNotFound,
+    InvalidParams,
+    UnknownCommand,
+    ActMalformed,
+    LgrIdxMalformed,
+    Internal,
+    NotSupported,
+}
+
+impl RpcError {
+    pub fn token(&self) -> &'static str {
+        match self {
+            RpcError::TooBusy => "tooBusy",
+            RpcError::ActNotFound => "actNotFound",
+            RpcError::LgrNotFound => "lgrNotFound",
+            RpcError::TxnNotFound => "txnNotFound",
+            RpcError::InvalidParams => "invalidParams",
+            RpcError::UnknownCommand => "unknownCmd",
+            RpcError::ActMalformed => "actMalformed",
+            RpcError::LgrIdxMalformed => "lgrIdxMalformed",
+            RpcError::Internal => "internal",
+            RpcError::NotSupported => "notSupported",
+        }
+    }
+
+    pub fn code(&self) -> i32 {
+        match self {
+            RpcError::TooBusy => 9,
+            RpcError::ActNotFound => 19,
+            RpcError::LgrNotFound => 21,
+            RpcError::TxnNotFound => 29,
+            RpcError::InvalidParams => 31,
+            RpcError::UnknownCommand => 32,
+            RpcError::ActMalformed => 35,
+            RpcError::LgrIdxMalformed => 58,
+            RpcError::Internal => 73,
+            RpcError::NotSupported => 75,
+        }
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            RpcError::TooBusy => "The server is too busy to help you now.",
+            RpcError::ActNotFound => "Account not found.",
+            RpcError::LgrNotFound => "ledgerNotFound",
+            RpcError::TxnNotFound => "Transaction not found.",
+            RpcError::InvalidParams => "Invalid parameters.",
+            RpcError::UnknownCommand => "Unknown method.",
+            RpcError::ActMalformed => "Account malformed.",
+            RpcError::LgrIdxMalformed => "Ledger index malformed.",
+            RpcError::Internal => "Internal error.",
+            RpcError::NotSupported => "Operation not supported.",
+        }
+    }
+
+    /// Maps a query error, `not_found` being the method's error for missing data.
+    pub fn from_data_api_error(err: DataApiError, not_found: RpcError) -> RpcError {
+        match err {
+            DataApiError::NoDataReturned => not_found,
+            DataApiError::InvalidMarker(_) | DataApiError::InvalidParameter(_) => {
+                RpcError::InvalidParams
+            }
+            DataApiError::ScanLimitExceeded(_)
+            | DataApiError::QueryFailed(
+                QueryError::RequestTimeout(_)
+                | QueryError::TimeoutError
+                | QueryError::DbError(DbError::ReadTimeout { .. }, _),
+            ) => {
+                eprintln!("{}", err);
+                RpcError::TooBusy
+            }
+            err => {
+                eprintln!("{}", err);
+                RpcError::Internal
+            }
+        }
+    }
+}
+
+/// Runs a request and wraps its result in rippled's response shape. Errors are results with
+/// an `error` status, as rippled answers them with HTTP 200 too.
+pub async fn dispatch(
+    state: &AppState,
+    client: &Client,
+    budget: &ScanBudget,
+    request: RpcRequest,
+) -> Value {
+    let method = request.method.clone().unwrap_or_default();
+    let params = match request.params.first() {
+        Some(Value::Object(params)) => Ok(params.clone()),
+        Some(_) => Err(RpcError::InvalidParams),
+        None => Ok(Map::new()),
+    };
+    println!("JSON-RPC method: {}", method);
+
+    let result = match params {
+        Ok(ref params) => match method.as_str() {
+            "tx" => methods::tx(state, params).await,
+            "account_tx" => methods::account_tx(state, client, budget, params).await,
+            "ledger" => methods::ledger(state, budget, params).await,
+            "ledger_closed" => methods::ledger_closed(state).await,
+            "account_info" => methods::account_info(state, params).await,
+            _ => Err(RpcError::UnknownCommand),
+        },
+        Err(err) => Err(err),
+    };
+
+    let result = match result {
+        Ok(Value::Object(mut result)) => {
+            result.insert("status".to_string(), json!("success"));
+            Value::Object(result)
+        }
+        Ok(result) => result,
+        Err(err) => {
+            let mut echoed = params.unwrap_or_default();
+            echoed.insert("command".to_string(), json!(method));
+            json!({
+                "error": err.token(),
+                "error_code": err.code(),
+                "error_message": err.message(),
+                "request": echoed,
+                "status": "error",
+            })
+        }
+    };
+
+    let mut response = Map::new();
+    response.insert("result".to_string(), result);
+    for (name, value) in [
+        ("id", request.id),
+        ("jsonrpc", request.jsonrpc),
+        ("ripplerpc", request.ripplerpc),
+    ] {
+        if let Some(value) = value {
+            response.insert(name.to_string(), value);
+        }
+    }
+    Value::Object(response)
+}
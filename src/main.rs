@@ -1,30 +1,100 @@
+mod gaps;
+mod graphql;
+mod grpc;
 mod handlers;
+mod jsonrpc;
 mod models;
+mod openapi;
+mod routes;
+mod schema;
 mod utils;
+mod workers;
 
-use crate::utils::consts::KEYSPACE;
-use axum::{routing::get, Router};
-use scylla::{Session, SessionBuilder};
+use crate::graphql::{build_schema, GraphQlSchema};
+use crate::grpc::proto::data_api_server::DataApiServer;
+use crate::grpc::DataApiService;
+use crate::handlers::candle::CandleCacheKey;
+use crate::models::account::AccountSummary;
+use crate::models::api_key::ApiKey;
+use crate::models::daily_stats::DailyStats;
+use crate::models::exchange::Candle;
+use crate::models::ledger::Ledger;
+use crate::openapi::ApiDoc;
+use crate::utils::auth::{authenticate, UsageTracker};
+use crate::utils::cache::ResponseCache;
+use crate::utils::consts::{
+    ACCOUNT_SUMMARY_CACHE_CAPACITY, ACCOUNT_SUMMARY_CACHE_TTL_SECS, ADMIN_KEY_ENV,
+    API_KEY_CACHE_CAPACITY, API_KEY_CACHE_TTL_SECS, CANDLE_CACHE_CAPACITY, CANDLE_CACHE_TTL_SECS,
+    DAILY_STATS_CACHE_CAPACITY, DAILY_STATS_CACHE_TTL_SECS, KEYSPACE, KEYSPACE_ENV, LEDGER_FEED_CLIENT_BUFFER, QUERY_TIMEOUT_SECS, SCYLLA_NODES,
+    WEBHOOK_REQUEST_TIMEOUT_SECS,
+};
+use crate::utils::guardrails::enforce_limits;
+use crate::utils::metrics::Metrics;
+use crate::utils::rate_limit::RateLimiter;
+use crate::utils::version::{add_deprecation_headers, deprecations_from_env, ApiVersion};
+use axum::{middleware, Router};
+use chrono::NaiveDate;
+use moka::future::Cache;
+use scylla::{ExecutionProfile, Session, SessionBuilder};
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+use uuid::Uuid;
 
 struct AppState {
     scylla_session: Arc<Session>,
+    account_summary_cache: Cache<String, Arc<AccountSummary>>,
+    candle_cache: Cache<CandleCacheKey, Option<Candle>>,
+    daily_stats_cache: Cache<NaiveDate, Arc<DailyStats>>,
+    response_cache: ResponseCache,
+    ledger_feed: broadcast::Sender<Arc<Ledger>>,
+    webhook_client: reqwest::Client,
+    api_key_cache: Cache<Uuid, Option<Arc<ApiKey>>>,
+    usage_tracker: Arc<UsageTracker>,
+    rate_limiter: Arc<RateLimiter>,
+    admin_key: Option<String>,
+    graphql_schema: GraphQlSchema,
+    metrics: Arc<Metrics>,
 }
 
 #[tokio::main]
 async fn main() {
+    let keyspace = std::env::var(KEYSPACE_ENV).unwrap_or_else(|_| KEYSPACE.to_string());
+    if !schema::is_valid_keyspace(&keyspace) {
+        eprintln!("Invalid keyspace {} in {}", keyspace, KEYSPACE_ENV);
+        std::process::exit(1);
+    }
+
     println!("Connecting to scylla.");
 
+    let execution_profile = ExecutionProfile::builder()
+        .request_timeout(Some(Duration::from_secs(QUERY_TIMEOUT_SECS)))
+        .build();
     let session: Session = SessionBuilder::new()
-        .known_node("172.27.0.2")
-        .known_node("172.27.0.3")
-        .known_node("172.27.0.4")
+        .known_nodes(SCYLLA_NODES)
+        .default_execution_profile_handle(execution_profile.into_handle())
         .build()
         .await
         .expect("Failed to connect to scylla nodes");
 
+    // Queries rely on the tables and views of the expected schema, newer schemas are compatible
+    let schema_version = schema::applied_version(&session, &keyspace)
+        .await
+        .expect("Unable to read the schema version");
+    if schema_version < schema::expected_version() {
+        eprintln!(
+            "Schema is at version {} but version {} is required, run the migrate binary",
+            schema_version,
+            schema::expected_version()
+        );
+        std::process::exit(1);
+    }
+
     session
-        .use_keyspace(KEYSPACE, true)
+        .use_keyspace(&keyspace, true)
         .await
         .expect("Unable to use keyspace");
 
@@ -33,57 +103,119 @@ async fn main() {
 
     let arc_session = Arc::new(session);
 
+    let account_summary_cache = Cache::builder()
+        .max_capacity(ACCOUNT_SUMMARY_CACHE_CAPACITY)
+        .time_to_live(Duration::from_secs(ACCOUNT_SUMMARY_CACHE_TTL_SECS))
+        .build();
+    let candle_cache = Cache::builder()
+        .max_capacity(CANDLE_CACHE_CAPACITY)
+        .time_to_live(Duration::from_secs(CANDLE_CACHE_TTL_SECS))
+        .build();
+    let daily_stats_cache = Cache::builder()
+        .max_capacity(DAILY_STATS_CACHE_CAPACITY)
+        .time_to_live(Duration::from_secs(DAILY_STATS_CACHE_TTL_SECS))
+        .build();
+    tokio::spawn(workers::daily_stats::run_daily_stats(
+        arc_session.clone(),
+        daily_stats_cache.clone(),
+    ));
+    let response_cache = ResponseCache::new();
+    let (ledger_feed, _) = broadcast::channel(LEDGER_FEED_CLIENT_BUFFER);
+    tokio::spawn(workers::ledger_feed::run_ledger_feed(
+        arc_session.clone(),
+        ledger_feed.clone(),
+    ));
+    let webhook_client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(WEBHOOK_REQUEST_TIMEOUT_SECS))
+        .build()
+        .expect("Failed to build webhook client");
+    tokio::spawn(workers::webhook::run_webhook_dispatcher(
+        arc_session.clone(),
+        webhook_client.clone(),
+        ledger_feed.subscribe(),
+    ));
+
+    let api_key_cache = Cache::builder()
+        .max_capacity(API_KEY_CACHE_CAPACITY)
+        .time_to_live(Duration::from_secs(API_KEY_CACHE_TTL_SECS))
+        .build();
+    let usage_tracker = Arc::new(UsageTracker::default());
+    tokio::spawn(workers::usage::run_usage_flush(
+        arc_session.clone(),
+        usage_tracker.clone(),
+    ));
+    let admin_key = std::env::var(ADMIN_KEY_ENV)
+        .ok()
+        .filter(|key| !key.is_empty());
+    let metrics = Arc::new(Metrics::default());
+    tokio::spawn(workers::gaps::run_gap_scan(
+        arc_session.clone(),
+        metrics.clone(),
+    ));
+
     let shared_state = Arc::new(AppState {
         scylla_session: arc_session,
+        account_summary_cache,
+        candle_cache,
+        daily_stats_cache,
+        response_cache,
+        ledger_feed,
+        webhook_client,
+        api_key_cache,
+        usage_tracker,
+        rate_limiter: Arc::new(RateLimiter::default()),
+        admin_key,
+        graphql_schema: build_schema(),
+        metrics,
+    });
+
+    // gRPC shares the state with the router and is served on its own port
+    let grpc_service = DataApiServer::new(DataApiService::new(shared_state.clone()));
+    tokio::spawn(async move {
+        let address = "0.0.0.0:50051".parse().unwrap();
+        println!("gRPC listening on port 50051");
+        if let Err(err) = tonic::transport::Server::builder()
+            .add_service(grpc_service)
+            .serve(address)
+            .await
+        {
+            eprintln!("gRPC server failed: {}", err);
+        }
     });
 
-    // todo: sql injections for strings
-    let app = Router::new()
-        // Ledger handlers
-        .route(
-            "/ledger/:ledger_identifier",
-            get(handlers::ledger::get_ledger_handler),
-        )
-        .route(
-            "/daily_ledgers/:close_day",
-            get(handlers::daily_ledger::get_daily_ledgers_handler),
-        )
-        .route(
-            "/closed_ledger/:close_time",
-            get(handlers::daily_ledger::get_latest_closed_ledger_handler),
-        )
-        // Transaction handlers
-        .route(
-            "/transaction/hash/:tx_hash",
-            get(handlers::transaction::get_transaction_by_hash),
-        )
-        .route(
-            "/transaction/ledger/:ledger_index",
-            get(handlers::transaction::get_transaction_by_ledger_index),
-        )
-        .route(
-            "/transaction/account/:account",
-            get(handlers::transaction::get_transaction_by_account),
-        )
-        // Account handlers
-        .route(
-            "/account/:account",
-            get(handlers::account::get_account_handler),
-        )
-        // Balance changes handlers
-        .route(
-            "/account/:account/balance_changes",
-            get(handlers::balance_change::get_account_balance_changes_handler),
-        )
-        // Payments handlers
-        .route(
-            "/account/:account/payments",
-            get(handlers::payment::get_account_payments_handler),
-        )
+    let mut app = Router::new();
+    for version in ApiVersion::ALL {
+        app = app.nest(version.prefix(), version.router());
+    }
+    let app = app
+        // Root paths are aliases of the first version
+        .merge(ApiVersion::ALL[0].router())
+        .route_layer(middleware::from_fn_with_state(
+            Arc::new(deprecations_from_env()),
+            add_deprecation_headers,
+        ))
+        // Legacy routes keep the contract of the API they reproduce and are never deprecated
+        .merge(routes::legacy_router())
+        .route_layer(middleware::from_fn_with_state(
+            shared_state.clone(),
+            enforce_limits,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            shared_state.clone(),
+            authenticate,
+        ))
+        // Docs and metrics are added after the layers, so they are served without a key
+        .merge(SwaggerUi::new("/docs").url("/openapi.json", ApiDoc::openapi()))
+        .merge(routes::unauthenticated_router())
         .with_state(shared_state);
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .unwrap();
 
     println!("API listening on port 3000");
 }
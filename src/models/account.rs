@@ -3,10 +3,13 @@ use num_bigint::BigInt;
 use scylla::FromRow;
 use serde::ser::SerializeStruct;
 use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
 
-#[derive(Debug, FromRow)]
+#[derive(Debug, Clone, FromRow, ToSchema)]
 pub struct Account {
     pub ledger_index: i64,
+    #[schema(value_type = String)]
     pub tx_index: BigInt,
     pub account: String,
     pub client: Option<String>,
@@ -33,3 +36,26 @@ impl Serialize for Account {
         s.end()
     }
 }
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AccountBalance {
+    pub currency: String,
+    pub counterparty: Option<String>,
+    pub balance: String,
+    pub ledger_index: i64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Activation of an account with its balances and activity since `since`. Balances are those
+/// last changed within the window.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AccountSummary {
+    pub activation: Account,
+    pub since: DateTime<Utc>,
+    pub balances: Vec<AccountBalance>,
+    pub first_transaction_time: Option<DateTime<Utc>>,
+    pub last_transaction_time: Option<DateTime<Utc>>,
+    pub transaction_count: u64,
+    pub transaction_types: BTreeMap<String, u64>,
+    pub payment_counterparties: u64,
+}
@@ -0,0 +1,143 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use scylla::frame::value::Counter;
+use scylla::FromRow;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Usage tier of a client. Routes are matched by prefix against the route pattern.
+#[derive(Debug, Serialize)]
+pub struct Tier {
+    pub name: &'static str,
+    pub requests_per_minute: u32,
+    pub max_limit: i32,
+    pub allowed_routes: &'static [&'static [&'static str]],
+}
+
+static PUBLIC_ROUTES: &[&str] = &[
+    // Only matches the root itself, the JSON-RPC endpoint
+    "/",
+    "/ledger",
+    "/daily_ledgers",
+    "/closed_ledger",
+    "/transaction",
+    "/account",
+    "/exchanges",
+    "/batch",
+    "/graphql",
+    // Data API v2 compatible routes
+    "/legacy",
+];
+// Aggregates over whole days, only served to callers with a key
+static STATS_ROUTES: &[&str] = &["/stats"];
+static STREAMING_ROUTES: &[&str] = &["/stream"];
+static INTEGRATION_ROUTES: &[&str] = &["/webhooks"];
+static ADMIN_ROUTES: &[&str] = &["/admin"];
+
+// Applies to requests without an API key
+pub static ANONYMOUS_TIER: Tier = Tier {
+    name: "anonymous",
+    requests_per_minute: 60,
+    max_limit: 100,
+    allowed_routes: &[PUBLIC_ROUTES],
+};
+
+pub static TIERS: &[&Tier] = &[
+    &ANONYMOUS_TIER,
+    &Tier {
+        name: "free",
+        requests_per_minute: 600,
+        max_limit: 1000,
+        allowed_routes: &[PUBLIC_ROUTES, STATS_ROUTES, STREAMING_ROUTES],
+    },
+    &Tier {
+        name: "pro",
+        requests_per_minute: 6000,
+        max_limit: 10_000,
+        allowed_routes: &[
+            PUBLIC_ROUTES,
+            STATS_ROUTES,
+            STREAMING_ROUTES,
+            INTEGRATION_ROUTES,
+        ],
+    },
+    &Tier {
+        name: "admin",
+        requests_per_minute: 60_000,
+        max_limit: 10_000,
+        allowed_routes: &[
+            PUBLIC_ROUTES,
+            STATS_ROUTES,
+            STREAMING_ROUTES,
+            INTEGRATION_ROUTES,
+            ADMIN_ROUTES,
+        ],
+    },
+];
+
+impl Tier {
+    pub fn find(name: &str) -> Option<&'static Tier> {
+        TIERS.iter().copied().find(|tier| tier.name == name)
+    }
+
+    pub fn allows(&self, route: &str) -> bool {
+        self.allowed_routes
+            .iter()
+            .flat_map(|routes| routes.iter())
+            .any(|prefix| {
+                route
+                    .strip_prefix(prefix)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+    }
+}
+
+/// Stored API key. Only a hash of the secret part is kept.
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub name: String,
+    pub tier: String,
+    #[serde(skip_serializing)]
+    pub secret_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ApiKeyRequest {
+    pub name: String,
+    pub tier: String,
+}
+
+/// Returned once on creation, the plain key can not be recovered afterwards.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedApiKey {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub key: String,
+}
+
+#[derive(Debug, FromRow, ToSchema)]
+pub struct ApiKeyUsage {
+    pub day: NaiveDate,
+    pub route: String,
+    #[schema(value_type = i64)]
+    pub requests: Counter,
+}
+
+impl Serialize for ApiKeyUsage {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("ApiKeyUsage", 3)?;
+
+        state.serialize_field("day", &self.day)?;
+        state.serialize_field("route", &self.route)?;
+        state.serialize_field("requests", &self.requests.0)?;
+
+        state.end()
+    }
+}
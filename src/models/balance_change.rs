@@ -3,11 +3,14 @@ use num_bigint::BigInt;
 use scylla::FromRow;
 use serde::ser::SerializeStruct;
 use serde::{Serialize, Serializer};
+use utoipa::ToSchema;
 
-#[derive(Debug, FromRow)]
+#[derive(Debug, Clone, FromRow, ToSchema)]
 pub struct BalanceChange {
     pub ledger_index: i64,
+    #[schema(value_type = String)]
     pub tx_index: BigInt,
+    #[schema(value_type = String)]
     pub node_index: BigInt,
     pub account: String,
     pub change: String,
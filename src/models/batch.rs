@@ -0,0 +1,56 @@
+use crate::models::account::Account;
+use crate::models::ledger::Ledger;
+use crate::models::transaction::Transaction;
+use crate::utils::errors::DataApiError;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Identifiers to look up in one request.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BatchRequest {
+    pub ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchItemStatus {
+    Found,
+    NotFound,
+    Error,
+}
+
+/// Result of one identifier of a batch, in the order of the request. `data` is only set when
+/// found and `error` only on errors.
+#[derive(Debug, Serialize, ToSchema)]
+#[aliases(
+    TransactionBatchItem = BatchItem<Transaction>,
+    LedgerBatchItem = BatchItem<Ledger>,
+    AccountBatchItem = BatchItem<Account>,
+)]
+pub struct BatchItem<T> {
+    pub id: String,
+    pub status: BatchItemStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl<T> BatchItem<T> {
+    pub fn new(id: String, result: Result<T, DataApiError>) -> Self {
+        let (status, data, error) = match result {
+            Ok(data) => (BatchItemStatus::Found, Some(data), None),
+            Err(DataApiError::NoDataReturned) => (BatchItemStatus::NotFound, None, None),
+            Err(err) => {
+                eprintln!("{}", err);
+                (BatchItemStatus::Error, None, Some(err.to_string()))
+            }
+        };
+        BatchItem {
+            id,
+            status,
+            data,
+            error,
+        }
+    }
+}
@@ -1,13 +1,10 @@
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::{DateTime, Utc};
 use scylla::FromRow;
 use serde::Serialize;
+use utoipa::ToSchema;
 
-#[derive(Debug, Serialize, FromRow)]
+#[derive(Debug, Serialize, FromRow, ToSchema)]
 pub struct DailyLedger {
-    #[serde(skip_serializing)]
-    pub ledger_close_day: NaiveDate,
     pub ledger_index: i64,
     pub close_time: DateTime<Utc>,
-    #[serde(skip_serializing)]
-    pub close_time_unix: i64,
 }
@@ -0,0 +1,18 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DailyStats {
+    pub date: NaiveDate,
+    pub ledger_count: u64,
+    pub first_ledger_index: Option<i64>,
+    pub last_ledger_index: Option<i64>,
+    pub transaction_count: u64,
+    pub transactions_by_type: BTreeMap<String, u64>,
+    pub transactions_by_result: BTreeMap<String, u64>,
+    pub fees_burned: String,
+    pub new_accounts: u64,
+    pub payment_volume: BTreeMap<String, String>,
+}
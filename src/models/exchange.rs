@@ -0,0 +1,147 @@
+use crate::utils::consts::XRP_CURRENCY;
+use crate::utils::decimal::Decimal;
+use crate::utils::errors::DataApiError;
+use chrono::{DateTime, Utc};
+use num_bigint::BigInt;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+/// Currency identifier used in exchange routes, either `XRP` or `CODE+issuer`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Currency {
+    pub code: String,
+    pub issuer: Option<String>,
+}
+
+impl Currency {
+    pub fn is_xrp(&self) -> bool {
+        self.code == XRP_CURRENCY && self.issuer.is_none()
+    }
+
+    /// Checks whether a balance change in `currency` with `counterparty` is in this currency.
+    pub fn matches(&self, currency: &str, counterparty: Option<&str>) -> bool {
+        if self.code != currency {
+            return false;
+        }
+        match &self.issuer {
+            Some(issuer) => counterparty == Some(issuer.as_str()),
+            None => true,
+        }
+    }
+}
+
+impl FromStr for Currency {
+    type Err = DataApiError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || DataApiError::InvalidParameter(format!("invalid currency {}", value));
+        match value.split_once('+') {
+            Some((code, issuer)) if !code.is_empty() && !issuer.is_empty() => Ok(Currency {
+                code: code.to_string(),
+                issuer: Some(issuer.to_string()),
+            }),
+            Some(_) => Err(invalid()),
+            None if value == XRP_CURRENCY => Ok(Currency {
+                code: value.to_string(),
+                issuer: None,
+            }),
+            None => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.issuer {
+            Some(issuer) => write!(f, "{}+{}", self.code, issuer),
+            None => write!(f, "{}", self.code),
+        }
+    }
+}
+
+/// A single offer fill, built by pairing the exchange balance changes of a transaction.
+#[derive(Debug, Clone, ToSchema)]
+pub struct Exchange {
+    #[schema(value_type = String)]
+    pub base: Currency,
+    #[schema(value_type = String)]
+    pub counter: Currency,
+    #[schema(value_type = String)]
+    pub base_amount: Decimal,
+    #[schema(value_type = String)]
+    pub counter_amount: Decimal,
+    #[schema(value_type = String)]
+    pub rate: Decimal,
+    pub buyer: String,
+    pub seller: String,
+    pub taker: String,
+    pub maker: String,
+    pub ledger_index: i64,
+    #[schema(value_type = String)]
+    pub tx_index: BigInt,
+    pub tx_hash: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Serialize for Exchange {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("Exchange", 13)?;
+        s.serialize_field("base", &self.base.to_string())?;
+        s.serialize_field("counter", &self.counter.to_string())?;
+        s.serialize_field("base_amount", &self.base_amount)?;
+        s.serialize_field("counter_amount", &self.counter_amount)?;
+        s.serialize_field("rate", &self.rate)?;
+        s.serialize_field("buyer", &self.buyer)?;
+        s.serialize_field("seller", &self.seller)?;
+        s.serialize_field("taker", &self.taker)?;
+        s.serialize_field("maker", &self.maker)?;
+        s.serialize_field("ledger_index", &self.ledger_index)?;
+        s.serialize_field("tx_index", &self.tx_index.to_string())?;
+        s.serialize_field("tx_hash", &self.tx_hash)?;
+        s.serialize_field("timestamp", &self.timestamp)?;
+        s.end()
+    }
+}
+
+/// Open/high/low/close rates and volumes of a pair's trades within one interval.
+#[derive(Debug, Clone, ToSchema)]
+pub struct Candle {
+    pub start: DateTime<Utc>,
+    #[schema(value_type = String)]
+    pub open: Decimal,
+    #[schema(value_type = String)]
+    pub high: Decimal,
+    #[schema(value_type = String)]
+    pub low: Decimal,
+    #[schema(value_type = String)]
+    pub close: Decimal,
+    #[schema(value_type = String)]
+    pub base_volume: Decimal,
+    #[schema(value_type = String)]
+    pub counter_volume: Decimal,
+    pub trade_count: u64,
+}
+
+impl Serialize for Candle {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("Candle", 8)?;
+        s.serialize_field("start", &self.start)?;
+        s.serialize_field("open", &self.open)?;
+        s.serialize_field("high", &self.high)?;
+        s.serialize_field("low", &self.low)?;
+        s.serialize_field("close", &self.close)?;
+        s.serialize_field("base_volume", &self.base_volume)?;
+        s.serialize_field("counter_volume", &self.counter_volume)?;
+        s.serialize_field("trade_count", &self.trade_count)?;
+        s.end()
+    }
+}
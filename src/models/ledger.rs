@@ -1,19 +1,11 @@
 use chrono::Utc;
 use num_bigint::BigInt;
-use scylla::{
-    frame::value::{CqlDate, CqlTimestamp},
-    FromRow,
-};
+use scylla::FromRow;
 use serde::{ser::SerializeStruct, Serialize, Serializer};
+use utoipa::openapi::{KnownFormat, ObjectBuilder, RefOr, Schema, SchemaFormat, SchemaType};
+use utoipa::ToSchema;
 
-#[derive(Debug, FromRow)]
-pub struct DailyLedgerScylla {
-    pub ledger_close_day: CqlDate,
-    pub ledger_index: i64,
-    pub close_time: CqlTimestamp,
-}
-
-#[derive(Debug, FromRow)]
+#[derive(Debug, Clone, FromRow)]
 pub struct Ledger {
     pub ledger_index: i64,
     pub ledger_hash: String,
@@ -51,3 +43,38 @@ impl Serialize for Ledger {
         state.end()
     }
 }
+
+// Written by hand as `ledger_processed` is not serialized
+impl<'s> ToSchema<'s> for Ledger {
+    fn schema() -> (&'s str, RefOr<Schema>) {
+        let field = |schema_type: SchemaType, format: Option<KnownFormat>| {
+            ObjectBuilder::new()
+                .schema_type(schema_type)
+                .format(format.map(SchemaFormat::KnownFormat))
+        };
+        let schema = ObjectBuilder::new()
+            .property("ledger_index", field(SchemaType::Integer, Some(KnownFormat::Int64)))
+            .property("ledger_hash", field(SchemaType::String, None))
+            .property("parent_hash", field(SchemaType::String, None))
+            .property("account_hash", field(SchemaType::String, None))
+            .property("transaction_hash", field(SchemaType::String, None))
+            .property("close_flags", field(SchemaType::Integer, Some(KnownFormat::Int32)))
+            .property("close_time", field(SchemaType::String, Some(KnownFormat::DateTime)))
+            .property("parent_close_time", field(SchemaType::String, Some(KnownFormat::DateTime)))
+            .property("total_coins", field(SchemaType::Integer, Some(KnownFormat::Int64)))
+            .property("tx_count", field(SchemaType::String, None))
+            .required("ledger_index")
+            .required("ledger_hash")
+            .required("parent_hash")
+            .required("account_hash")
+            .required("transaction_hash")
+            .required("close_flags")
+            .required("close_time")
+            .required("parent_close_time")
+            .required("total_coins")
+            .required("tx_count")
+            .build();
+
+        ("Ledger", schema.into())
+    }
+}
@@ -0,0 +1,243 @@
+use crate::models::balance_change::BalanceChange;
+use crate::models::ledger::Ledger;
+use crate::models::payment::Payment;
+use crate::models::transaction::Transaction;
+use chrono::{DateTime, SecondsFormat, Utc};
+use num_bigint::BigInt;
+use serde::Serialize;
+use serde_json::Value;
+use utoipa::ToSchema;
+
+// Shapes of Ripple's Data API v2, served by the legacy routes. Field names and types follow
+// v2 rather than the rest of this API.
+
+pub static LEGACY_SUCCESS: &str = "success";
+pub static LEGACY_ERROR: &str = "error";
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LegacyError {
+    pub result: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LegacyLedgerResponse {
+    pub result: String,
+    pub ledger: LegacyLedger,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LegacyTransactionResponse {
+    pub result: String,
+    pub transaction: LegacyTransaction,
+}
+
+/// A page of payments, `marker` is set when more may follow.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LegacyPaymentsResponse {
+    pub result: String,
+    pub count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub marker: Option<String>,
+    pub payments: Vec<LegacyPayment>,
+}
+
+/// A page of balance changes, `marker` is set when more may follow.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LegacyBalanceChangesResponse {
+    pub result: String,
+    pub count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub marker: Option<String>,
+    pub balance_changes: Vec<LegacyBalanceChange>,
+}
+
+/// Ledger header, times are Unix times. `close_time_res` is not stored, so it is left out.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LegacyLedger {
+    pub ledger_hash: String,
+    pub ledger_index: i64,
+    pub parent_hash: String,
+    pub total_coins: String,
+    pub accounts_hash: String,
+    pub transactions_hash: String,
+    pub close_time: i64,
+    pub close_time_human: String,
+    pub close_flags: i32,
+    pub parent_close_time: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transactions: Option<LegacyLedgerTransactions>,
+}
+
+/// Hashes of the ledger's transactions, or the transactions themselves with `expand`.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum LegacyLedgerTransactions {
+    Hashes(Vec<String>),
+    Expanded(Vec<LegacyTransaction>),
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LegacyTransaction {
+    pub hash: String,
+    pub ledger_index: i64,
+    pub date: String,
+    #[schema(value_type = Object)]
+    pub tx: Value,
+    #[schema(value_type = Object)]
+    pub meta: Value,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LegacyPayment {
+    pub amount: String,
+    pub delivered_amount: String,
+    pub destination_balance_changes: Vec<LegacyBalance>,
+    pub source_balance_changes: Vec<LegacyBalance>,
+    pub tx_index: i64,
+    pub currency: String,
+    pub issuer: String,
+    pub destination: String,
+    pub executed_time: String,
+    pub ledger_index: i64,
+    pub source: String,
+    pub source_currency: String,
+    pub tx_hash: String,
+    // In XRP, not drops
+    pub transaction_cost: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination_tag: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_tag: Option<i64>,
+}
+
+/// A balance change of a payment's source or destination, the counterparty is empty for XRP.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LegacyBalance {
+    pub counterparty: String,
+    pub currency: String,
+    pub value: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LegacyBalanceChange {
+    pub amount_change: String,
+    pub final_balance: String,
+    pub node_index: i64,
+    pub tx_index: i64,
+    pub change_type: String,
+    pub currency: String,
+    pub executed_time: String,
+    pub counterparty: String,
+    pub ledger_index: i64,
+    pub tx_hash: String,
+}
+
+impl From<&Ledger> for LegacyLedger {
+    fn from(ledger: &Ledger) -> Self {
+        LegacyLedger {
+            ledger_hash: ledger.ledger_hash.clone(),
+            ledger_index: ledger.ledger_index,
+            parent_hash: ledger.parent_hash.clone(),
+            total_coins: ledger.total_coins.to_string(),
+            accounts_hash: ledger.account_hash.clone(),
+            transactions_hash: ledger.transaction_hash.clone(),
+            close_time: ledger.close_time.timestamp(),
+            close_time_human: ledger.close_time.format("%Y-%b-%d %H:%M:%S").to_string(),
+            close_flags: ledger.close_flags,
+            parent_close_time: ledger.parent_close_time.timestamp(),
+            transactions: None,
+        }
+    }
+}
+
+impl From<&Transaction> for LegacyTransaction {
+    fn from(transaction: &Transaction) -> Self {
+        LegacyTransaction {
+            hash: transaction.hash.clone(),
+            ledger_index: transaction.ledger_index,
+            date: executed_time(transaction.timestamp),
+            tx: transaction.tx_json(),
+            meta: transaction.meta_json(),
+        }
+    }
+}
+
+impl From<&BalanceChange> for LegacyBalance {
+    fn from(change: &BalanceChange) -> Self {
+        LegacyBalance {
+            counterparty: change.counterparty.clone().unwrap_or_default(),
+            currency: change.currency.clone(),
+            value: change.change.clone(),
+        }
+    }
+}
+
+impl From<&BalanceChange> for LegacyBalanceChange {
+    fn from(change: &BalanceChange) -> Self {
+        LegacyBalanceChange {
+            amount_change: change.change.clone(),
+            final_balance: change.final_balance.clone(),
+            node_index: integer(&change.node_index),
+            tx_index: integer(&change.tx_index),
+            change_type: change.change_type.clone(),
+            currency: change.currency.clone(),
+            executed_time: executed_time(change.timestamp),
+            counterparty: change.counterparty.clone().unwrap_or_default(),
+            ledger_index: change.ledger_index,
+            tx_hash: change.tx_hash.clone(),
+        }
+    }
+}
+
+impl LegacyPayment {
+    /// `changes` are the balance changes of the payment's transaction, split by account.
+    pub fn new(payment: &Payment, changes: &[BalanceChange]) -> Self {
+        let balances_of = |account: &str| {
+            changes
+                .iter()
+                .filter(|change| change.account == account)
+                .map(LegacyBalance::from)
+                .collect::<Vec<LegacyBalance>>()
+        };
+        LegacyPayment {
+            amount: payment.amount.clone(),
+            delivered_amount: payment.delivered_amount.clone(),
+            destination_balance_changes: balances_of(&payment.destination),
+            source_balance_changes: balances_of(&payment.source),
+            tx_index: integer(&payment.tx_index),
+            currency: payment.destination_currency.clone(),
+            issuer: payment.destination_currency_issuer.clone(),
+            destination: payment.destination.clone(),
+            executed_time: executed_time(payment.timestamp),
+            ledger_index: payment.ledger_index,
+            source: payment.source.clone(),
+            source_currency: payment.source_currency.clone(),
+            tx_hash: payment.tx_hash.clone(),
+            transaction_cost: drops_to_xrp(&payment.transaction_cost),
+            destination_tag: payment.destination_tag,
+            source_tag: payment.source_tag,
+        }
+    }
+}
+
+// Indexes within a ledger always fit
+fn integer(value: &BigInt) -> i64 {
+    i64::try_from(value).unwrap_or_default()
+}
+
+fn executed_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+fn drops_to_xrp(drops: &BigInt) -> String {
+    let xrp: BigInt = drops / 1_000_000;
+    let drops: BigInt = drops % 1_000_000;
+    let drops = format!("{:06}", drops);
+    let drops = drops.trim_end_matches('0');
+    if drops.is_empty() {
+        xrp.to_string()
+    } else {
+        format!("{}.{}", xrp, drops)
+    }
+}
@@ -1,6 +1,13 @@
 pub mod account;
+pub mod api_key;
 pub mod balance_change;
+pub mod batch;
 pub mod daily_ledger;
+pub mod daily_stats;
+pub mod exchange;
 pub mod ledger;
+pub mod legacy;
 pub mod payment;
+pub mod subscription;
 pub mod transaction;
+pub mod webhook;
@@ -3,11 +3,13 @@ use num_bigint::BigInt;
 use scylla::FromRow;
 use serde::ser::SerializeStruct;
 use serde::{Serialize, Serializer};
+use utoipa::ToSchema;
 
-#[derive(Debug, FromRow)]
+#[derive(Debug, Clone, FromRow, ToSchema)]
 pub struct Payment {
     pub tx_hash: String,
     pub ledger_index: i64,
+    #[schema(value_type = String)]
     pub tx_index: BigInt,
     pub source: String,
     pub source_currency: String,
@@ -17,6 +19,7 @@ pub struct Payment {
     pub destination_currency_issuer: String,
     pub amount: String,
     pub delivered_amount: String,
+    #[schema(value_type = String)]
     pub transaction_cost: BigInt,
     pub destination_tag: Option<i64>,
     pub source_tag: Option<i64>,
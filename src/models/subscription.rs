@@ -0,0 +1,61 @@
+use crate::models::balance_change::BalanceChange;
+use crate::models::payment::Payment;
+use crate::models::transaction::Transaction;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountEventKind {
+    Payments,
+    BalanceChanges,
+    Transactions,
+}
+
+/// Message sent by clients of the account activity WebSocket.
+/// `since_ledger` resumes delivery after the given ledger, e.g. the last `ledger_closed` seen
+/// before a reconnect, and may be up to `SUBSCRIPTION_CATCH_UP_LEDGERS` ledgers old. Without
+/// it delivery starts at the latest closed ledger.
+#[derive(Deserialize, Debug)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum SubscriptionRequest {
+    Subscribe {
+        accounts: Vec<String>,
+        events: Option<Vec<AccountEventKind>>,
+        since_ledger: Option<i64>,
+    },
+    Unsubscribe {
+        accounts: Vec<String>,
+    },
+}
+
+/// Message sent to clients of the account activity WebSocket.
+/// `ledger_closed` confirms every event up to and including that ledger was delivered.
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AccountEvent {
+    Subscribed {
+        accounts: Vec<String>,
+        ledger_index: Option<i64>,
+    },
+    Unsubscribed {
+        accounts: Vec<String>,
+    },
+    Transaction {
+        account: String,
+        transaction: Transaction,
+    },
+    BalanceChange {
+        account: String,
+        balance_change: BalanceChange,
+    },
+    Payment {
+        account: String,
+        payment: Payment,
+    },
+    LedgerClosed {
+        ledger_index: i64,
+    },
+    Error {
+        message: String,
+    },
+}
@@ -3,21 +3,28 @@ use num_bigint::BigInt;
 use scylla::FromRow;
 use serde::ser::SerializeStruct;
 use serde::{Serialize, Serializer};
+use serde_json::Value;
+use utoipa::ToSchema;
 
-#[derive(Debug, FromRow)]
+// Big integers and the binary meta and tx are serialized as strings
+#[derive(Debug, Clone, FromRow, ToSchema)]
 pub struct Transaction {
     pub account: String,
     pub hash: String,
     pub ctid: String,
     pub ledger_index: i64,
+    #[schema(value_type = String)]
     pub tx_index: BigInt,
     pub tx_type: String,
     pub timestamp: DateTime<Utc>,
     pub flags: i64,
+    #[schema(value_type = String)]
     pub fee: BigInt,
     pub sequence: i64,
     pub result: i16,
+    #[schema(value_type = String)]
     pub meta: Vec<u8>,
+    #[schema(value_type = String)]
     pub tx: Vec<u8>,
 }
 
@@ -43,3 +50,19 @@ impl Serialize for Transaction {
         s.end()
     }
 }
+
+impl Transaction {
+    pub fn tx_json(&self) -> Value {
+        json_blob(&self.tx)
+    }
+
+    pub fn meta_json(&self) -> Value {
+        json_blob(&self.meta)
+    }
+}
+
+// Stored as JSON text, kept as a string if it does not parse
+fn json_blob(blob: &[u8]) -> Value {
+    serde_json::from_slice(blob)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(blob).to_string()))
+}
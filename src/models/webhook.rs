@@ -0,0 +1,107 @@
+use crate::models::exchange::Currency;
+use crate::models::payment::Payment;
+use chrono::{DateTime, Utc};
+use scylla::FromRow;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Registered callback receiving the payments of `accounts` that pass the filters.
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub url: String,
+    // Only known to the registrant, used to sign deliveries
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub accounts: Vec<String>,
+    pub currency: Option<String>,
+    pub destination_tag: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    // Key that registered the webhook, none for the bootstrap admin key
+    #[serde(skip_serializing)]
+    pub key_id: Option<Uuid>,
+}
+
+impl Webhook {
+    /// Checks whether a payment is sent or received by a registered account and passes the filters.
+    pub fn matches(&self, payment: &Payment) -> bool {
+        let involves_account = self
+            .accounts
+            .iter()
+            .any(|account| *account == payment.source || *account == payment.destination);
+        let currency_matches = match &self.currency {
+            Some(currency) => currency.parse::<Currency>().is_ok_and(|currency| {
+                currency.matches(
+                    &payment.destination_currency,
+                    Some(payment.destination_currency_issuer.as_str()),
+                )
+            }),
+            None => true,
+        };
+        let destination_tag_matches = self
+            .destination_tag
+            .is_none_or(|destination_tag| payment.destination_tag == Some(destination_tag));
+
+        involves_account && currency_matches && destination_tag_matches
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct WebhookRequest {
+    pub url: String,
+    pub secret: String,
+    pub accounts: Vec<String>,
+    pub currency: Option<String>,
+    pub destination_tag: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Delivered => "delivered",
+            DeliveryStatus::Failed => "failed",
+        }
+    }
+}
+
+/// A payment queued for a webhook. `payload` is the exact signed body sent on every attempt.
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct WebhookDelivery {
+    pub webhook_id: Uuid,
+    pub delivery_id: Uuid,
+    pub tx_hash: String,
+    #[serde(skip_serializing)]
+    pub payload: String,
+    pub status: String,
+    pub attempts: i32,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow, Serialize, ToSchema)]
+pub struct WebhookDeliveryAttempt {
+    pub delivery_id: Uuid,
+    pub attempt: i32,
+    pub attempted_at: DateTime<Utc>,
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+    pub duration_ms: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WebhookDeliveryDetails {
+    #[serde(flatten)]
+    pub delivery: WebhookDelivery,
+    pub attempts_log: Vec<WebhookDeliveryAttempt>,
+}
@@ -0,0 +1,231 @@
+use crate::gaps::{LedgerGaps, LedgerRange};
+use crate::handlers;
+use crate::models::account::{Account, AccountBalance, AccountSummary};
+use crate::models::api_key::{ApiKey, ApiKeyRequest, ApiKeyUsage, CreatedApiKey};
+use crate::models::balance_change::BalanceChange;
+use crate::models::batch::{
+    AccountBatchItem, BatchItemStatus, BatchRequest, LedgerBatchItem, TransactionBatchItem,
+};
+use crate::models::daily_ledger::DailyLedger;
+use crate::models::daily_stats::DailyStats;
+use crate::models::exchange::{Candle, Exchange};
+use crate::models::ledger::Ledger;
+use crate::models::legacy::{
+    LegacyBalance, LegacyBalanceChange, LegacyBalanceChangesResponse, LegacyError, LegacyLedger,
+    LegacyLedgerResponse, LegacyLedgerTransactions, LegacyPayment, LegacyPaymentsResponse,
+    LegacyTransaction, LegacyTransactionResponse,
+};
+use crate::models::payment::Payment;
+use crate::models::transaction::Transaction;
+use crate::models::webhook::{
+    Webhook, WebhookDelivery, WebhookDeliveryAttempt, WebhookDeliveryDetails, WebhookRequest,
+};
+use crate::utils::consts::API_KEY_HEADER;
+use crate::utils::format::OutputFormat;
+use crate::utils::params::{CandleInterval, LegacyPaymentType};
+use utoipa::openapi::security::{ApiKey as ApiKeyScheme, ApiKeyValue, SecurityScheme};
+use utoipa::openapi::Server;
+use utoipa::{Modify, OpenApi};
+
+/// OpenAPI document of every route, served at `/openapi.json`.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "XRPL Data API"),
+    servers((url = "/v1")),
+    paths(
+        handlers::ledger::get_ledger_handler,
+        handlers::daily_ledger::get_daily_ledgers_handler,
+        handlers::daily_ledger::get_latest_closed_ledger_handler,
+        handlers::stats::get_daily_stats_handler,
+        handlers::stream::ledgers_sse_handler,
+        handlers::stream::ledgers_ws_handler,
+        handlers::subscription::accounts_ws_handler,
+        handlers::transaction::get_transaction_by_hash,
+        handlers::transaction::get_transaction_by_ledger_index,
+        handlers::transaction::get_transaction_by_account,
+        handlers::account::get_account_handler,
+        handlers::account::get_account_children_handler,
+        handlers::account::get_account_summary_handler,
+        handlers::account::get_account_ancestry_handler,
+        handlers::balance_change::get_account_balance_changes_handler,
+        handlers::exchange::get_account_exchanges_handler,
+        handlers::exchange::get_exchanges_handler,
+        handlers::candle::get_candles_handler,
+        handlers::payment::get_account_payments_handler,
+        handlers::batch::batch_transactions_handler,
+        handlers::batch::batch_ledgers_handler,
+        handlers::batch::batch_accounts_handler,
+        handlers::jsonrpc::jsonrpc_handler,
+        handlers::graphql::graphql_handler,
+        handlers::graphql::graphiql_handler,
+        handlers::legacy::get_legacy_ledger_handler,
+        handlers::legacy::get_legacy_transaction_handler,
+        handlers::legacy::get_legacy_payments_handler,
+        handlers::legacy::get_legacy_balance_changes_handler,
+        handlers::webhook::get_webhooks_handler,
+        handlers::webhook::create_webhook_handler,
+        handlers::webhook::get_webhook_handler,
+        handlers::webhook::update_webhook_handler,
+        handlers::webhook::delete_webhook_handler,
+        handlers::webhook::get_webhook_deliveries_handler,
+        handlers::webhook::get_webhook_delivery_handler,
+        handlers::webhook::redeliver_webhook_handler,
+        handlers::api_key::get_api_keys_handler,
+        handlers::api_key::create_api_key_handler,
+        handlers::api_key::revoke_api_key_handler,
+        handlers::api_key::get_api_key_usage_handler,
+        handlers::gaps::get_gaps_handler,
+        handlers::metrics::get_metrics_handler,
+    ),
+    components(schemas(
+        Ledger,
+        DailyLedger,
+        DailyStats,
+        Transaction,
+        Account,
+        AccountBalance,
+        AccountSummary,
+        BalanceChange,
+        Payment,
+        BatchRequest,
+        BatchItemStatus,
+        TransactionBatchItem,
+        LedgerBatchItem,
+        AccountBatchItem,
+        Exchange,
+        Candle,
+        CandleInterval,
+        OutputFormat,
+        LegacyError,
+        LegacyLedgerResponse,
+        LegacyLedger,
+        LegacyLedgerTransactions,
+        LegacyTransactionResponse,
+        LegacyTransaction,
+        LegacyPaymentsResponse,
+        LegacyPayment,
+        LegacyPaymentType,
+        LegacyBalance,
+        LegacyBalanceChangesResponse,
+        LegacyBalanceChange,
+        Webhook,
+        WebhookRequest,
+        WebhookDelivery,
+        WebhookDeliveryAttempt,
+        WebhookDeliveryDetails,
+        ApiKey,
+        ApiKeyRequest,
+        CreatedApiKey,
+        ApiKeyUsage,
+        LedgerGaps,
+        LedgerRange,
+    )),
+    modifiers(&ApiKeySecurity, &RootServers),
+    security((), ("api_key" = [])),
+)]
+pub struct ApiDoc;
+
+struct ApiKeySecurity;
+
+impl Modify for ApiKeySecurity {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKeyScheme::Header(ApiKeyValue::new(API_KEY_HEADER))),
+        );
+    }
+}
+
+// Data API v2 routes and metrics are only served at the root, not under the versioned server
+struct RootServers;
+
+impl Modify for RootServers {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        for (path, item) in openapi.paths.paths.iter_mut() {
+            if path.starts_with("/legacy/") || path == "/metrics" {
+                item.servers = Some(vec![Server::new("/")]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ApiDoc;
+    use crate::routes::{API_ROUTES, LEGACY_ROUTES, UNAUTHENTICATED_ROUTES};
+    use utoipa::openapi::PathItemType;
+    use utoipa::OpenApi;
+
+    // Routes are read from the tables the router is built from, so a route added without
+    // a `utoipa::path` on its handler fails this test
+    fn routes() -> Vec<(String, &'static str, PathItemType)> {
+        let mut routes = Vec::new();
+        for route in API_ROUTES
+            .iter()
+            .chain(LEGACY_ROUTES)
+            .chain(UNAUTHENTICATED_ROUTES)
+        {
+            let path = route
+                .path
+                .split('/')
+                .map(|segment| match segment.strip_prefix(':') {
+                    Some(param) => format!("{{{}}}", param),
+                    None => segment.to_string(),
+                })
+                .collect::<Vec<String>>()
+                .join("/");
+            for method in route.methods {
+                let item_type = match *method {
+                    "get" => PathItemType::Get,
+                    "post" => PathItemType::Post,
+                    "put" => PathItemType::Put,
+                    "patch" => PathItemType::Patch,
+                    "delete" => PathItemType::Delete,
+                    other => panic!("unexpected method {} on {}", other, route.path),
+                };
+                routes.push((path.clone(), *method, item_type));
+            }
+        }
+        routes
+    }
+
+    #[test]
+    fn every_route_is_documented() {
+        let spec = ApiDoc::openapi();
+        let routes = routes();
+        assert!(!routes.is_empty());
+
+        let undocumented = routes
+            .iter()
+            .filter(|(path, _, item_type)| {
+                spec.paths
+                    .paths
+                    .get(path)
+                    .is_none_or(|item| !item.operations.contains_key(item_type))
+            })
+            .map(|(path, method, _)| format!("{} {}", method, path))
+            .collect::<Vec<String>>();
+        assert!(
+            undocumented.is_empty(),
+            "routes missing from the OpenAPI document: {:?}",
+            undocumented
+        );
+    }
+
+    #[test]
+    fn every_documented_path_is_routed() {
+        let routes = routes();
+        let stale = ApiDoc::openapi()
+            .paths
+            .paths
+            .into_keys()
+            .filter(|path| !routes.iter().any(|(route, _, _)| route == path))
+            .collect::<Vec<String>>();
+        assert!(
+            stale.is_empty(),
+            "documented paths without a route: {:?}",
+            stale
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use crate::handlers;
+use crate::AppState;
+use axum::Router;
+use std::sync::Arc;
+
+// A routed path and the methods it accepts, as written in the router
+#[cfg_attr(not(test), allow(dead_code))]
+pub struct Route {
+    pub path: &'static str,
+    pub methods: &'static [&'static str],
+}
+
+// Declares a route table and the router built from it, so the table always lists the
+// routes that are actually served
+macro_rules! routes {
+    ($table:ident, $router:ident, {
+        $($path:literal => $method:ident($handler:path) $(.$more:ident($more_handler:path))*,)*
+    }) => {
+        // Checked against the OpenAPI document by its tests
+        #[cfg_attr(not(test), allow(dead_code))]
+        pub static $table: &[Route] = &[$(Route {
+            path: $path,
+            methods: &[stringify!($method) $(, stringify!($more))*],
+        }),*];
+
+        pub fn $router() -> Router<Arc<AppState>> {
+            Router::new()$(.route(
+                $path,
+                axum::routing::$method($handler)$(.$more($more_handler))*,
+            ))*
+        }
+    };
+}
+
+// todo: sql injections for strings
+routes!(API_ROUTES, api_router, {
+    // Ledger handlers
+    "/ledger/:ledger_identifier" => get(handlers::ledger::get_ledger_handler),
+    "/daily_ledgers/:close_day" => get(handlers::daily_ledger::get_daily_ledgers_handler),
+    "/closed_ledger/:close_time" => get(handlers::daily_ledger::get_latest_closed_ledger_handler),
+    "/stats/daily/:date" => get(handlers::stats::get_daily_stats_handler),
+    // Streaming handlers
+    "/stream/ledgers" => get(handlers::stream::ledgers_sse_handler),
+    "/stream/ledgers/ws" => get(handlers::stream::ledgers_ws_handler),
+    "/stream/accounts/ws" => get(handlers::subscription::accounts_ws_handler),
+    // Transaction handlers
+    "/transaction/hash/:tx_hash" => get(handlers::transaction::get_transaction_by_hash),
+    "/transaction/ledger/:ledger_index" => get(handlers::transaction::get_transaction_by_ledger_index),
+    "/transaction/account/:account" => get(handlers::transaction::get_transaction_by_account),
+    // Account handlers
+    "/account/:account" => get(handlers::account::get_account_handler),
+    "/account/:account/children" => get(handlers::account::get_account_children_handler),
+    "/account/:account/summary" => get(handlers::account::get_account_summary_handler),
+    "/account/:account/ancestry" => get(handlers::account::get_account_ancestry_handler),
+    // Balance changes handlers
+    "/account/:account/balance_changes" => get(handlers::balance_change::get_account_balance_changes_handler),
+    // Exchanges handlers
+    "/account/:account/exchanges" => get(handlers::exchange::get_account_exchanges_handler),
+    "/exchanges/:base/:counter" => get(handlers::exchange::get_exchanges_handler),
+    "/exchanges/:base/:counter/candles" => get(handlers::candle::get_candles_handler),
+    // Payments handlers
+    "/account/:account/payments" => get(handlers::payment::get_account_payments_handler),
+    // Batch handlers
+    "/batch/transactions" => post(handlers::batch::batch_transactions_handler),
+    "/batch/ledgers" => post(handlers::batch::batch_ledgers_handler),
+    "/batch/accounts" => post(handlers::batch::batch_accounts_handler),
+    // rippled JSON-RPC handlers
+    "/" => post(handlers::jsonrpc::jsonrpc_handler),
+    // GraphQL handlers
+    "/graphql" => get(handlers::graphql::graphiql_handler)
+        .post(handlers::graphql::graphql_handler),
+    // Webhook handlers
+    "/webhooks" => get(handlers::webhook::get_webhooks_handler)
+        .post(handlers::webhook::create_webhook_handler),
+    "/webhooks/:id" => get(handlers::webhook::get_webhook_handler)
+        .put(handlers::webhook::update_webhook_handler)
+        .delete(handlers::webhook::delete_webhook_handler),
+    "/webhooks/:id/deliveries" => get(handlers::webhook::get_webhook_deliveries_handler),
+    "/webhooks/:id/deliveries/:delivery_id" => get(handlers::webhook::get_webhook_delivery_handler),
+    "/webhooks/:id/deliveries/:delivery_id/redeliver" => post(handlers::webhook::redeliver_webhook_handler),
+    // Admin handlers
+    "/admin/keys" => get(handlers::api_key::get_api_keys_handler)
+        .post(handlers::api_key::create_api_key_handler),
+    "/admin/keys/:id" => delete(handlers::api_key::revoke_api_key_handler),
+    "/admin/keys/:id/usage" => get(handlers::api_key::get_api_key_usage_handler),
+    "/admin/gaps" => get(handlers::gaps::get_gaps_handler),
+});
+
+// Ripple's retired Data API v2, only served at the root under /legacy so its /v2 is not taken
+// for a version of this API
+routes!(LEGACY_ROUTES, legacy_router, {
+    "/legacy/v2/ledgers/:ledger_identifier" => get(handlers::legacy::get_legacy_ledger_handler),
+    "/legacy/v2/transactions/:tx_hash" => get(handlers::legacy::get_legacy_transaction_handler),
+    "/legacy/v2/accounts/:account/payments" => get(handlers::legacy::get_legacy_payments_handler),
+    "/legacy/v2/accounts/:account/balance_changes" => get(handlers::legacy::get_legacy_balance_changes_handler),
+});
+
+// Served at the root without an API key
+routes!(UNAUTHENTICATED_ROUTES, unauthenticated_router, {
+    "/metrics" => get(handlers::metrics::get_metrics_handler),
+});
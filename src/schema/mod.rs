@@ -0,0 +1,202 @@
+use chrono::Utc;
+use scylla::transport::errors::QueryError;
+use scylla::Session;
+
+// Shared by the server and the `migrate` binary, so only depends on the scylla driver
+
+pub static SCHEMA_MIGRATIONS_TABLE: &str = "schema_migrations";
+// Placeholders of migration statements, filled in when they are applied
+static KEYSPACE_PLACEHOLDER: &str = "${keyspace}";
+static REPLICATION_PLACEHOLDER: &str = "${replication}";
+
+/// A versioned set of CQL statements. Versions are applied in order and never change once
+/// released, schema changes are added as new migrations.
+pub struct Migration {
+    pub version: i32,
+    pub name: &'static str,
+    pub cql: &'static str,
+}
+
+pub static MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "create_keyspace",
+        cql: include_str!("../../migrations/0001_create_keyspace.cql"),
+    },
+    Migration {
+        version: 2,
+        name: "ledger_tables",
+        cql: include_str!("../../migrations/0002_ledger_tables.cql"),
+    },
+    Migration {
+        version: 3,
+        name: "daily_stats",
+        cql: include_str!("../../migrations/0003_daily_stats.cql"),
+    },
+    Migration {
+        version: 4,
+        name: "api_keys",
+        cql: include_str!("../../migrations/0004_api_keys.cql"),
+    },
+    Migration {
+        version: 5,
+        name: "webhooks",
+        cql: include_str!("../../migrations/0005_webhooks.cql"),
+    },
+    Migration {
+        version: 6,
+        name: "ingest_checkpoints",
+        cql: include_str!("../../migrations/0006_ingest_checkpoints.cql"),
+    },
+    Migration {
+        version: 7,
+        name: "payments_by_destination",
+        cql: include_str!("../../migrations/0007_payments_by_destination.cql"),
+    },
+    Migration {
+        version: 8,
+        name: "webhook_dispatch",
+        cql: include_str!("../../migrations/0008_webhook_dispatch.cql"),
+    },
+    Migration {
+        version: 9,
+        name: "webhook_owners",
+        cql: include_str!("../../migrations/0009_webhook_owners.cql"),
+    },
+    Migration {
+        version: 10,
+        name: "exchange_pairs",
+        cql: include_str!("../../migrations/0010_exchange_pairs.cql"),
+    },
+];
+
+/// Whether `keyspace` can be used unquoted in statements. CQL lowercases unquoted names, so a
+/// name with uppercase letters would create a keyspace other than the one it spells.
+pub fn is_valid_keyspace(keyspace: &str) -> bool {
+    keyspace.len() <= 48
+        && keyspace.starts_with(|c: char| c.is_ascii_lowercase())
+        && keyspace
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Replication of a new keyspace, as a CQL map. A factor of 1 suits a single node.
+#[allow(dead_code)] // Only applied by the migrate binary
+pub fn replication(replication_factor: u32) -> String {
+    format!(
+        "{{'class': 'NetworkTopologyStrategy', 'replication_factor': {}}}",
+        replication_factor
+    )
+}
+
+/// Version of the schema this build expects, the latest migration.
+pub fn expected_version() -> i32 {
+    MIGRATIONS
+        .iter()
+        .map(|migration| migration.version)
+        .max()
+        .unwrap_or(0)
+}
+
+impl Migration {
+    /// The statements of the migration, without comments, for `keyspace` replicated with
+    /// `replication`.
+    #[allow(dead_code)] // Only applied by the migrate binary
+    pub fn statements(&self, keyspace: &str, replication: &str) -> Vec<String> {
+        let cql = self
+            .cql
+            .lines()
+            .filter(|line| !line.trim_start().starts_with("--"))
+            .collect::<Vec<&str>>()
+            .join("\n")
+            .replace(KEYSPACE_PLACEHOLDER, keyspace)
+            .replace(REPLICATION_PLACEHOLDER, replication);
+        cql.split(';')
+            .map(|statement| statement.trim().to_string())
+            .filter(|statement| !statement.is_empty())
+            .collect()
+    }
+}
+
+/// Latest migration applied to `keyspace`, 0 when it has no migrations table yet.
+pub async fn applied_version(session: &Session, keyspace: &str) -> Result<i32, QueryError> {
+    let query = "SELECT table_name from system_schema.tables \
+        WHERE keyspace_name=? AND table_name=?;";
+    let query_result = session
+        .query(query, (keyspace, SCHEMA_MIGRATIONS_TABLE))
+        .await?;
+    if query_result.rows_num().unwrap_or(0) == 0 {
+        return Ok(0);
+    }
+
+    let query = format!(
+        "SELECT version from {}.{};",
+        keyspace, SCHEMA_MIGRATIONS_TABLE
+    );
+    let query_result = session.query(query, ()).await?;
+    // todo: better row error handling
+    let version = query_result
+        .rows_typed_or_empty::<(i32,)>()
+        .filter_map(|row| row.ok())
+        .map(|(version,)| version)
+        .max()
+        .unwrap_or(0);
+
+    Ok(version)
+}
+
+/// Applies the migrations newer than the applied version, returning the new version. The first
+/// migration creates `keyspace` with `replication`, the others are applied within it.
+#[allow(dead_code)] // Only applied by the migrate binary
+pub async fn migrate(
+    session: &Session,
+    keyspace: &str,
+    replication: &str,
+) -> Result<i32, QueryError> {
+    let applied = applied_version(session, keyspace).await?;
+    if applied > 0 {
+        session.use_keyspace(keyspace, true).await?;
+    }
+
+    let mut version = applied;
+    for migration in MIGRATIONS
+        .iter()
+        .filter(|migration| migration.version > applied)
+    {
+        println!(
+            "Applying migration {} ({})",
+            migration.version, migration.name
+        );
+        for statement in migration.statements(keyspace, replication) {
+            println!("Query: {}", statement);
+            session.query(statement, ()).await?;
+        }
+        session.use_keyspace(keyspace, true).await?;
+
+        let query = format!(
+            "INSERT INTO {} (version, name, applied_at) VALUES (?, ?, ?);",
+            SCHEMA_MIGRATIONS_TABLE
+        );
+        session
+            .query(query, (migration.version, migration.name, Utc::now()))
+            .await?;
+        version = migration.version;
+    }
+
+    Ok(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_only_lowercase_keyspaces() {
+        assert!(is_valid_keyspace("xrpl_data_api"));
+        assert!(is_valid_keyspace("staging2"));
+        assert!(!is_valid_keyspace("Staging"));
+        assert!(!is_valid_keyspace("2staging"));
+        assert!(!is_valid_keyspace("staging-2"));
+        assert!(!is_valid_keyspace(&"a".repeat(49)));
+    }
+}
@@ -0,0 +1,213 @@
+use crate::handlers::api_key::get_api_key;
+use crate::models::api_key::{Tier, ANONYMOUS_TIER};
+use crate::utils::consts::{API_KEY_HEADER, API_KEY_QUERY_PARAM};
+use crate::utils::errors::map_error_to_status_code;
+use crate::utils::guardrails::is_streamed;
+use crate::utils::rate_limit::{RateLimitDecision, RouteCost};
+use crate::utils::version::ApiVersion;
+use crate::AppState;
+use axum::extract::{ConnectInfo, MatchedPath, Request, State};
+use axum::http::{StatusCode, Uri};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use chrono::{NaiveDate, Utc};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use subtle::ConstantTimeEq;
+use uuid::Uuid;
+
+/// Caller of a request, added to the request extensions by `authenticate`.
+#[derive(Debug, Clone)]
+pub struct Client {
+    // Requests made with the bootstrap admin key or without a key have no id
+    pub key_id: Option<Uuid>,
+    pub tier: &'static Tier,
+    // Rate limit bucket of the caller: the key id, "admin" or the IP address
+    pub bucket: String,
+}
+
+type UsageKey = (Uuid, NaiveDate, String);
+
+/// Counts requests per key, buffered until they are flushed to the usage table.
+#[derive(Default)]
+pub struct UsageTracker {
+    pending: Mutex<HashMap<UsageKey, i64>>,
+}
+
+impl UsageTracker {
+    pub fn record(&self, key_id: Uuid, route: &str) {
+        let key = (key_id, Utc::now().date_naive(), route.to_string());
+        let mut pending = self.pending.lock().unwrap_or_else(|err| err.into_inner());
+        *pending.entry(key).or_insert(0) += 1;
+    }
+
+    pub fn take_pending(&self) -> HashMap<UsageKey, i64> {
+        let mut pending = self.pending.lock().unwrap_or_else(|err| err.into_inner());
+        std::mem::take(&mut *pending)
+    }
+
+    /// Puts back counts that failed to flush, so they are retried with the next flush.
+    pub fn restore(&self, key: UsageKey, requests: i64) {
+        let mut pending = self.pending.lock().unwrap_or_else(|err| err.into_inner());
+        *pending.entry(key).or_insert(0) += requests;
+    }
+}
+
+/// Resolves the caller's tier from the `x-api-key` header or `api_key` query parameter and
+/// enforces its allowed routes and rate limit, where a request takes as many
+/// tokens as its route costs. Requests without a key get the
+/// anonymous tier and are rate limited by IP address.
+pub async fn authenticate(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    matched_path: MatchedPath,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let presented = presented_key(&request);
+    // Tiers and costs apply to a route in every version
+    let (_, route) = ApiVersion::split(matched_path.as_str());
+    // Limits above the tier's maximum are clamped by `enforce_limits`, except for streams
+    let limit = requested_limit(request.uri(), route);
+    let (client, decision) =
+        admit(&state, presented.as_deref(), address.ip(), route, limit).await?;
+    if !decision.allowed {
+        return Ok((StatusCode::TOO_MANY_REQUESTS, decision.headers()).into_response());
+    }
+
+    request.extensions_mut().insert(client);
+    let mut response = next.run(request).await;
+    response.headers_mut().extend(decision.headers());
+    Ok(response)
+}
+
+/// Resolves the caller of a request to `route` and takes the route's cost from its rate limit.
+/// Shared by the HTTP and gRPC servers, the returned decision is not allowed when the caller is
+/// rate limited.
+pub async fn admit(
+    state: &AppState,
+    presented: Option<&str>,
+    address: IpAddr,
+    route: &str,
+    limit: Option<i32>,
+) -> Result<(Client, RateLimitDecision), StatusCode> {
+    let client = match presented {
+        Some(key) if is_admin_key(state, key) => {
+            let tier = Tier::find("admin").ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+            Client {
+                key_id: None,
+                tier,
+                bucket: "admin".to_string(),
+            }
+        }
+        Some(key) => resolve_key(state, key).await?,
+        None => Client {
+            key_id: None,
+            tier: &ANONYMOUS_TIER,
+            bucket: address.to_string(),
+        },
+    };
+
+    if !client.tier.allows(route) {
+        println!("Tier {} may not access {}", client.tier.name, route);
+        // Anonymous callers may gain access with a key
+        return Err(if presented.is_none() {
+            StatusCode::UNAUTHORIZED
+        } else {
+            StatusCode::FORBIDDEN
+        });
+    }
+    let limit = limit.map(|limit| limit.min(client.tier.max_limit));
+    let cost = RouteCost::of(route, limit);
+    let decision = state
+        .rate_limiter
+        .acquire(&client.bucket, client.tier, cost);
+    if !decision.allowed {
+        println!("Client {} is rate limited on {}", client.bucket, route);
+    } else if let Some(key_id) = client.key_id {
+        state.usage_tracker.record(key_id, route);
+    }
+
+    Ok((client, decision))
+}
+
+// Compared in constant time like stored keys, both are hashed first so their lengths match
+fn is_admin_key(state: &AppState, key: &str) -> bool {
+    state.admin_key.as_deref().is_some_and(|admin_key| {
+        hash_secret(admin_key)
+            .as_bytes()
+            .ct_eq(hash_secret(key).as_bytes())
+            .into()
+    })
+}
+
+/// Hex encoded SHA-256 of the secret part of a key, as stored in the keys table.
+pub fn hash_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+fn presented_key(request: &Request) -> Option<String> {
+    if let Some(key) = request.headers().get(API_KEY_HEADER) {
+        return key.to_str().ok().map(|key| key.to_string());
+    }
+    let query = request.uri().query().unwrap_or_default();
+    serde_urlencoded::from_str::<Vec<(String, String)>>(query)
+        .ok()?
+        .into_iter()
+        .find(|(name, _)| name == API_KEY_QUERY_PARAM)
+        .map(|(_, key)| key)
+}
+
+// Keys are presented as `<id>.<secret>`
+async fn resolve_key(state: &AppState, key: &str) -> Result<Client, StatusCode> {
+    let (id, secret) = key.split_once('.').ok_or(StatusCode::UNAUTHORIZED)?;
+    let id = Uuid::parse_str(id).map_err(|_| StatusCode::UNAUTHORIZED)?;
+
+    let session = &state.scylla_session;
+    let api_key = state
+        .api_key_cache
+        .try_get_with(id, async {
+            get_api_key(session, id).await.map(|key| key.map(Arc::new))
+        })
+        .await;
+    let api_key = match api_key {
+        Ok(Some(api_key)) => api_key,
+        Ok(None) => return Err(StatusCode::UNAUTHORIZED),
+        Err(err) => {
+            eprintln!("{}", err);
+            return Err(map_error_to_status_code(&err));
+        }
+    };
+
+    // Compared in constant time, so response times do not reveal how much of the hash matched
+    let secret_matches: bool = api_key
+        .secret_hash
+        .as_bytes()
+        .ct_eq(hash_secret(secret).as_bytes())
+        .into();
+    if api_key.revoked_at.is_some() || !secret_matches {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    let tier = Tier::find(&api_key.tier).ok_or(StatusCode::UNAUTHORIZED)?;
+
+    Ok(Client {
+        key_id: Some(api_key.id),
+        tier,
+        bucket: api_key.id.to_string(),
+    })
+}
+
+fn requested_limit(uri: &Uri, route: &str) -> Option<i32> {
+    let query = uri.query().unwrap_or_default();
+    let params = serde_urlencoded::from_str::<Vec<(String, String)>>(query).ok()?;
+    // Rows of a streamed response are charged as they are read, see `RowThrottle`
+    if is_streamed(route, &params) {
+        return Some(1);
+    }
+    params
+        .into_iter()
+        .find(|(name, _)| name == "limit")
+        .and_then(|(_, limit)| limit.parse().ok())
+}
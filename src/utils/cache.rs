@@ -0,0 +1,130 @@
+use crate::utils::consts::{IMMUTABLE_CACHE_MAX_AGE_SECS, RESPONSE_CACHE_MAX_BYTES};
+use crate::utils::errors::DataApiError;
+use crate::utils::single_flight::SingleFlight;
+use axum::http::header::{CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_NONE_MATCH};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use bytes::Bytes;
+use moka::future::Cache;
+use moka::policy::EvictionPolicy;
+use sha2::{Digest, Sha256};
+use std::future::Future;
+use std::sync::Arc;
+
+/// Serialized response body with its strong entity tag.
+pub struct CachedResponse {
+    pub content_type: &'static str,
+    pub body: Bytes,
+    pub etag: String,
+    // Immutable responses are kept in the cache and may be stored by clients for good
+    pub immutable: bool,
+}
+
+/// Rendered responses of objects that never change once written, keyed by route and format.
+/// Misses are coalesced, so a burst of requests for the same object renders it only once.
+pub struct ResponseCache {
+    entries: Cache<String, Arc<CachedResponse>>,
+    flights: SingleFlight<Arc<CachedResponse>>,
+}
+
+impl ResponseCache {
+    pub fn new() -> Self {
+        let entries = Cache::builder()
+            .eviction_policy(EvictionPolicy::lru())
+            .weigher(|key: &String, response: &Arc<CachedResponse>| {
+                (key.len() + response.body.len())
+                    .try_into()
+                    .unwrap_or(u32::MAX)
+            })
+            .max_capacity(RESPONSE_CACHE_MAX_BYTES)
+            .build();
+        ResponseCache {
+            entries,
+            flights: SingleFlight::new(),
+        }
+    }
+
+    /// Serves a response from the cache, rendering it with `render` on a miss. Only immutable
+    /// responses are cached, so objects that may still change are rendered on every request.
+    pub async fn respond<F>(
+        &self,
+        key: String,
+        request_headers: &HeaderMap,
+        render: F,
+    ) -> Result<Response, Arc<DataApiError>>
+    where
+        F: Future<Output = Result<CachedResponse, DataApiError>> + Send + 'static,
+    {
+        if let Some(cached) = self.entries.get(&key).await {
+            return Ok(cached.to_response(request_headers));
+        }
+
+        let entries = self.entries.clone();
+        let cache_key = key.clone();
+        let rendered = self
+            .flights
+            .run(key, async move {
+                let rendered = Arc::new(render.await?);
+                if rendered.immutable {
+                    entries.insert(cache_key, rendered.clone()).await;
+                }
+                Ok(rendered)
+            })
+            .await?;
+
+        Ok(rendered.to_response(request_headers))
+    }
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CachedResponse {
+    pub fn new(content_type: &'static str, body: Vec<u8>, immutable: bool) -> Self {
+        let etag = format!("\"{}\"", hex::encode(Sha256::digest(&body)));
+        CachedResponse {
+            content_type,
+            body: Bytes::from(body),
+            etag,
+            immutable,
+        }
+    }
+
+    /// Answers with `304 Not Modified` when `If-None-Match` lists the entity tag.
+    pub fn to_response(&self, request_headers: &HeaderMap) -> Response {
+        let cache_control = if self.immutable {
+            format!(
+                "public, max-age={}, immutable",
+                IMMUTABLE_CACHE_MAX_AGE_SECS
+            )
+        } else {
+            "no-cache".to_string()
+        };
+        let mut headers = HeaderMap::new();
+        if let Ok(etag) = HeaderValue::from_str(&self.etag) {
+            headers.insert(ETAG, etag);
+        }
+        if let Ok(cache_control) = HeaderValue::from_str(&cache_control) {
+            headers.insert(CACHE_CONTROL, cache_control);
+        }
+
+        if self.matches(request_headers) {
+            return (StatusCode::NOT_MODIFIED, headers).into_response();
+        }
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static(self.content_type));
+        (headers, self.body.clone()).into_response()
+    }
+
+    fn matches(&self, request_headers: &HeaderMap) -> bool {
+        request_headers
+            .get_all(IF_NONE_MATCH)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|tag| tag.trim())
+            .any(|tag| tag == "*" || tag.trim_start_matches("W/") == self.etag)
+    }
+}
@@ -1,10 +1,144 @@
 pub static KEYSPACE: &str = "xrpl_data_api";
+// Keyspace the server reads instead of `KEYSPACE`, the binaries take a --keyspace argument
+pub static KEYSPACE_ENV: &str = "DATA_API_KEYSPACE";
+pub static SCYLLA_NODES: &[&str] = &["172.27.0.2", "172.27.0.3", "172.27.0.4"];
 pub static LEDGER_TABLE: &str = "ledgers";
 pub static ACCOUNTS_TABLE: &str = "accounts";
+pub static ACCOUNTS_PARENT_MV_TABLE: &str = "mv_account_children";
+pub static ACCOUNTS_LEDGER_MV_TABLE: &str = "mv_accounts_by_ledger";
 pub static DAILY_LEDGERS_TABLE: &str = "daily_ledgers";
 pub static TRANSACTIONS_TABLE: &str = "transactions";
 pub static PAYMENTS_TABLE: &str = "payments";
+pub static PAYMENTS_LEDGER_MV_TABLE: &str = "mv_payments_by_ledger";
+pub static PAYMENTS_DESTINATION_MV_TABLE: &str = "mv_payments_by_destination";
 pub static TRANSACTIONS_ACCOUNT_MV_TABLE: &str = "mv_account_transactions";
 pub static BALANCE_CHANGES_TABLE: &str = "balance_changes";
+pub static DAILY_STATS_TABLE: &str = "daily_stats";
+pub static BALANCE_CHANGES_TX_MV_TABLE: &str = "mv_balance_changes_by_tx";
+pub static EXCHANGE_PAIRS_TABLE: &str = "exchange_pairs";
+pub static API_KEYS_TABLE: &str = "api_keys";
+pub static API_KEY_USAGE_TABLE: &str = "api_key_usage";
+pub static WEBHOOKS_TABLE: &str = "webhooks";
+pub static WEBHOOK_DELIVERIES_TABLE: &str = "webhook_deliveries";
+pub static WEBHOOK_DELIVERY_ATTEMPTS_TABLE: &str = "webhook_delivery_attempts";
+pub static WEBHOOK_DISPATCH_CURSOR_TABLE: &str = "webhook_dispatch_cursor";
+pub static WEBHOOK_PENDING_DELIVERIES_TABLE: &str = "webhook_pending_deliveries";
 
 pub static DEFAULT_RESULT_LIMIT: i32 = 100;
+pub static STREAM_PAGE_SIZE: i32 = 1000;
+// Rows a request may read from scylla while aggregating or streaming a partition
+pub static MAX_SCANNED_ROWS: usize = 100_000;
+pub static QUERY_TIMEOUT_SECS: u64 = 10;
+
+pub static GENESIS_ACCOUNT: &str = "rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh";
+pub static MAX_ANCESTRY_DEPTH: usize = 1000;
+
+// Activity and balances of the summary are read from the ledgers closed in this many days
+pub static ACCOUNT_SUMMARY_WINDOW_DAYS: i64 = 90;
+pub static ACCOUNT_SUMMARY_CACHE_TTL_SECS: u64 = 30;
+pub static ACCOUNT_SUMMARY_CACHE_CAPACITY: u64 = 10_000;
+
+pub static EXCHANGE_CHANGE_TYPE: &str = "exchange";
+pub static EXCHANGE_LOOKUP_CONCURRENCY: usize = 16;
+pub static XRP_CURRENCY: &str = "XRP";
+
+pub static DEFAULT_CANDLE_COUNT: i64 = 100;
+pub static MAX_CANDLE_COUNT: i64 = 1000;
+pub static CANDLE_EXCHANGES_PAGE_SIZE: i32 = 1000;
+pub static CANDLE_CACHE_CAPACITY: u64 = 100_000;
+// Cached buckets are rebuilt after this long, so one built before late trades, such as a
+// backfilled gap, were ingested is not served forever
+pub static CANDLE_CACHE_TTL_SECS: u64 = 3600;
+// Buckets that closed less than this long ago may still receive trades from ledgers being ingested
+pub static CANDLE_FINALITY_GRACE_SECS: i64 = 60;
+
+pub static DAILY_STATS_LEDGER_CONCURRENCY: usize = 16;
+// Rows the daily stats worker may read across all ledgers of a day. Days are persisted once
+// computed, so this covers a busy day's transactions, payments and new accounts
+pub static DAILY_STATS_MAX_SCANNED_ROWS: usize = 10_000_000;
+// Today's and yesterday's stats are recomputed this often, until they are persisted
+pub static DAILY_STATS_INTERVAL_SECS: u64 = 300;
+// Stats of days that are not persisted yet are served from memory for this long
+pub static DAILY_STATS_CACHE_TTL_SECS: u64 = 900;
+pub static DAILY_STATS_CACHE_CAPACITY: u64 = 1_000;
+// Days are only treated as final once ingestion had this long to catch up after midnight
+pub static DAY_FINALITY_GRACE_SECS: i64 = 60 * 60;
+
+pub static LEDGER_FEED_POLL_INTERVAL_MS: u64 = 1000;
+// Ledgers a subscriber may fall behind by before it is disconnected
+pub static LEDGER_FEED_CLIENT_BUFFER: usize = 64;
+pub static LEDGER_FEED_MAX_CATCH_UP: i64 = 100;
+
+pub static SUBSCRIPTION_MAX_ACCOUNTS: usize = 100;
+// Subscriptions resume at most this many ledgers back, and catch up queries span at most as many
+pub static SUBSCRIPTION_CATCH_UP_LEDGERS: i64 = 10_000;
+
+pub static WEBHOOK_MAX_ATTEMPTS: i32 = 8;
+// Retries wait this long, doubling after every failed attempt
+pub static WEBHOOK_RETRY_BASE_SECS: u64 = 5;
+pub static WEBHOOK_REQUEST_TIMEOUT_SECS: u64 = 10;
+pub static WEBHOOK_MAX_ACCOUNTS: usize = 1000;
+pub static WEBHOOK_SIGNATURE_HEADER: &str = "x-webhook-signature";
+pub static WEBHOOK_DELIVERY_HEADER: &str = "x-webhook-delivery";
+// Name of the dispatcher's row in the cursor table
+pub static WEBHOOK_DISPATCH_CURSOR: &str = "dispatcher";
+// Deleting a webhook reads its whole delivery history, so it gets a larger budget than requests
+pub static WEBHOOK_DELETE_MAX_SCANNED_ROWS: usize = 10_000_000;
+
+pub static API_KEY_HEADER: &str = "x-api-key";
+// Browsers can not set headers on WebSocket and EventSource requests
+pub static API_KEY_QUERY_PARAM: &str = "api_key";
+// Bootstrap key with the admin tier, for creating the first stored keys
+pub static ADMIN_KEY_ENV: &str = "DATA_API_ADMIN_KEY";
+// RFC 3339 times announced in the deprecation headers of the root paths
+pub static ROOT_DEPRECATED_AT_ENV: &str = "DATA_API_ROOT_DEPRECATED_AT";
+pub static ROOT_SUNSET_AT_ENV: &str = "DATA_API_ROOT_SUNSET_AT";
+// Revoked keys keep working for at most this long on each instance
+pub static API_KEY_CACHE_TTL_SECS: u64 = 60;
+pub static API_KEY_CACHE_CAPACITY: u64 = 100_000;
+pub static USAGE_FLUSH_INTERVAL_SECS: u64 = 30;
+// how often idle rate limit buckets are forgotten
+pub static RATE_LIMIT_PRUNE_INTERVAL_SECS: u64 = 60;
+// Longest a streamed row waits for the client's bucket to refill before the stream fails
+pub static ROW_THROTTLE_MAX_WAIT_SECS: u64 = 30;
+
+// Bounds the total size of cached response bodies
+pub static RESPONSE_CACHE_MAX_BYTES: u64 = 256 * 1024 * 1024;
+pub static IMMUTABLE_CACHE_MAX_AGE_SECS: u64 = 365 * 24 * 60 * 60;
+
+pub static MARKER_HEADER: &str = "x-next-marker";
+// Set when the requested limit was above the caller's maximum
+pub static REQUESTED_LIMIT_HEADER: &str = "x-requested-limit";
+pub static APPLIED_LIMIT_HEADER: &str = "x-applied-limit";
+// False when an account's ancestry could not be followed up to its root
+pub static ANCESTRY_COMPLETE_HEADER: &str = "x-ancestry-complete";
+
+// Distinct keys of a GraphQL batch looked up at once
+pub static GRAPHQL_LOOKUP_CONCURRENCY: usize = 16;
+pub static GRAPHQL_MAX_DEPTH: usize = 8;
+pub static GRAPHQL_MAX_COMPLEXITY: usize = 1000;
+// Estimated size of per-ledger lists, which take no limit
+pub const GRAPHQL_LEDGER_LIST_COMPLEXITY: usize = 100;
+
+// Identifiers per batch request, lowered to the caller's maximum limit
+pub static BATCH_MAX_IDS: usize = 1000;
+pub static BATCH_CONCURRENCY: usize = 16;
+// Batches take a token for every this many identifiers, on top of the route's cost
+pub static BATCH_IDS_PER_TOKEN: usize = 10;
+
+// rippled times are seconds since 2000-01-01
+pub static RIPPLE_EPOCH_OFFSET: i64 = 946_684_800;
+pub static JSON_RPC_DEFAULT_LIMIT: i32 = 200;
+
+// Data API v2 defaults to 200 results
+pub static LEGACY_DEFAULT_LIMIT: i32 = 200;
+pub static LEGACY_LOOKUP_CONCURRENCY: usize = 16;
+
+// Ledgers looked up per gap scan query, and queries run at once
+pub static GAP_SCAN_CHUNK: usize = 100;
+pub static GAP_SCAN_CONCURRENCY: usize = 16;
+// Ledgers a single /admin/gaps request may scan, the gaps binary has no limit
+pub static GAP_SCAN_MAX_LEDGERS: i64 = 100_000;
+// The gaps metric covers this many of the latest ledgers, rescanned every interval
+pub static GAP_METRIC_LEDGERS: i64 = 100_000;
+pub static GAP_METRIC_INTERVAL_SECS: u64 = 600;
@@ -0,0 +1,216 @@
+use num_bigint::{BigInt, Sign};
+use serde::{Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+
+// Significant digits kept by a division, as many as a token amount has
+const QUOTIENT_DIGITS: u32 = 16;
+
+/// An exact decimal, `mantissa * 10^exponent`. Token values are up to 16 significant digits
+/// with exponents far outside what floats keep exact, so amounts are added and compared as
+/// these. Serialized as a string.
+#[derive(Clone, Debug)]
+pub struct Decimal {
+    mantissa: BigInt,
+    exponent: i32,
+}
+
+impl Decimal {
+    pub fn new(mantissa: impl Into<BigInt>, exponent: i32) -> Self {
+        Decimal {
+            mantissa: mantissa.into(),
+            exponent,
+        }
+    }
+
+    pub fn zero() -> Self {
+        Decimal::new(0, 0)
+    }
+
+    /// Parses plain and scientific notation, like rippled's "1.5" and "1234e-30".
+    pub fn parse(value: &str) -> Option<Self> {
+        let (negative, value) = match value.strip_prefix('-') {
+            Some(value) => (true, value),
+            None => (false, value),
+        };
+        let (number, exponent) = match value.split_once(['e', 'E']) {
+            Some((number, exponent)) => (number, exponent.parse::<i32>().ok()?),
+            None => (value, 0),
+        };
+        let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+        let digits = format!("{}{}", integer, fraction);
+        if digits.is_empty() || !digits.bytes().all(|digit| digit.is_ascii_digit()) {
+            return None;
+        }
+        let mantissa = BigInt::parse_bytes(digits.as_bytes(), 10)?;
+        let mantissa = if negative { -mantissa } else { mantissa };
+        Some(Decimal::new(mantissa, exponent - fraction.len() as i32))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa.sign() == Sign::NoSign
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.mantissa.sign() == Sign::Minus
+    }
+
+    pub fn neg(&self) -> Self {
+        Decimal::new(-self.mantissa.clone(), self.exponent)
+    }
+
+    pub fn abs(&self) -> Self {
+        if self.is_negative() {
+            self.neg()
+        } else {
+            self.clone()
+        }
+    }
+
+    pub fn add(&self, other: &Decimal) -> Self {
+        let exponent = self.exponent.min(other.exponent);
+        Decimal::new(self.rescaled(exponent) + other.rescaled(exponent), exponent)
+    }
+
+    /// Quotient truncated to 16 significant digits, `None` when dividing by zero.
+    pub fn div(&self, other: &Decimal) -> Option<Self> {
+        if other.is_zero() {
+            return None;
+        }
+        // Scales the dividend so the integer quotient has at least the kept digits
+        let digits = |mantissa: &BigInt| mantissa.magnitude().to_string().len() as i64;
+        let shift = (QUOTIENT_DIGITS as i64 + digits(&other.mantissa) - digits(&self.mantissa))
+            .max(0) as u32;
+        let dividend = &self.mantissa * BigInt::from(10).pow(shift);
+        let quotient = dividend / &other.mantissa;
+        // It has a digit more when the dividend's leading digits are larger, and any number of
+        // them when the dividend alone has more than the kept digits
+        let excess = (digits(&quotient) - QUOTIENT_DIGITS as i64).max(0);
+        let quotient = quotient / BigInt::from(10).pow(excess as u32);
+        let exponent = self.exponent as i64 - other.exponent as i64 - shift as i64 + excess;
+        Some(Decimal::new(quotient, i32::try_from(exponent).ok()?))
+    }
+
+    // Mantissa for a lower or equal exponent
+    fn rescaled(&self, exponent: i32) -> BigInt {
+        let shift = (self.exponent - exponent) as u32;
+        &self.mantissa * BigInt::from(10).pow(shift)
+    }
+}
+
+// Equal values compare equal whatever their exponents, 1.5 is 1.50
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let exponent = self.exponent.min(other.exponent);
+        self.rescaled(exponent).cmp(&other.rescaled(exponent))
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.is_negative() { "-" } else { "" };
+        let digits = self.mantissa.magnitude().to_string();
+        if self.exponent >= 0 {
+            let zeros = if self.is_zero() { 0 } else { self.exponent };
+            return write!(f, "{}{}{}", sign, digits, "0".repeat(zeros as usize));
+        }
+
+        let scale = self.exponent.unsigned_abs() as usize;
+        let digits = if digits.len() <= scale {
+            format!("{}{}", "0".repeat(scale + 1 - digits.len()), digits)
+        } else {
+            digits
+        };
+        let (integer, fraction) = digits.split_at(digits.len() - scale);
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.is_empty() {
+            write!(f, "{}{}", sign, integer)
+        } else {
+            write!(f, "{}{}.{}", sign, integer, fraction)
+        }
+    }
+}
+
+impl Serialize for Decimal {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Decimal;
+
+    fn parse(value: &str) -> String {
+        Decimal::parse(value).unwrap().to_string()
+    }
+
+    #[test]
+    fn parses_plain_and_scientific_notation() {
+        assert_eq!(parse("1.5"), "1.5");
+        assert_eq!(parse("-0.25"), "-0.25");
+        assert_eq!(parse("1.50"), "1.5");
+        assert_eq!(parse("1E3"), "1000");
+        assert_eq!(parse("1234e-30"), "0.000000000000000000000000001234");
+        assert_eq!(
+            parse("9999999999999999e80"),
+            format!("9999999999999999{}", "0".repeat(80))
+        );
+        assert_eq!(parse("-0"), "0");
+        for invalid in ["", "-", ".", "1.2.3", "1e", "abc", "1,5", "+1"] {
+            assert!(Decimal::parse(invalid).is_none(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn displays_without_trailing_zeros() {
+        assert_eq!(Decimal::new(15, -1).to_string(), "1.5");
+        assert_eq!(Decimal::new(100, -2).to_string(), "1");
+        assert_eq!(Decimal::new(5, -3).to_string(), "0.005");
+        assert_eq!(Decimal::new(-5, -3).to_string(), "-0.005");
+        assert_eq!(Decimal::new(12, 2).to_string(), "1200");
+        assert_eq!(Decimal::new(0, 5).to_string(), "0");
+        assert_eq!(Decimal::new(0, -3).to_string(), "0");
+    }
+
+    #[test]
+    fn compares_and_computes_exactly() {
+        let parse = |value| Decimal::parse(value).unwrap();
+        assert_eq!(parse("1.5"), parse("1.50"));
+        assert!(parse("-2") < parse("1e-30"));
+        assert_eq!(parse("0.1").add(&parse("0.2")).to_string(), "0.3");
+        assert_eq!(
+            parse("1").div(&parse("3")).unwrap().to_string(),
+            "0.3333333333333333"
+        );
+        assert_eq!(
+            parse("99").div(&parse("7")).unwrap().to_string(),
+            "14.14285714285714"
+        );
+        assert_eq!(
+            parse("-12345678901234567890")
+                .div(&parse("1"))
+                .unwrap()
+                .to_string(),
+            "-12345678901234560000"
+        );
+        assert!(parse("1").div(&Decimal::zero()).is_none());
+    }
+}
@@ -1,5 +1,5 @@
 use axum::http::StatusCode;
-use scylla::transport::errors::QueryError;
+use scylla::transport::errors::{DbError, QueryError};
 use scylla::transport::query_result::SingleRowTypedError;
 use thiserror::Error;
 
@@ -13,12 +13,53 @@ pub enum DataApiError {
 
     #[error("no data returned from query")]
     NoDataReturned,
+
+    #[error("invalid marker ({0})")]
+    InvalidMarker(String),
+
+    #[error("invalid parameter ({0})")]
+    InvalidParameter(String),
+
+    #[error("serializing data failed ({0})")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("writing csv failed ({0})")]
+    CsvSerialization(#[from] csv::Error),
+
+    #[error("query task failed ({0})")]
+    TaskFailed(String),
+
+    #[error("request scans more than {0} rows")]
+    ScanLimitExceeded(usize),
+
+    #[error("invalid amount ({0})")]
+    InvalidAmount(String),
+
+    #[error("conflicting request ({0})")]
+    Conflict(String),
+
+    #[error("rate limit exceeded, retry after {0} seconds")]
+    RateLimited(u64),
 }
 
 pub fn map_error_to_status_code(err: &DataApiError) -> StatusCode {
     match err {
         DataApiError::RowTypedCast(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        DataApiError::QueryFailed(
+            QueryError::RequestTimeout(_)
+            | QueryError::TimeoutError
+            | QueryError::DbError(DbError::ReadTimeout { .. } | DbError::WriteTimeout { .. }, _),
+        ) => StatusCode::GATEWAY_TIMEOUT,
         DataApiError::QueryFailed(_) => StatusCode::INTERNAL_SERVER_ERROR,
         DataApiError::NoDataReturned => StatusCode::NOT_FOUND,
+        DataApiError::InvalidMarker(_) => StatusCode::BAD_REQUEST,
+        DataApiError::InvalidParameter(_) => StatusCode::BAD_REQUEST,
+        DataApiError::Serialization(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        DataApiError::CsvSerialization(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        DataApiError::TaskFailed(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        DataApiError::ScanLimitExceeded(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        DataApiError::InvalidAmount(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        DataApiError::Conflict(_) => StatusCode::CONFLICT,
+        DataApiError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
     }
 }
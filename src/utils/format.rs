@@ -0,0 +1,138 @@
+use crate::utils::consts::MARKER_HEADER;
+use crate::utils::errors::{map_error_to_status_code, DataApiError};
+use crate::utils::pagination::Paginated;
+use axum::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::header::{ACCEPT, CONTENT_TYPE};
+use axum::http::request::Parts;
+use axum::http::{HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+pub static JSON_CONTENT_TYPE: &str = "application/json";
+pub static CSV_CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+pub static NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// Output format of list endpoints, picked from the `format` query parameter or the `Accept`
+/// header, in that order. Defaults to a JSON array.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Csv,
+    Ndjson,
+}
+
+#[derive(Deserialize)]
+struct FormatParam {
+    format: Option<OutputFormat>,
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for OutputFormat {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let query = parts.uri.query().unwrap_or_default();
+        match serde_urlencoded::from_str::<FormatParam>(query) {
+            Ok(FormatParam {
+                format: Some(format),
+            }) => return Ok(format),
+            Ok(FormatParam { format: None }) => {}
+            Err(err) => {
+                println!("Failed to parse format: {}", err);
+                return Err(StatusCode::BAD_REQUEST);
+            }
+        }
+
+        let accept = parts
+            .headers
+            .get(ACCEPT)
+            .and_then(|accept| accept.to_str().ok())
+            .unwrap_or_default();
+        if accept.contains("text/csv") {
+            Ok(OutputFormat::Csv)
+        } else if accept.contains("application/x-ndjson") || accept.contains("application/ndjson") {
+            Ok(OutputFormat::Ndjson)
+        } else {
+            Ok(OutputFormat::Json)
+        }
+    }
+}
+
+/// List response rendered in the negotiated output format. CSV columns follow the field order
+/// of the model's `Serialize` implementation, so big integers are written as strings.
+pub struct Formatted<T> {
+    pub format: OutputFormat,
+    pub items: Vec<T>,
+    pub marker: Option<String>,
+}
+
+impl<T> Formatted<T> {
+    pub fn new(format: OutputFormat, items: Vec<T>) -> Self {
+        Formatted {
+            format,
+            items,
+            marker: None,
+        }
+    }
+
+    pub fn page(format: OutputFormat, page: Paginated<T>) -> Self {
+        Formatted {
+            format,
+            items: page.items,
+            marker: page.marker,
+        }
+    }
+}
+
+impl<T: Serialize> IntoResponse for Formatted<T> {
+    fn into_response(self) -> Response {
+        let mut response = match render(self.format, &self.items) {
+            Ok((content_type, body)) => ([(CONTENT_TYPE, content_type)], body).into_response(),
+            Err(err) => {
+                eprintln!("{}", err);
+                map_error_to_status_code(&err).into_response()
+            }
+        };
+        if let Some(marker) = self.marker {
+            if let Ok(value) = HeaderValue::from_str(&marker) {
+                response.headers_mut().insert(MARKER_HEADER, value);
+            }
+        }
+        response
+    }
+}
+
+/// Serializes items in the given format, returning the content type and the body.
+pub fn render<T: Serialize>(
+    format: OutputFormat,
+    items: &[T],
+) -> Result<(&'static str, Vec<u8>), DataApiError> {
+    match format {
+        OutputFormat::Json => Ok((JSON_CONTENT_TYPE, serde_json::to_vec(items)?)),
+        OutputFormat::Ndjson => Ok((NDJSON_CONTENT_TYPE, to_ndjson(items)?)),
+        OutputFormat::Csv => Ok((CSV_CONTENT_TYPE, to_csv(items)?)),
+    }
+}
+
+fn to_ndjson<T: Serialize>(items: &[T]) -> Result<Vec<u8>, serde_json::Error> {
+    let mut body = Vec::new();
+    for item in items {
+        serde_json::to_writer(&mut body, item)?;
+        body.push(b'\n');
+    }
+    Ok(body)
+}
+
+fn to_csv<T: Serialize>(items: &[T]) -> Result<Vec<u8>, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for item in items {
+        writer.serialize(item)?;
+    }
+    writer
+        .into_inner()
+        .map_err(|err| csv::Error::from(err.into_error()))
+}
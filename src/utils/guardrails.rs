@@ -0,0 +1,108 @@
+use crate::utils::auth::Client;
+use crate::utils::consts::{APPLIED_LIMIT_HEADER, REQUESTED_LIMIT_HEADER};
+use crate::utils::rate_limit::{RouteCost, RowThrottle};
+use crate::utils::rows::ScanBudget;
+use crate::utils::version::ApiVersion;
+use crate::AppState;
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::{HeaderValue, StatusCode, Uri};
+use axum::middleware::Next;
+use axum::response::Response;
+use axum::Extension;
+use std::sync::Arc;
+
+// Routes whose handlers stream their rows with `stream=true`, other routes ignore the flag
+static STREAMED_ROUTES: &[&str] = &[
+    "/transaction/account/:account",
+    "/account/:account/balance_changes",
+    "/account/:account/payments",
+];
+
+/// Whether a request to `route` with the query `params` gets a streamed response.
+pub fn is_streamed(route: &str, params: &[(String, String)]) -> bool {
+    STREAMED_ROUTES.contains(&route)
+        && params
+            .iter()
+            .any(|(name, value)| name == "stream" && value == "true")
+}
+
+/// Scan budget of a streamed response to `route`, whose rows are charged to the client's rate
+/// limit as they are read. Shared by the HTTP and gRPC servers.
+pub fn streamed_budget(state: &AppState, client: &Client, route: &str) -> ScanBudget {
+    ScanBudget::streamed(RowThrottle::new(
+        state.rate_limiter.clone(),
+        client.bucket.clone(),
+        client.tier,
+        RouteCost::rows_per_token(route),
+    ))
+}
+
+/// Validates the `limit` query parameter before it reaches a handler. Limits that are not
+/// positive are rejected and limits above the caller's tier maximum are clamped, which is
+/// reported in the `x-requested-limit` and `x-applied-limit` headers. Every request gets a
+/// single scan budget, shared by all the queries its handler runs. Responses of the routes
+/// that stream page through the partition with bounded memory, so their limit is neither
+/// required nor clamped and their rows are charged to the client's rate limit as they are
+/// read instead.
+pub async fn enforce_limits(
+    State(state): State<Arc<AppState>>,
+    Extension(client): Extension<Client>,
+    matched_path: MatchedPath,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let mut params = serde_urlencoded::from_str::<Vec<(String, String)>>(
+        request.uri().query().unwrap_or_default(),
+    )
+    .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    let (_, route) = ApiVersion::split(matched_path.as_str());
+    let streamed = is_streamed(route, &params);
+    let budget = if streamed {
+        streamed_budget(&state, &client, route)
+    } else {
+        ScanBudget::default()
+    };
+    request.extensions_mut().insert(budget);
+
+    let Some(position) = params.iter().position(|(name, _)| name == "limit") else {
+        return Ok(next.run(request).await);
+    };
+
+    let requested = match params[position].1.parse::<i32>() {
+        Ok(limit) if limit > 0 => limit,
+        _ => {
+            println!("Invalid limit {}", params[position].1);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    };
+    if streamed || requested <= client.tier.max_limit {
+        return Ok(next.run(request).await);
+    }
+
+    let applied = client.tier.max_limit;
+    println!(
+        "Clamped limit {} to the maximum {} of tier {}",
+        requested, applied, client.tier.name
+    );
+    params[position].1 = applied.to_string();
+    *request.uri_mut() = with_query(request.uri(), &params)?;
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert(REQUESTED_LIMIT_HEADER, HeaderValue::from(requested));
+    headers.insert(APPLIED_LIMIT_HEADER, HeaderValue::from(applied));
+    Ok(response)
+}
+
+fn with_query(uri: &Uri, params: &[(String, String)]) -> Result<Uri, StatusCode> {
+    let query = serde_urlencoded::to_string(params).map_err(|_| StatusCode::BAD_REQUEST)?;
+    let path_and_query = format!("{}?{}", uri.path(), query);
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(
+        path_and_query
+            .parse()
+            .map_err(|_| StatusCode::BAD_REQUEST)?,
+    );
+    Uri::from_parts(parts).map_err(|_| StatusCode::BAD_REQUEST)
+}
@@ -0,0 +1,83 @@
+use crate::gaps::LedgerGaps;
+use chrono::{DateTime, Utc};
+use std::fmt::Write;
+use std::sync::RwLock;
+
+static METRIC_PREFIX: &str = "xrpl_data_api";
+
+/// Values served at `/metrics` in the Prometheus text format.
+#[derive(Default)]
+pub struct Metrics {
+    gap_scan: RwLock<Option<GapScan>>,
+}
+
+// Outcome of the latest periodic gap scan
+struct GapScan {
+    missing: usize,
+    unprocessed: usize,
+    unindexed: usize,
+    ledgers: i64,
+    scanned_at: DateTime<Utc>,
+}
+
+impl Metrics {
+    pub fn record_gaps(&self, gaps: &LedgerGaps) {
+        let scan = GapScan {
+            missing: gaps.missing.len(),
+            unprocessed: gaps.unprocessed.len(),
+            unindexed: gaps.unindexed.len(),
+            ledgers: gaps.ledger_count(),
+            scanned_at: Utc::now(),
+        };
+        *self.gap_scan.write().unwrap_or_else(|err| err.into_inner()) = Some(scan);
+    }
+
+    /// Every metric in the text exposition format. Gap metrics are left out until the first
+    /// scan completes, so they are absent rather than zero after a restart.
+    pub fn render(&self) -> String {
+        let mut body = String::new();
+        let gap_scan = self.gap_scan.read().unwrap_or_else(|err| err.into_inner());
+        if let Some(scan) = gap_scan.as_ref() {
+            gauge(
+                &mut body,
+                "ledger_gaps",
+                "Ranges of missing, unprocessed or unindexed ledgers among the latest ledgers",
+                &[
+                    ("missing", scan.missing as i64),
+                    ("unprocessed", scan.unprocessed as i64),
+                    ("unindexed", scan.unindexed as i64),
+                ],
+            );
+            gauge(
+                &mut body,
+                "ledger_gap_ledgers",
+                "Ledgers within the gap ranges",
+                &[("", scan.ledgers)],
+            );
+            gauge(
+                &mut body,
+                "ledger_gap_scan_timestamp_seconds",
+                "Time the latest gap scan completed",
+                &[("", scan.scanned_at.timestamp())],
+            );
+        }
+        body
+    }
+}
+
+// Values are labelled with their kind, unless it is empty
+fn gauge(body: &mut String, name: &str, help: &str, values: &[(&str, i64)]) {
+    // Writing to a String does not fail
+    let _ = writeln!(body, "# HELP {}_{} {}", METRIC_PREFIX, name, help);
+    let _ = writeln!(body, "# TYPE {}_{} gauge", METRIC_PREFIX, name);
+    for (kind, value) in values {
+        let _ = match kind.is_empty() {
+            true => writeln!(body, "{}_{} {}", METRIC_PREFIX, name, value),
+            false => writeln!(
+                body,
+                "{}_{}{{kind=\"{}\"}} {}",
+                METRIC_PREFIX, name, kind, value
+            ),
+        };
+    }
+}
@@ -1,3 +1,15 @@
+pub mod auth;
+pub mod cache;
 pub mod consts;
+pub mod decimal;
 pub mod errors;
+pub mod format;
+pub mod guardrails;
+pub mod metrics;
+pub mod pagination;
 pub mod params;
+pub mod rate_limit;
+pub mod rows;
+pub mod single_flight;
+pub mod stream;
+pub mod version;
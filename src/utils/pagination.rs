@@ -0,0 +1,56 @@
+use crate::utils::errors::DataApiError;
+use bytes::Bytes;
+use num_bigint::BigInt;
+
+/// A single page of results together with the marker of the next page, if any.
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    pub marker: Option<String>,
+}
+
+/// Encodes a scylla paging state as a hex marker that can be passed back by clients.
+pub fn encode_marker(paging_state: Option<Bytes>) -> Option<String> {
+    paging_state.map(|state| state.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Decodes a marker previously returned by `encode_marker` back into a scylla paging state.
+pub fn decode_marker(marker: Option<&str>) -> Result<Option<Bytes>, DataApiError> {
+    let marker = match marker {
+        Some(marker) if !marker.is_empty() => marker,
+        _ => return Ok(None),
+    };
+    if !marker.is_ascii() || marker.len() % 2 != 0 {
+        return Err(DataApiError::InvalidMarker(marker.to_string()));
+    }
+    let paging_state = (0..marker.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&marker[i..i + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|_| DataApiError::InvalidMarker(marker.to_string()))?;
+
+    Ok(Some(Bytes::from(paging_state)))
+}
+
+/// Encodes the (ledger index, transaction index) of the last row of a page as a marker, for
+/// pages merged from several queries that share no paging state.
+pub fn encode_position((ledger_index, tx_index): &(i64, BigInt)) -> String {
+    format!("{}.{}", ledger_index, tx_index)
+}
+
+/// Decodes a marker previously returned by `encode_position`.
+pub fn decode_position(marker: Option<&str>) -> Result<Option<(i64, BigInt)>, DataApiError> {
+    let marker = match marker {
+        Some(marker) if !marker.is_empty() => marker,
+        _ => return Ok(None),
+    };
+    let position = marker.split_once('.').and_then(|(ledger_index, tx_index)| {
+        Some((
+            ledger_index.parse::<i64>().ok()?,
+            tx_index.parse::<BigInt>().ok()?,
+        ))
+    });
+    match position {
+        Some(position) => Ok(Some(position)),
+        None => Err(DataApiError::InvalidMarker(marker.to_string())),
+    }
+}
@@ -1,7 +1,119 @@
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct DataApiQueryParams {
+    /// Maximum number of results, clamped to the caller's tier maximum
     pub limit: Option<i32>,
-    pub ledger_index: Option<u32>,
+    /// Value of the `x-next-marker` header of the previous page
+    pub marker: Option<String>,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    /// Stream all rows, or up to `limit`, instead of returning a page
+    pub stream: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, ToSchema)]
+pub enum CandleInterval {
+    #[serde(rename = "1m")]
+    OneMinute,
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "1h")]
+    OneHour,
+    #[serde(rename = "1d")]
+    OneDay,
+}
+
+impl CandleInterval {
+    pub fn seconds(&self) -> i64 {
+        match self {
+            CandleInterval::OneMinute => 60,
+            CandleInterval::FiveMinutes => 5 * 60,
+            CandleInterval::OneHour => 60 * 60,
+            CandleInterval::OneDay => 24 * 60 * 60,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CandleQueryParams {
+    pub interval: CandleInterval,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+/// Query parameters of the Data API v2 compatible payments route.
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LegacyPaymentsParams {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    /// `sent` or `received`, both when absent
+    #[serde(rename = "type")]
+    pub payment_type: Option<LegacyPaymentType>,
+    /// Currency delivered to the destination
+    pub currency: Option<String>,
+    pub issuer: Option<String>,
+    pub source_tag: Option<i64>,
+    pub destination_tag: Option<i64>,
+    pub descending: Option<bool>,
+    pub limit: Option<i32>,
+    /// `marker` of the previous page
+    pub marker: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LegacyPaymentType {
+    Sent,
+    Received,
+}
+
+/// Query parameters of the Data API v2 compatible balance changes route.
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LegacyBalanceChangesParams {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    pub currency: Option<String>,
+    pub counterparty: Option<String>,
+    pub change_type: Option<String>,
+    pub descending: Option<bool>,
+    pub limit: Option<i32>,
+    /// `marker` of the previous page
+    pub marker: Option<String>,
+}
+
+/// Query parameters of the Data API v2 compatible ledger route.
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LegacyLedgerParams {
+    /// Include the hashes of the ledger's transactions
+    pub transactions: Option<bool>,
+    /// Include the transactions themselves, implies `transactions`
+    pub expand: Option<bool>,
+    /// Not supported, transactions are stored as JSON
+    pub binary: Option<bool>,
+}
+
+/// Query parameters of the Data API v2 compatible transaction route.
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LegacyTransactionParams {
+    /// Not supported, transactions are stored as JSON
+    pub binary: Option<bool>,
+}
+
+/// Query parameters of the ledger gap scan.
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GapsParams {
+    /// First ledger to scan, defaults to as many ledgers before `end` as a request may scan
+    pub start: Option<i64>,
+    /// Last ledger to scan, defaults to the latest ledger
+    pub end: Option<i64>,
 }
@@ -0,0 +1,383 @@
+use crate::models::api_key::Tier;
+use crate::utils::consts::{
+    DEFAULT_RESULT_LIMIT, RATE_LIMIT_PRUNE_INTERVAL_SECS, ROW_THROTTLE_MAX_WAIT_SECS,
+};
+use crate::utils::errors::DataApiError;
+use axum::http::{HeaderMap, HeaderValue};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Token cost of a route. Routes returning a page of rows additionally cost a token per
+/// `rows_per_token` requested rows, so a big `limit` drains the bucket faster.
+pub struct RouteCost {
+    pub route: &'static str,
+    pub base: u32,
+    pub rows_per_token: Option<u32>,
+}
+
+// Routes not listed cost a single token
+static DEFAULT_ROUTE_COST: u32 = 1;
+
+pub static ROUTE_COSTS: &[RouteCost] = &[
+    RouteCost {
+        route: "/transaction/ledger/:ledger_index",
+        base: 2,
+        rows_per_token: None,
+    },
+    RouteCost {
+        route: "/transaction/account/:account",
+        base: 1,
+        rows_per_token: Some(100),
+    },
+    RouteCost {
+        route: "/account/:account/summary",
+        base: 5,
+        rows_per_token: None,
+    },
+    RouteCost {
+        route: "/account/:account/ancestry",
+        base: 3,
+        rows_per_token: None,
+    },
+    RouteCost {
+        route: "/account/:account/balance_changes",
+        base: 1,
+        rows_per_token: Some(100),
+    },
+    RouteCost {
+        route: "/account/:account/exchanges",
+        base: 1,
+        rows_per_token: Some(100),
+    },
+    RouteCost {
+        route: "/account/:account/payments",
+        base: 1,
+        rows_per_token: Some(100),
+    },
+    RouteCost {
+        route: "/exchanges/:base/:counter",
+        base: 1,
+        rows_per_token: Some(100),
+    },
+    RouteCost {
+        route: "/exchanges/:base/:counter/candles",
+        base: 3,
+        rows_per_token: None,
+    },
+    // Days not computed by the stats worker yet are read in full
+    RouteCost {
+        route: "/stats/daily/:date",
+        base: 20,
+        rows_per_token: None,
+    },
+    // JSON-RPC methods are charged alike, account_tx pages are clamped to the tier's maximum
+    RouteCost {
+        route: "/",
+        base: 2,
+        rows_per_token: None,
+    },
+    // One query can resolve many relations, bounded by the complexity limit
+    RouteCost {
+        route: "/graphql",
+        base: 5,
+        rows_per_token: None,
+    },
+    // Ledgers may include all of their transactions
+    RouteCost {
+        route: "/legacy/v2/ledgers/:ledger_identifier",
+        base: 2,
+        rows_per_token: None,
+    },
+    // Balance changes are looked up for every payment
+    RouteCost {
+        route: "/legacy/v2/accounts/:account/payments",
+        base: 1,
+        rows_per_token: Some(50),
+    },
+    RouteCost {
+        route: "/legacy/v2/accounts/:account/balance_changes",
+        base: 1,
+        rows_per_token: Some(100),
+    },
+    // Scans look up every ledger of the range
+    RouteCost {
+        route: "/admin/gaps",
+        base: 10,
+        rows_per_token: None,
+    },
+];
+
+impl RouteCost {
+    /// Tokens taken by a request to `route`, `limit` defaults to the handlers' default limit.
+    pub fn of(route: &str, limit: Option<i32>) -> u32 {
+        let Some(cost) = ROUTE_COSTS.iter().find(|cost| cost.route == route) else {
+            return DEFAULT_ROUTE_COST;
+        };
+        let rows = limit.unwrap_or(DEFAULT_RESULT_LIMIT).max(1) as u32;
+        let row_tokens = cost
+            .rows_per_token
+            .map(|rows_per_token| rows.div_ceil(rows_per_token))
+            .unwrap_or(0);
+
+        cost.base + row_tokens
+    }
+
+    /// Rows of a streamed response of `route` charged a token each, routes without a row cost
+    /// are charged per `DEFAULT_RESULT_LIMIT` rows.
+    pub fn rows_per_token(route: &str) -> u32 {
+        ROUTE_COSTS
+            .iter()
+            .find(|cost| cost.route == route)
+            .and_then(|cost| cost.rows_per_token)
+            .unwrap_or(DEFAULT_RESULT_LIMIT as u32)
+    }
+}
+
+/// Outcome of taking tokens from a client's bucket.
+#[derive(Debug)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    // Seconds until the bucket is full again
+    pub reset: u64,
+    // Seconds until the request's cost is available, only set for rejected requests
+    pub retry_after: Option<u64>,
+}
+
+impl RateLimitDecision {
+    /// `RateLimit-*` headers describing the client's bucket, plus `Retry-After` when rejected.
+    pub fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("ratelimit-limit", HeaderValue::from(self.limit));
+        headers.insert("ratelimit-remaining", HeaderValue::from(self.remaining));
+        headers.insert("ratelimit-reset", HeaderValue::from(self.reset));
+        if let Some(retry_after) = self.retry_after {
+            headers.insert("retry-after", HeaderValue::from(retry_after));
+        }
+        headers
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token buckets per client. A tier's bucket holds a minute's worth of requests and refills
+/// continuously at its per minute rate.
+pub struct RateLimiter {
+    buckets: Mutex<HashMap<String, Bucket>>,
+    pruned: Mutex<Instant>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        RateLimiter {
+            buckets: Mutex::new(HashMap::new()),
+            pruned: Mutex::new(Instant::now()),
+        }
+    }
+}
+
+impl RateLimiter {
+    pub fn acquire(&self, client_id: &str, tier: &Tier, cost: u32) -> RateLimitDecision {
+        self.prune();
+
+        let capacity = tier.requests_per_minute as f64;
+        let refill_per_sec = capacity / 60.0;
+        // A request costing more than the whole bucket could never be admitted
+        let cost = (cost as f64).min(capacity);
+
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|err| err.into_inner());
+        let bucket = buckets.entry(client_id.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_per_sec).min(capacity);
+        bucket.updated = now;
+
+        let allowed = bucket.tokens >= cost;
+        let retry_after = if allowed {
+            bucket.tokens -= cost;
+            None
+        } else {
+            Some(((cost - bucket.tokens) / refill_per_sec).ceil() as u64)
+        };
+
+        RateLimitDecision {
+            allowed,
+            limit: tier.requests_per_minute,
+            remaining: bucket.tokens.floor() as u32,
+            reset: ((capacity - bucket.tokens) / refill_per_sec).ceil() as u64,
+            retry_after,
+        }
+    }
+
+    // Forgets buckets that refilled completely, they are recreated full on the next request.
+    // Every tier refills within a minute, so any bucket idle for that long is full.
+    fn prune(&self) {
+        let mut pruned = self.pruned.lock().unwrap_or_else(|err| err.into_inner());
+        if pruned.elapsed() < Duration::from_secs(RATE_LIMIT_PRUNE_INTERVAL_SECS) {
+            return;
+        }
+        *pruned = Instant::now();
+        drop(pruned);
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|err| err.into_inner());
+        buckets.retain(|_, bucket| bucket.updated.elapsed() < Duration::from_secs(60));
+    }
+}
+
+/// Charges the rows of a streamed response to the client's bucket while they are read, a token
+/// per `rows_per_token` rows. Streams are not limited in rows, so once the bucket is empty the
+/// stream waits for it to refill instead of failing, unless a row would wait for longer than
+/// `ROW_THROTTLE_MAX_WAIT_SECS`.
+#[derive(Clone)]
+pub struct RowThrottle {
+    limiter: Arc<RateLimiter>,
+    bucket: String,
+    tier: &'static Tier,
+    rows_per_token: u32,
+    rows: Arc<AtomicU32>,
+}
+
+impl RowThrottle {
+    pub fn new(
+        limiter: Arc<RateLimiter>,
+        bucket: String,
+        tier: &'static Tier,
+        rows_per_token: u32,
+    ) -> Self {
+        RowThrottle {
+            limiter,
+            bucket,
+            tier,
+            rows_per_token: rows_per_token.max(1),
+            rows: Arc::new(AtomicU32::new(0)),
+        }
+    }
+
+    /// Counts a read row, taking a token once `rows_per_token` rows were read.
+    pub async fn row(&self) -> Result<(), DataApiError> {
+        let rows = self.rows.fetch_add(1, Ordering::Relaxed).wrapping_add(1);
+        if rows % self.rows_per_token != 0 {
+            return Ok(());
+        }
+        let mut waited = 0;
+        loop {
+            let decision = self.limiter.acquire(&self.bucket, self.tier, 1);
+            let Some(retry_after) = decision.retry_after else {
+                return Ok(());
+            };
+            // Other requests of the client can keep the bucket empty, give up rather than
+            // holding the stream open indefinitely
+            let wait = retry_after.max(1);
+            if waited + wait > ROW_THROTTLE_MAX_WAIT_SECS {
+                return Err(DataApiError::RateLimited(retry_after));
+            }
+            tokio::time::sleep(Duration::from_secs(wait)).await;
+            waited += wait;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static TIER: Tier = Tier {
+        name: "test",
+        requests_per_minute: 60,
+        max_limit: 100,
+        allowed_routes: &[],
+    };
+
+    #[tokio::test(start_paused = true)]
+    async fn refills_at_the_per_minute_rate() {
+        let limiter = RateLimiter::default();
+        assert_eq!(limiter.acquire("client", &TIER, 60).remaining, 0);
+
+        tokio::time::advance(Duration::from_secs(10)).await;
+        let decision = limiter.acquire("client", &TIER, 1);
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 9);
+        assert_eq!(decision.reset, 51);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rejects_until_the_cost_refilled() {
+        let limiter = RateLimiter::default();
+        limiter.acquire("client", &TIER, 58);
+
+        let decision = limiter.acquire("client", &TIER, 5);
+        assert!(!decision.allowed);
+        assert_eq!(decision.remaining, 2);
+        assert_eq!(decision.retry_after, Some(3));
+        assert_eq!(decision.reset, 58);
+
+        tokio::time::advance(Duration::from_secs(3)).await;
+        assert!(limiter.acquire("client", &TIER, 5).allowed);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn clamps_the_cost_to_the_bucket() {
+        let limiter = RateLimiter::default();
+        let decision = limiter.acquire("client", &TIER, 1000);
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 0);
+
+        let decision = limiter.acquire("client", &TIER, 1000);
+        assert_eq!(decision.retry_after, Some(60));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn prunes_idle_buckets() {
+        let limiter = RateLimiter::default();
+        limiter.acquire("idle", &TIER, 1);
+        tokio::time::advance(Duration::from_secs(30)).await;
+        limiter.acquire("active", &TIER, 1);
+
+        tokio::time::advance(Duration::from_secs(RATE_LIMIT_PRUNE_INTERVAL_SECS)).await;
+        limiter.acquire("active", &TIER, 1);
+        let buckets = limiter.buckets.lock().unwrap();
+        assert!(!buckets.contains_key("idle"));
+        assert!(buckets.contains_key("active"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn throttles_rows_until_the_bucket_refills() {
+        let limiter = Arc::new(RateLimiter::default());
+        limiter.acquire("client", &TIER, 60);
+        let throttle = RowThrottle::new(limiter, "client".to_string(), &TIER, 2);
+
+        let start = Instant::now();
+        throttle.row().await.unwrap();
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        throttle.row().await.unwrap();
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fails_rows_waiting_too_long() {
+        static SLOW_TIER: Tier = Tier {
+            name: "slow",
+            requests_per_minute: 1,
+            max_limit: 100,
+            allowed_routes: &[],
+        };
+        let limiter = Arc::new(RateLimiter::default());
+        limiter.acquire("client", &SLOW_TIER, 1);
+        let throttle = RowThrottle::new(limiter, "client".to_string(), &SLOW_TIER, 1);
+
+        let start = Instant::now();
+        let result = throttle.row().await;
+        assert!(matches!(result, Err(DataApiError::RateLimited(60))));
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,141 @@
+use crate::utils::consts::MAX_SCANNED_ROWS;
+use crate::utils::errors::DataApiError;
+use crate::utils::rate_limit::RowThrottle;
+use futures::{Stream, StreamExt};
+use scylla::query::Query;
+use scylla::serialize::row::SerializeRow;
+use scylla::transport::iterator::{NextRowError, TypedRowIterator};
+use scylla::{FromRow, Session};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Rows a request may still read, shared by all of its queries. Reading fails once more than
+/// `MAX_SCANNED_ROWS` rows were read, instead of scanning a huge partition to the end. HTTP
+/// requests get theirs from `enforce_limits` as an extension, other callers create one per unit
+/// of work.
+#[derive(Clone)]
+pub struct ScanBudget {
+    limit: usize,
+    remaining: Arc<AtomicUsize>,
+    throttle: Option<RowThrottle>,
+}
+
+impl Default for ScanBudget {
+    fn default() -> Self {
+        ScanBudget::new(MAX_SCANNED_ROWS)
+    }
+}
+
+impl ScanBudget {
+    pub fn new(limit: usize) -> Self {
+        ScanBudget {
+            limit,
+            remaining: Arc::new(AtomicUsize::new(limit)),
+            throttle: None,
+        }
+    }
+
+    /// Budget of a streamed response. Memory stays bounded however many rows are read, so
+    /// they are not limited, they are charged to the client's rate limit instead.
+    pub fn streamed(throttle: RowThrottle) -> Self {
+        ScanBudget {
+            throttle: Some(throttle),
+            ..ScanBudget::new(usize::MAX)
+        }
+    }
+
+    fn take(&self) -> Result<(), DataApiError> {
+        self.charge(1)
+    }
+
+    /// Charges `rows` read by a query that does not go through `next_row`, such as a lookup
+    /// made for each row of a page.
+    pub fn charge(&self, rows: usize) -> Result<(), DataApiError> {
+        self.remaining
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |remaining| {
+                remaining.checked_sub(rows)
+            })
+            .map(|_| ())
+            .map_err(|_| DataApiError::ScanLimitExceeded(self.limit))
+    }
+}
+
+/// Returns the next row of a lazily paged query, fetching the next page when needed.
+/// Rows that fail to deserialize are skipped, the same way single page queries filter them.
+pub async fn next_row<T, S>(rows: &mut S, budget: &ScanBudget) -> Result<Option<T>, DataApiError>
+where
+    S: Stream<Item = Result<T, NextRowError>> + Unpin,
+{
+    while let Some(row) = rows.next().await {
+        budget.take()?;
+        if let Some(throttle) = &budget.throttle {
+            throttle.row().await?;
+        }
+        match row {
+            Ok(row) => return Ok(Some(row)),
+            // todo: better row error handling
+            Err(NextRowError::FromRowError(_)) => continue,
+            Err(NextRowError::QueryError(err)) => return Err(err.into()),
+        }
+    }
+    Ok(None)
+}
+
+/// Runs a query and collects the rows of all its pages, charging them to `budget`.
+pub async fn collect_rows<T: FromRow>(
+    session: &Session,
+    query: Query,
+    values: impl SerializeRow,
+    budget: &ScanBudget,
+) -> Result<Vec<T>, DataApiError> {
+    let mut rows = session.query_iter(query, values).await?.into_typed::<T>();
+    let mut collected = Vec::new();
+    while let Some(row) = next_row(&mut rows, budget).await? {
+        collected.push(row);
+    }
+    Ok(collected)
+}
+
+/// Merges lazily paged queries that are each sorted by `key` descending into one stream sorted
+/// the same way, reading every query one page at a time. A row whose key equals the previous
+/// row's is left out, for rows found by more than one of the queries.
+pub fn merge_descending<T, K, F>(
+    queries: Vec<TypedRowIterator<T>>,
+    key: F,
+) -> impl Stream<Item = Result<T, NextRowError>> + Send + Unpin
+where
+    T: FromRow + Send + 'static,
+    K: Ord + Send + 'static,
+    F: Fn(&T) -> K + Send + 'static,
+{
+    let heads = queries.iter().map(|_| None).collect::<Vec<Option<T>>>();
+    let state = (queries, heads, None::<K>, key);
+    Box::pin(futures::stream::unfold(
+        state,
+        |(mut queries, mut heads, mut last, key)| async move {
+            loop {
+                for (query, head) in queries.iter_mut().zip(heads.iter_mut()) {
+                    if head.is_none() {
+                        match query.next().await {
+                            Some(Ok(row)) => *head = Some(row),
+                            Some(Err(err)) => return Some((Err(err), (queries, heads, last, key))),
+                            None => {}
+                        }
+                    }
+                }
+
+                let newest = heads
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, head)| head.as_ref().map(|row| (key(row), index)))
+                    .max()?;
+                let row = heads[newest.1].take()?;
+                if last.as_ref() == Some(&newest.0) {
+                    continue;
+                }
+                last = Some(newest.0);
+                return Some((Ok(row), (queries, heads, last, key)));
+            }
+        },
+    ))
+}
@@ -0,0 +1,161 @@
+use crate::utils::errors::DataApiError;
+use futures::future::{BoxFuture, Shared};
+use futures::FutureExt;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+type Flight<T> = Shared<BoxFuture<'static, Result<T, Arc<DataApiError>>>>;
+
+/// Coalesces identical concurrent queries so they share one backend call and its result.
+/// Queries run on their own task: a caller that goes away does not cancel the query for the
+/// others waiting on it. A key is released as soon as its query finishes, so neither results
+/// nor errors outlive the flight.
+pub struct SingleFlight<T> {
+    flights: Arc<Mutex<HashMap<String, Flight<T>>>>,
+}
+
+// Releases the key when the query finishes, including when it panics
+struct Release<T> {
+    flights: Arc<Mutex<HashMap<String, Flight<T>>>>,
+    key: String,
+}
+
+impl<T> Drop for Release<T> {
+    fn drop(&mut self) {
+        self.flights
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(&self.key);
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> Default for SingleFlight<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> SingleFlight<T> {
+    pub fn new() -> Self {
+        SingleFlight {
+            flights: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Runs `query` under `key`, or waits for the query already running under it.
+    pub async fn run<F>(&self, key: String, query: F) -> Result<T, Arc<DataApiError>>
+    where
+        F: Future<Output = Result<T, DataApiError>> + Send + 'static,
+    {
+        let flight = {
+            let mut flights = self.flights.lock().unwrap_or_else(|err| err.into_inner());
+            match flights.get(&key) {
+                Some(flight) => flight.clone(),
+                None => {
+                    // The task can only release the key once it is inserted, as the lock is held
+                    let task = tokio::spawn({
+                        let flights = self.flights.clone();
+                        let key = key.clone();
+                        async move {
+                            let _release = Release { flights, key };
+                            query.await.map_err(Arc::new)
+                        }
+                    });
+                    let flight = async move {
+                        match task.await {
+                            Ok(result) => result,
+                            Err(err) => Err(Arc::new(DataApiError::TaskFailed(err.to_string()))),
+                        }
+                    }
+                    .boxed()
+                    .shared();
+                    flights.insert(key, flight.clone());
+                    flight
+                }
+            }
+        };
+
+        flight.await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::oneshot;
+
+    #[tokio::test]
+    async fn finishes_the_query_for_waiters_when_the_leader_is_dropped() {
+        let flights = Arc::new(SingleFlight::new());
+        let (release, released) = oneshot::channel::<()>();
+        let leader = tokio::spawn({
+            let flights = flights.clone();
+            async move {
+                flights
+                    .run("key".to_string(), async move {
+                        released.await.ok();
+                        Ok(1)
+                    })
+                    .await
+            }
+        });
+        tokio::task::yield_now().await;
+
+        let waiter = tokio::spawn({
+            let flights = flights.clone();
+            async move { flights.run("key".to_string(), async { Ok(2) }).await }
+        });
+        tokio::task::yield_now().await;
+        leader.abort();
+        release.send(()).unwrap();
+
+        assert_eq!(waiter.await.unwrap().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn shares_the_error_and_releases_the_key() {
+        let flights = Arc::new(SingleFlight::<i32>::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let (release, released) = oneshot::channel::<()>();
+
+        let leader = tokio::spawn({
+            let flights = flights.clone();
+            let calls = calls.clone();
+            async move {
+                flights
+                    .run("key".to_string(), async move {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        released.await.ok();
+                        Err(DataApiError::NoDataReturned)
+                    })
+                    .await
+            }
+        });
+        tokio::task::yield_now().await;
+        let waiter = tokio::spawn({
+            let flights = flights.clone();
+            let calls = calls.clone();
+            async move {
+                flights
+                    .run("key".to_string(), async move {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        Ok(2)
+                    })
+                    .await
+            }
+        });
+        tokio::task::yield_now().await;
+        release.send(()).unwrap();
+
+        let leader_err = leader.await.unwrap().unwrap_err();
+        let waiter_err = waiter.await.unwrap().unwrap_err();
+        assert!(Arc::ptr_eq(&leader_err, &waiter_err));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // The failure is not cached, the next call queries again
+        let result = flights.run("key".to_string(), async { Ok(3) }).await;
+        assert_eq!(result.unwrap(), 3);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::utils::errors::DataApiError;
+use crate::utils::format::{OutputFormat, CSV_CONTENT_TYPE, NDJSON_CONTENT_TYPE};
+use crate::utils::rows::{next_row, ScanBudget};
+use axum::body::Body;
+use axum::http::header::CONTENT_TYPE;
+use axum::response::{IntoResponse, Response};
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use scylla::transport::iterator::NextRowError;
+use scylla::FromRow;
+use serde::Serialize;
+
+struct RowStream<S> {
+    rows: S,
+    format: OutputFormat,
+    emitted: usize,
+    finished: bool,
+}
+
+/// Reads the rows of a lazily paged query one at a time, ending after `limit` rows or the first
+/// error. Rows are charged to the request's scan budget.
+pub fn row_stream<T, S>(
+    rows: S,
+    limit: Option<usize>,
+    budget: ScanBudget,
+) -> impl Stream<Item = Result<T, DataApiError>> + Send
+where
+    T: FromRow + Send + 'static,
+    S: Stream<Item = Result<T, NextRowError>> + Send + Unpin + 'static,
+{
+    let state = (rows, budget, false);
+    futures::stream::unfold(state, |(mut rows, budget, failed)| async move {
+        if failed {
+            return None;
+        }
+        match next_row(&mut rows, &budget).await {
+            Ok(Some(row)) => Some((Ok(row), (rows, budget, false))),
+            Ok(None) => None,
+            Err(err) => Some((Err(err), (rows, budget, true))),
+        }
+    })
+    .take(limit.unwrap_or(usize::MAX))
+}
+
+/// Streams the rows of a lazily paged query into a chunked response body, one chunk per row.
+/// The next page is only fetched from scylla once the client has consumed the current one,
+/// so memory stays bounded regardless of how many rows the query returns.
+pub fn stream_rows<T, S>(
+    rows: S,
+    format: OutputFormat,
+    limit: Option<usize>,
+    budget: ScanBudget,
+) -> Response
+where
+    T: FromRow + Serialize + Send + 'static,
+    S: Stream<Item = Result<T, NextRowError>> + Send + Unpin + 'static,
+{
+    let state = RowStream {
+        rows: Box::pin(row_stream(rows, limit, budget)),
+        format,
+        emitted: 0,
+        finished: false,
+    };
+    let chunks = futures::stream::unfold(state, |mut state| async move {
+        if state.finished {
+            return None;
+        }
+        let chunk = match state.rows.next().await {
+            Some(Ok(row)) => {
+                let chunk = encode_row(&row, state.format, state.emitted == 0);
+                state.emitted += 1;
+                chunk
+            }
+            None => {
+                state.finished = true;
+                match (state.format, state.emitted) {
+                    (OutputFormat::Json, 0) => Ok(Bytes::from_static(b"[]")),
+                    (OutputFormat::Json, _) => Ok(Bytes::from_static(b"]")),
+                    _ => return None,
+                }
+            }
+            Some(Err(err)) => Err(err),
+        };
+        if let Err(err) = &chunk {
+            eprintln!("Streaming rows failed: {}", err);
+            state.finished = true;
+        }
+        Some((chunk, state))
+    });
+
+    let content_type = match format {
+        OutputFormat::Json => "application/json",
+        OutputFormat::Ndjson => NDJSON_CONTENT_TYPE,
+        OutputFormat::Csv => CSV_CONTENT_TYPE,
+    };
+    ([(CONTENT_TYPE, content_type)], Body::from_stream(chunks)).into_response()
+}
+
+fn encode_row<T: Serialize>(
+    row: &T,
+    format: OutputFormat,
+    first: bool,
+) -> Result<Bytes, DataApiError> {
+    let mut chunk = Vec::new();
+    match format {
+        OutputFormat::Json => {
+            chunk.push(if first { b'[' } else { b',' });
+            serde_json::to_writer(&mut chunk, row)?;
+        }
+        OutputFormat::Ndjson => {
+            serde_json::to_writer(&mut chunk, row)?;
+            chunk.push(b'\n');
+        }
+        OutputFormat::Csv => {
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(first)
+                .from_writer(chunk);
+            writer.serialize(row)?;
+            chunk = writer
+                .into_inner()
+                .map_err(|err| csv::Error::from(err.into_error()))?;
+        }
+    }
+    Ok(Bytes::from(chunk))
+}
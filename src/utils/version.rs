@@ -0,0 +1,161 @@
+use crate::routes;
+use crate::utils::consts::{ROOT_DEPRECATED_AT_ENV, ROOT_SUNSET_AT_ENV};
+use crate::AppState;
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::{HeaderName, HeaderValue};
+use axum::middleware::Next;
+use axum::response::Response;
+use axum::Router;
+use chrono::DateTime;
+use std::sync::Arc;
+
+/// Version of the API a route is mounted under. Each version has its own router, mounting a
+/// version with changed serializers takes adding it here with its router.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiVersion {
+    V1,
+}
+
+impl ApiVersion {
+    pub const ALL: &'static [ApiVersion] = &[ApiVersion::V1];
+
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "/v1",
+        }
+    }
+
+    /// Routes served under the version, the first version's are also served at the root.
+    pub fn router(&self) -> Router<Arc<AppState>> {
+        match self {
+            ApiVersion::V1 => routes::api_router(),
+        }
+    }
+
+    /// Splits a matched route into its version and the route within the version. Root paths
+    /// have no version.
+    pub fn split(route: &str) -> (Option<ApiVersion>, &str) {
+        for version in ApiVersion::ALL {
+            if let Some(rest) = route.strip_prefix(version.prefix()) {
+                if rest.starts_with('/') {
+                    return (Some(*version), rest);
+                }
+                // The root of a version
+                if rest.is_empty() {
+                    return (Some(*version), "/");
+                }
+            }
+        }
+        (None, route)
+    }
+}
+
+/// Deprecation of the routes under `route`, matched by segment prefix against the route within
+/// the version. `version` is `None` for the root aliases.
+pub struct RouteDeprecation {
+    pub version: Option<ApiVersion>,
+    pub route: &'static str,
+    // Routes under `route` that are not deprecated, matched exactly
+    pub except: &'static [&'static str],
+    // Environment variables holding RFC 3339 times, the routes are not deprecated while the
+    // first is unset
+    pub deprecated_at_env: &'static str,
+    pub sunset_at_env: &'static str,
+    pub link: Option<&'static str>,
+}
+
+pub static DEPRECATIONS: &[RouteDeprecation] = &[
+    // Root paths only remain for clients that predate versioning
+    RouteDeprecation {
+        version: None,
+        route: "/",
+        // rippled clients can only reach JSON-RPC at the root
+        except: &["/"],
+        deprecated_at_env: ROOT_DEPRECATED_AT_ENV,
+        sunset_at_env: ROOT_SUNSET_AT_ENV,
+        link: Some("/docs"),
+    },
+];
+
+/// A deprecation with its configured times, as unix times.
+pub struct ActiveDeprecation {
+    deprecation: &'static RouteDeprecation,
+    deprecated_at: i64,
+    sunset_at: Option<i64>,
+}
+
+/// Reads the times of `DEPRECATIONS` from the environment, leaving out the deprecations whose
+/// time is unset.
+pub fn deprecations_from_env() -> Vec<ActiveDeprecation> {
+    let read_time = |name: &str| {
+        std::env::var(name)
+            .ok()
+            .filter(|value| !value.is_empty())
+            .map(|value| match DateTime::parse_from_rfc3339(&value) {
+                Ok(time) => time.timestamp(),
+                Err(err) => panic!("Invalid time {} in {}: {}", value, name, err),
+            })
+    };
+    DEPRECATIONS
+        .iter()
+        .filter_map(|deprecation| {
+            Some(ActiveDeprecation {
+                deprecation,
+                deprecated_at: read_time(deprecation.deprecated_at_env)?,
+                sunset_at: read_time(deprecation.sunset_at_env),
+            })
+        })
+        .collect()
+}
+
+fn find_deprecation<'a>(
+    deprecations: &'a [ActiveDeprecation],
+    version: Option<ApiVersion>,
+    route: &str,
+) -> Option<&'a ActiveDeprecation> {
+    deprecations.iter().find(|active| {
+        let deprecation = active.deprecation;
+        deprecation.version == version
+            && !deprecation.except.contains(&route)
+            && route
+                .strip_prefix(deprecation.route.trim_end_matches('/'))
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
+
+/// Adds the `Deprecation`, `Sunset` and `Link` headers to responses of deprecated routes.
+pub async fn add_deprecation_headers(
+    State(deprecations): State<Arc<Vec<ActiveDeprecation>>>,
+    matched_path: MatchedPath,
+    request: Request,
+    next: Next,
+) -> Response {
+    let (version, route) = ApiVersion::split(matched_path.as_str());
+    let deprecation = find_deprecation(&deprecations, version, route);
+
+    let mut response = next.run(request).await;
+    if let Some(deprecation) = deprecation {
+        let headers = response.headers_mut();
+        // Structured field date, as in RFC 9745
+        let deprecated_at = format!("@{}", deprecation.deprecated_at);
+        if let Ok(value) = HeaderValue::from_str(&deprecated_at) {
+            headers.insert(HeaderName::from_static("deprecation"), value);
+        }
+        let sunset_at = deprecation
+            .sunset_at
+            .and_then(|sunset_at| DateTime::from_timestamp(sunset_at, 0));
+        if let Some(sunset_at) = sunset_at {
+            let sunset_at = sunset_at.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+            if let Ok(value) = HeaderValue::from_str(&sunset_at) {
+                headers.insert(HeaderName::from_static("sunset"), value);
+            }
+        }
+        if let Some(link) = deprecation.deprecation.link {
+            let link = format!("<{}>; rel=\"deprecation\"", link);
+            if let Ok(value) = HeaderValue::from_str(&link) {
+                headers.append(axum::http::header::LINK, value);
+            }
+        }
+    }
+    response
+}
@@ -0,0 +1,30 @@
+use crate::handlers::stats::get_daily_stats;
+use crate::models::daily_stats::DailyStats;
+use crate::utils::consts::{DAILY_STATS_INTERVAL_SECS, DAILY_STATS_MAX_SCANNED_ROWS};
+use crate::utils::rows::ScanBudget;
+use chrono::{Duration, NaiveDate, Utc};
+use moka::future::Cache;
+use scylla::Session;
+use std::sync::Arc;
+
+/// Periodically computes the stats of today and yesterday, the days that are not final yet and
+/// so can not be persisted, into the cache `/stats/daily` serves them from. Yesterday is
+/// persisted by the first run after it became final, after which it is a single row read.
+pub async fn run_daily_stats(session: Arc<Session>, cache: Cache<NaiveDate, Arc<DailyStats>>) {
+    let mut interval =
+        tokio::time::interval(std::time::Duration::from_secs(DAILY_STATS_INTERVAL_SECS));
+
+    loop {
+        interval.tick().await;
+
+        let today = Utc::now().date_naive();
+        for day in [today - Duration::days(1), today] {
+            // A whole day is read at once, so the worker gets a larger budget than requests
+            let budget = ScanBudget::new(DAILY_STATS_MAX_SCANNED_ROWS);
+            match get_daily_stats(&session, day, &budget).await {
+                Ok(stats) => cache.insert(day, Arc::new(stats)).await,
+                Err(err) => eprintln!("Failed to compute stats of {}: {}", day, err),
+            }
+        }
+    }
+}
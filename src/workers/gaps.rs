@@ -0,0 +1,61 @@
+use crate::gaps::{find_gaps, latest_ledger_index};
+use crate::utils::consts::{GAP_METRIC_INTERVAL_SECS, GAP_METRIC_LEDGERS};
+use crate::utils::metrics::Metrics;
+use scylla::Session;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Periodically scans the latest ledgers for gaps and records them for `/metrics`. Older gaps
+/// are only found by `/admin/gaps` and the gaps binary.
+pub async fn run_gap_scan(session: Arc<Session>, metrics: Arc<Metrics>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(GAP_METRIC_INTERVAL_SECS));
+
+    loop {
+        interval.tick().await;
+
+        let latest = match latest_ledger_index(&session).await {
+            Ok(Some(latest)) => latest,
+            Ok(None) => continue,
+            Err(err) => {
+                eprintln!("Gap scan failed to find latest ledger: {}", err);
+                continue;
+            }
+        };
+        let start = metric_range_start(latest);
+        match find_gaps(&session, start, latest).await {
+            Ok(gaps) => {
+                println!(
+                    "Found {} gaps in ledgers {} to {}",
+                    gaps.gap_count(),
+                    start,
+                    latest
+                );
+                metrics.record_gaps(&gaps);
+            }
+            Err(err) => eprintln!("Gap scan failed: {}", err),
+        }
+    }
+}
+
+// First of the `GAP_METRIC_LEDGERS` ledgers ending at `latest`, the range starts at the first
+// ledger while fewer ledgers exist
+fn metric_range_start(latest: i64) -> i64 {
+    (latest - GAP_METRIC_LEDGERS + 1).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::metric_range_start;
+    use crate::utils::consts::GAP_METRIC_LEDGERS;
+
+    #[test]
+    fn scans_the_latest_ledgers() {
+        let latest = 90_000_000;
+        let start = metric_range_start(latest);
+        assert_eq!(latest - start + 1, GAP_METRIC_LEDGERS);
+
+        assert_eq!(metric_range_start(GAP_METRIC_LEDGERS), 1);
+        assert_eq!(metric_range_start(GAP_METRIC_LEDGERS + 1), 2);
+        assert_eq!(metric_range_start(10), 1);
+    }
+}
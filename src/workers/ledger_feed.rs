@@ -0,0 +1,61 @@
+use crate::gaps::latest_ledger_index;
+use crate::handlers::ledger::{get_ledger, LedgerIdentifier};
+use crate::models::ledger::Ledger;
+use crate::utils::consts::{LEDGER_FEED_MAX_CATCH_UP, LEDGER_FEED_POLL_INTERVAL_MS};
+use scylla::Session;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Polls `daily_ledgers` for newly closed ledgers and publishes their headers to every subscriber.
+/// Subscribers that fall more than the channel capacity behind observe a lag and are dropped by
+/// their handlers, so a slow client never holds back the others.
+pub async fn run_ledger_feed(session: Arc<Session>, sender: broadcast::Sender<Arc<Ledger>>) {
+    let mut interval = tokio::time::interval(Duration::from_millis(LEDGER_FEED_POLL_INTERVAL_MS));
+    let mut last_published: Option<i64> = None;
+
+    loop {
+        interval.tick().await;
+
+        // Reads a single row, rather than the day's partition
+        let latest = match latest_ledger_index(&session).await {
+            Ok(Some(latest)) => latest,
+            Ok(None) => continue,
+            Err(err) => {
+                eprintln!("Ledger feed failed to find latest ledger: {}", err);
+                continue;
+            }
+        };
+        let from = match last_published {
+            Some(last_published) if latest > last_published => {
+                (last_published + 1).max(latest - LEDGER_FEED_MAX_CATCH_UP + 1)
+            }
+            Some(_) => continue,
+            None => latest,
+        };
+
+        for ledger_index in from..=latest {
+            let ledger = get_ledger(
+                &session,
+                "ledger_index",
+                &LedgerIdentifier::BigInt(ledger_index),
+            )
+            .await;
+            match ledger {
+                Ok(ledger) => {
+                    // Sending only fails when nobody is subscribed
+                    let _ = sender.send(Arc::new(ledger));
+                    last_published = Some(ledger_index);
+                }
+                Err(err) => {
+                    // The header may not be written yet, retry from this ledger on the next tick
+                    eprintln!(
+                        "Ledger feed failed to load ledger {}: {}",
+                        ledger_index, err
+                    );
+                    break;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod daily_stats;
+pub mod gaps;
+pub mod ledger_feed;
+pub mod usage;
+pub mod webhook;
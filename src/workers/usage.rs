@@ -0,0 +1,50 @@
+use crate::handlers::api_key::add_api_key_usage;
+use crate::utils::auth::UsageTracker;
+use crate::utils::consts::USAGE_FLUSH_INTERVAL_SECS;
+use crate::utils::errors::DataApiError;
+use scylla::transport::errors::{DbError, QueryError};
+use scylla::Session;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Periodically adds the buffered per key request counts to the usage table. Counter updates
+/// are not idempotent and a failed one may still have been applied, so counts are only kept
+/// for the next flush when the update certainly was not, and dropped otherwise.
+pub async fn run_usage_flush(session: Arc<Session>, usage_tracker: Arc<UsageTracker>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(USAGE_FLUSH_INTERVAL_SECS));
+
+    loop {
+        interval.tick().await;
+
+        for ((key_id, day, route), requests) in usage_tracker.take_pending() {
+            if let Err(err) = add_api_key_usage(&session, key_id, day, &route, requests).await {
+                eprintln!("Failed to flush usage of key {}: {}", key_id, err);
+                if !may_have_applied(&err) {
+                    usage_tracker.restore((key_id, day, route), requests);
+                }
+            }
+        }
+    }
+}
+
+// All errors but those of requests that never reached a replica or were refused before any
+// write
+fn may_have_applied(err: &DataApiError) -> bool {
+    !matches!(
+        err,
+        DataApiError::QueryFailed(
+            QueryError::BadQuery(_)
+                | QueryError::UnableToAllocStreamId
+                | QueryError::TranslationError(_)
+                | QueryError::DbError(
+                    DbError::Unavailable { .. }
+                        | DbError::Overloaded
+                        | DbError::IsBootstrapping
+                        | DbError::SyntaxError
+                        | DbError::Invalid
+                        | DbError::Unauthorized,
+                    _,
+                ),
+        )
+    )
+}
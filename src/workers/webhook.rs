@@ -0,0 +1,307 @@
+use crate::handlers::payment::get_ledger_payments;
+use crate::handlers::webhook::{
+    delete_pending_delivery, get_pending_deliveries, get_webhook, get_webhook_delivery,
+    get_webhooks, record_delivery_attempt, save_delivery, save_pending_delivery,
+};
+use crate::models::ledger::Ledger;
+use crate::models::webhook::{DeliveryStatus, Webhook, WebhookDelivery, WebhookDeliveryAttempt};
+use crate::utils::consts::{
+    WEBHOOK_DELIVERY_HEADER, WEBHOOK_DISPATCH_CURSOR, WEBHOOK_DISPATCH_CURSOR_TABLE,
+    WEBHOOK_MAX_ATTEMPTS, WEBHOOK_RETRY_BASE_SECS, WEBHOOK_SIGNATURE_HEADER,
+};
+use crate::utils::errors::DataApiError;
+use crate::utils::rows::ScanBudget;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use scylla::Session;
+use sha2::Sha256;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+/// Matches the payments of every newly closed ledger against the registered webhooks and
+/// queues a delivery for each match. Ledgers skipped while lagging behind the feed, or while the
+/// server was down, are dispatched together with the next one, from the persisted cursor.
+/// Deliveries that were pending when the server stopped are resumed first. Delivery is at least
+/// once, so receivers should deduplicate on `tx_hash`.
+pub async fn run_webhook_dispatcher(
+    session: Arc<Session>,
+    client: reqwest::Client,
+    mut ledgers: broadcast::Receiver<Arc<Ledger>>,
+) {
+    if let Err(err) = resume_pending_deliveries(&session, &client).await {
+        eprintln!("Failed to resume pending webhook deliveries: {}", err);
+    }
+    let mut last_dispatched = match load_cursor(&session).await {
+        Ok(last_dispatched) => last_dispatched,
+        Err(err) => {
+            eprintln!("Failed to load the webhook dispatch cursor: {}", err);
+            None
+        }
+    };
+    // Deliveries queued for the ledger being dispatched, kept while it is retried
+    let mut queued = HashSet::new();
+
+    loop {
+        let latest = match ledgers.recv().await {
+            Ok(ledger) => ledger.ledger_index,
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        };
+        let from = match last_dispatched {
+            Some(last_dispatched) if latest > last_dispatched => last_dispatched + 1,
+            Some(_) => continue,
+            None => latest,
+        };
+
+        for ledger_index in from..=latest {
+            let dispatched =
+                match dispatch_ledger(&session, &client, ledger_index, &mut queued).await {
+                    Ok(()) => save_cursor(&session, ledger_index).await,
+                    Err(err) => Err(err),
+                };
+            match dispatched {
+                Ok(()) => {
+                    last_dispatched = Some(ledger_index);
+                    queued.clear();
+                }
+                Err(err) => {
+                    // Retry from this ledger once the next one closes
+                    eprintln!(
+                        "Webhook dispatcher failed on ledger {}: {}",
+                        ledger_index, err
+                    );
+                    break;
+                }
+            }
+        }
+    }
+}
+
+// Deliveries in `queued`, as webhook id and transaction hash, were queued by an earlier try
+// of the same ledger and are not queued again
+async fn dispatch_ledger(
+    session: &Arc<Session>,
+    client: &reqwest::Client,
+    ledger_index: i64,
+    queued: &mut HashSet<(Uuid, String)>,
+) -> Result<(), DataApiError> {
+    let budget = ScanBudget::default();
+    let webhooks = get_webhooks(session, &budget).await?;
+    if webhooks.is_empty() {
+        return Ok(());
+    }
+    let payments = get_ledger_payments(session, ledger_index, &budget).await?;
+
+    for payment in &payments {
+        for webhook in webhooks.iter().filter(|webhook| webhook.matches(payment)) {
+            let key = (webhook.id, payment.tx_hash.clone());
+            if queued.contains(&key) {
+                continue;
+            }
+            let now = Utc::now();
+            let delivery = WebhookDelivery {
+                webhook_id: webhook.id,
+                delivery_id: Uuid::now_v7(),
+                tx_hash: payment.tx_hash.clone(),
+                payload: serde_json::to_string(payment)?,
+                status: DeliveryStatus::Pending.as_str().to_string(),
+                attempts: 0,
+                last_status_code: None,
+                last_error: None,
+                created_at: now,
+                updated_at: now,
+            };
+            queue_delivery(session, client, delivery).await?;
+            queued.insert(key);
+        }
+    }
+
+    Ok(())
+}
+
+/// Saves a pending delivery and starts its retries, `WEBHOOK_MAX_ATTEMPTS` more attempts than
+/// it already had.
+pub async fn queue_delivery(
+    session: &Arc<Session>,
+    client: &reqwest::Client,
+    delivery: WebhookDelivery,
+) -> Result<(), DataApiError> {
+    let last_attempt = delivery.attempts + WEBHOOK_MAX_ATTEMPTS;
+    save_delivery(session, &delivery).await?;
+    save_pending_delivery(session, &delivery, last_attempt).await?;
+    tokio::spawn(deliver(
+        session.clone(),
+        client.clone(),
+        delivery,
+        last_attempt,
+    ));
+
+    Ok(())
+}
+
+// Restarts the retries of every delivery that was pending when the server stopped, with the
+// attempts it had left
+async fn resume_pending_deliveries(
+    session: &Arc<Session>,
+    client: &reqwest::Client,
+) -> Result<(), DataApiError> {
+    let pending = get_pending_deliveries(session, &ScanBudget::default()).await?;
+    println!("Resuming {} pending webhook deliveries", pending.len());
+    for (webhook_id, delivery_id, last_attempt) in pending {
+        let delivery = match get_webhook_delivery(session, webhook_id, delivery_id).await {
+            Ok(delivery) if delivery.status == DeliveryStatus::Pending.as_str() => delivery,
+            // Finished or deleted before it was unmarked
+            Ok(_) | Err(DataApiError::NoDataReturned) => {
+                delete_pending_delivery(session, webhook_id, delivery_id).await?;
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+        tokio::spawn(deliver(
+            session.clone(),
+            client.clone(),
+            delivery,
+            last_attempt,
+        ));
+    }
+
+    Ok(())
+}
+
+/// POSTs a delivery until the receiver answers with a success status, waiting exponentially
+/// longer between attempts. Every attempt is recorded and the delivery is marked failed once
+/// `last_attempt` was made without success.
+async fn deliver(
+    session: Arc<Session>,
+    client: reqwest::Client,
+    mut delivery: WebhookDelivery,
+    last_attempt: i32,
+) {
+    let first_attempt = delivery.attempts + 1;
+
+    for attempt in first_attempt..=last_attempt {
+        if attempt > first_attempt {
+            let backoff = WEBHOOK_RETRY_BASE_SECS << (attempt - first_attempt - 1);
+            tokio::time::sleep(Duration::from_secs(backoff)).await;
+        }
+
+        let attempted_at = Utc::now();
+        let started = Instant::now();
+        let (status_code, error) = match get_webhook(&session, delivery.webhook_id).await {
+            Ok(webhook) => post_delivery(&client, &webhook, &delivery).await,
+            // The webhook was deleted while the delivery was pending
+            Err(DataApiError::NoDataReturned) => return,
+            // Recorded as a failed attempt, so the delivery still ends once its attempts run out
+            Err(err) => (None, Some(format!("loading the webhook failed ({})", err))),
+        };
+        let duration_ms = started.elapsed().as_millis() as i64;
+        let delivered = error.is_none();
+
+        let attempt_record = WebhookDeliveryAttempt {
+            delivery_id: delivery.delivery_id,
+            attempt,
+            attempted_at,
+            status_code,
+            error: error.clone(),
+            duration_ms,
+        };
+        if let Err(err) = record_delivery_attempt(&session, &attempt_record).await {
+            eprintln!("Failed to record webhook delivery attempt: {}", err);
+        }
+
+        let status = if delivered {
+            DeliveryStatus::Delivered
+        } else if attempt == last_attempt {
+            DeliveryStatus::Failed
+        } else {
+            DeliveryStatus::Pending
+        };
+        delivery.status = status.as_str().to_string();
+        delivery.attempts = attempt;
+        delivery.last_status_code = status_code;
+        delivery.last_error = error;
+        delivery.updated_at = Utc::now();
+        if let Err(err) = save_delivery(&session, &delivery).await {
+            eprintln!("Failed to update webhook delivery: {}", err);
+        }
+
+        if status != DeliveryStatus::Pending {
+            let (webhook_id, delivery_id) = (delivery.webhook_id, delivery.delivery_id);
+            if let Err(err) = delete_pending_delivery(&session, webhook_id, delivery_id).await {
+                eprintln!("Failed to unmark pending webhook delivery: {}", err);
+            }
+            return;
+        }
+    }
+}
+
+// Status code and error of a POST of the delivery to the webhook's url
+async fn post_delivery(
+    client: &reqwest::Client,
+    webhook: &Webhook,
+    delivery: &WebhookDelivery,
+) -> (Option<i32>, Option<String>) {
+    let response = client
+        .post(&webhook.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(WEBHOOK_DELIVERY_HEADER, delivery.delivery_id.to_string())
+        .header(
+            WEBHOOK_SIGNATURE_HEADER,
+            sign_payload(&webhook.secret, &delivery.payload),
+        )
+        .body(delivery.payload.clone())
+        .send()
+        .await;
+    match response {
+        Ok(response) if response.status().is_success() => {
+            (Some(response.status().as_u16() as i32), None)
+        }
+        Ok(response) => (
+            Some(response.status().as_u16() as i32),
+            Some(format!("receiver responded with {}", response.status())),
+        ),
+        Err(err) => (None, Some(err.to_string())),
+    }
+}
+
+// Last ledger whose deliveries were all queued
+async fn load_cursor(session: &Session) -> Result<Option<i64>, DataApiError> {
+    let query = format!(
+        "SELECT ledger_index from {} WHERE name=?;",
+        WEBHOOK_DISPATCH_CURSOR_TABLE
+    );
+    println!("Query: {}", query);
+    let query_result = session.query(query, (WEBHOOK_DISPATCH_CURSOR,)).await?;
+
+    // todo: better row error handling
+    Ok(query_result
+        .rows_typed_or_empty::<(i64,)>()
+        .filter_map(|row| row.ok())
+        .map(|(ledger_index,)| ledger_index)
+        .next())
+}
+
+async fn save_cursor(session: &Session, ledger_index: i64) -> Result<(), DataApiError> {
+    let query = format!(
+        "INSERT INTO {} (name, ledger_index, updated_at) VALUES (?, ?, ?);",
+        WEBHOOK_DISPATCH_CURSOR_TABLE
+    );
+    println!("Query: {}", query);
+    session
+        .query(query, (WEBHOOK_DISPATCH_CURSOR, ledger_index, Utc::now()))
+        .await?;
+
+    Ok(())
+}
+
+/// Hex encoded HMAC-SHA256 of the body keyed with the webhook secret, prefixed with `sha256=`.
+fn sign_payload(secret: &str, payload: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(payload.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}